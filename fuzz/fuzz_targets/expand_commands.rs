@@ -0,0 +1,19 @@
+//! `expand_commands` must not panic, must terminate on deeply nested input,
+//! and must not produce empty or untrimmed segments.
+
+#![no_main]
+
+use aca_safety_net::shell::expand_commands;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    for segment in expand_commands(input) {
+        assert!(!segment.command.is_empty(), "input: {:?}", input);
+        assert_eq!(
+            segment.command.trim(),
+            segment.command,
+            "input: {:?}",
+            input
+        );
+    }
+});
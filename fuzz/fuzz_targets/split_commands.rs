@@ -0,0 +1,29 @@
+//! `split_commands` must not panic, must not produce empty or untrimmed
+//! segments, and must not split a segment any further on a second pass.
+
+#![no_main]
+
+use aca_safety_net::shell::split_commands;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    for segment in split_commands(input) {
+        assert!(!segment.command.is_empty(), "input: {:?}", input);
+        assert_eq!(
+            segment.command.trim(),
+            segment.command,
+            "input: {:?}",
+            input
+        );
+
+        let again = split_commands(&segment.command);
+        assert_eq!(
+            again.len(),
+            1,
+            "segment {:?} of {:?}",
+            segment.command,
+            input
+        );
+        assert_eq!(again[0].command, segment.command);
+    }
+});
@@ -0,0 +1,35 @@
+//! `strip_wrappers` must not panic, and `sudo` in front of a command must
+//! not change what it strips down to.
+//!
+//! Stripping stops after a fixed depth, so the wrapped command is only
+//! compared when its result was fully stripped.
+
+#![no_main]
+
+use aca_safety_net::shell::{Token, strip_wrappers, tokenize};
+use libfuzzer_sys::fuzz_target;
+
+fn words(command: &str) -> Vec<String> {
+    tokenize(command)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Word(word) => Some(word),
+            _ => None,
+        })
+        .collect()
+}
+
+fuzz_target!(|input: &str| {
+    let stripped = strip_wrappers(input);
+
+    // sudo reads its own options, so the command must start with a word
+    match tokenize(input).first() {
+        Some(Token::Word(first)) if !first.starts_with('-') => {}
+        _ => return,
+    }
+    let wrapped = strip_wrappers(&format!("sudo {}", input));
+    if strip_wrappers(&wrapped) != wrapped {
+        return;
+    }
+    assert_eq!(words(&wrapped), words(&stripped), "input: {:?}", input);
+});
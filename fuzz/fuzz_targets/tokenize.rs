@@ -0,0 +1,33 @@
+//! `tokenize` must not panic and must split words like a POSIX shell.
+//!
+//! The reference is `shell-words`. Inputs using syntax the tokenizer
+//! handles on purpose in its own way (redirections, assignments, comments,
+//! expansions, Unicode whitespace) are only checked for panics.
+
+#![no_main]
+
+use aca_safety_net::shell::{Token, tokenize};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let tokens = tokenize(input);
+
+    let own_syntax = input.contains(['<', '>', '=', '#', '$', '`'])
+        || input
+            .chars()
+            .any(|c| c.is_whitespace() && !matches!(c, ' ' | '\t' | '\n'));
+    if own_syntax {
+        return;
+    }
+    let Ok(expected) = shell_words::split(input) else {
+        return;
+    };
+    let words: Vec<&str> = tokens
+        .iter()
+        .map(|token| match token {
+            Token::Word(word) => word.as_str(),
+            other => panic!("unexpected token {:?} in {:?}", other, input),
+        })
+        .collect();
+    assert_eq!(words, expected, "input: {:?}", input);
+});
@@ -1,19 +1,24 @@
 //! Edit tool analysis.
 
+use super::apply_allow_rule;
 use crate::config::CompiledConfig;
 use crate::decision::{AskInfo, Decision};
 use crate::input::EditInput;
-use crate::rules::check_custom_rules;
+use crate::rules::{check_content_secrets, check_custom_rules, check_protected_write};
 
 /// Analyze an Edit tool invocation.
 pub fn analyze_edit(input: &EditInput, config: &CompiledConfig) -> Decision {
+    // Explicit allow rules take precedence over deny rules and built-ins
+    let allow_rule = config.matching_allow_rule("Edit", &input.file_path);
+    apply_allow_rule(allow_rule, || check_edit(input, config))
+}
+
+fn check_edit(input: &EditInput, config: &CompiledConfig) -> Decision {
     let path = &input.file_path;
 
     // 1. Check explicit deny rules
-    for (rule, re) in &config.deny_patterns {
-        if rule.tool == "Edit" && re.is_match(path) {
-            return Decision::block(&rule.reason, &rule.reason);
-        }
+    if let Some(rule) = config.matching_deny_rule("Edit", path) {
+        return rule.decision();
     }
 
     // 2. Check custom rules
@@ -22,7 +27,22 @@ pub fn analyze_edit(input: &EditInput, config: &CompiledConfig) -> Decision {
         return custom_decision;
     }
 
-    // 3. Check dependency file patterns (ask for approval)
+    // 3. Writes to .git internals and shell startup files
+    let protected = check_protected_write(path, config);
+    if protected.is_blocked() {
+        return protected;
+    }
+
+    // 4. Secrets in the written content
+    let decision = check_content_secrets(path, &input.new_string, config);
+    if decision.is_blocked() {
+        return decision;
+    }
+    if protected.is_ask() {
+        return protected;
+    }
+
+    // 5. Check dependency file patterns (ask for approval)
     if config.is_dependency_file(path) {
         let mut ask = AskInfo::new(
             "dependencies.edit",
@@ -34,7 +54,7 @@ pub fn analyze_edit(input: &EditInput, config: &CompiledConfig) -> Decision {
         return Decision::Ask(ask);
     }
 
-    Decision::allow()
+    custom_decision
 }
 
 #[cfg(test)]
@@ -59,6 +79,7 @@ mod tests {
             file_path: "Cargo.toml".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(decision.is_ask());
@@ -71,6 +92,7 @@ mod tests {
             file_path: "/home/user/project/Cargo.toml".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(decision.is_ask());
@@ -83,6 +105,7 @@ mod tests {
             file_path: "package.json".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(decision.is_ask());
@@ -95,6 +118,7 @@ mod tests {
             file_path: "pyproject.toml".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(decision.is_ask());
@@ -107,6 +131,7 @@ mod tests {
             file_path: "requirements.txt".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(decision.is_ask());
@@ -119,6 +144,7 @@ mod tests {
             file_path: "go.mod".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(decision.is_ask());
@@ -131,6 +157,7 @@ mod tests {
             file_path: "Gemfile".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(decision.is_ask());
@@ -143,11 +170,25 @@ mod tests {
             file_path: "src/main.rs".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(!decision.is_blocked() && !decision.is_ask());
     }
 
+    #[test]
+    fn test_edit_protectedfiles_blocked() {
+        let config = test_config();
+        let input = EditInput {
+            file_path: ".protectedfiles".to_string(),
+            old_string: "secrets/".to_string(),
+            new_string: String::new(),
+            replace_all: false,
+        };
+        let decision = analyze_edit(&input, &config);
+        assert_eq!(decision.rule(), Some("self_protection.hook_config"));
+    }
+
     #[test]
     fn test_edit_deps_disabled_allows() {
         let config = config_with_deps_disabled();
@@ -155,6 +196,7 @@ mod tests {
             file_path: "Cargo.toml".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         assert!(!decision.is_blocked() && !decision.is_ask());
@@ -167,6 +209,7 @@ mod tests {
             file_path: "Cargo.toml".to_string(),
             old_string: "old".to_string(),
             new_string: "new".to_string(),
+            replace_all: false,
         };
         let decision = analyze_edit(&input, &config);
         if let Decision::Ask(info) = decision {
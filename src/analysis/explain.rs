@@ -0,0 +1,345 @@
+//! Evaluation trace for a Bash command, for `aca-safety-net explain`.
+//!
+//! Every check `analyze_bash` can run is evaluated on its own, so the trace
+//! also shows rules that matched after an earlier block. The exceptions and
+//! severities applied afterwards are traced too, and the final decision
+//! always comes from the hook's full pipeline, `analyze_tool`.
+
+use crate::config::{CompiledConfig, DenyRule, EnforcementMode};
+use crate::decision::Decision;
+use crate::input::{BashInput, HookInput};
+use crate::rules::{
+    analyze_builtin, analyze_gcloud_raw, analyze_kubectl_raw, check_allowlist, check_blocked_hosts,
+    check_custom_rule, check_encoded_execution, check_inline_secrets, check_pipe_to_shell,
+    check_protected_write_tokens, check_sensitive_env, check_sensitive_glob, check_sensitive_path,
+};
+use crate::shell::{Operator, Token, expand_commands, strip_wrappers, tokenize};
+
+use super::{analyze_bash, analyze_tool};
+
+/// How one segment of the command was parsed.
+#[derive(Debug, Clone)]
+pub struct SegmentTrace {
+    /// Segment text as split from the command line.
+    pub command: String,
+    /// Segment after removing wrappers such as `sudo` and `env`.
+    pub stripped: String,
+    /// Tokens of the stripped segment.
+    pub tokens: Vec<Token>,
+    /// Operator that follows the segment.
+    pub operator: Option<Operator>,
+}
+
+/// One rule that was evaluated.
+#[derive(Debug, Clone)]
+pub struct RuleTrace {
+    /// Which check the rule belongs to (`deny`, `custom`, `builtin`, ...).
+    pub stage: &'static str,
+    /// The rule pattern, name, or the word it was applied to.
+    pub name: String,
+    /// The decision when the rule matched, `None` when it did not.
+    pub outcome: Option<Decision>,
+}
+
+/// Full evaluation trace of a command.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub segments: Vec<SegmentTrace>,
+    pub rules: Vec<RuleTrace>,
+    pub decision: Decision,
+}
+
+/// Evaluate every check for a Bash command and record the results.
+pub fn explain_bash(command: &str, config: &CompiledConfig, cwd: Option<&str>) -> Explanation {
+    let input = HookInput::for_tool("Bash", command, cwd.map(String::from))
+        .expect("Bash calls can be simulated");
+    let full_config = config;
+    let scoped = config.scoped_to(&input.locations());
+    let config = scoped.as_ref().unwrap_or(config);
+
+    let segments: Vec<SegmentTrace> = expand_commands(command)
+        .into_iter()
+        .map(|segment| {
+            let stripped = strip_wrappers(&segment.command);
+            SegmentTrace {
+                tokens: tokenize(&stripped),
+                command: segment.command,
+                stripped,
+                operator: segment.operator,
+            }
+        })
+        .collect();
+
+    let mut rules = Vec::new();
+    let mut record = |stage, name: &str, outcome: Option<Decision>| {
+        rules.push(RuleTrace {
+            stage,
+            name: name.to_string(),
+            outcome,
+        });
+    };
+
+    if config.raw.mode == EnforcementMode::Allowlist {
+        record(
+            "allowlist",
+            "allowed_commands",
+            matched(check_allowlist(command, config)),
+        );
+    }
+
+    // A Bash allow rule applies when every segment matches an allow rule
+    for rule in config.raw.allow.iter().filter(|rule| rule.tool == "Bash") {
+        let outcome = segments
+            .iter()
+            .any(|segment| {
+                config
+                    .matching_allow_rule("Bash", segment.command.trim())
+                    .is_some_and(|hit| std::ptr::eq(hit, rule))
+            })
+            .then(Decision::allow);
+        record("allow", &rule.pattern, outcome);
+    }
+
+    let denied: Vec<&DenyRule> = config.matching_deny_rules("Bash", command).collect();
+    for rule in config.raw.deny.iter().filter(|rule| rule.tool == "Bash") {
+        let outcome = denied
+            .iter()
+            .any(|hit| std::ptr::eq(*hit, rule))
+            .then(|| rule.decision());
+        record("deny", &rule.pattern, outcome);
+    }
+
+    for rule in config.raw.rules.iter().filter(|rule| rule.tool == "Bash") {
+        record(
+            "custom",
+            &rule.name,
+            check_custom_rule(rule, "Bash", command),
+        );
+    }
+
+    if config.raw.paranoid.enabled {
+        let outcome = config.matches_paranoid(command).map(|pattern| {
+            Decision::block(
+                "paranoid.sensitive_mention",
+                format!("command mentions sensitive pattern '{}'", pattern),
+            )
+        });
+        record("paranoid", "sensitive mention", outcome);
+    }
+
+    if config.raw.secrets.block_inline {
+        record(
+            "secrets",
+            "inline secret",
+            matched(check_inline_secrets(command, config)),
+        );
+    }
+    record(
+        "secrets",
+        "sensitive variable",
+        matched(check_sensitive_env(command, config)),
+    );
+    record(
+        "network",
+        "blocked host",
+        matched(check_blocked_hosts(command, config)),
+    );
+
+    record(
+        "obfuscation",
+        "encoded execution",
+        matched(check_encoded_execution(&expand_commands(command))),
+    );
+    record(
+        "network",
+        "pipe to shell",
+        matched(check_pipe_to_shell(&expand_commands(command))),
+    );
+
+    record("builtin", "kubectl", matched(analyze_kubectl_raw(command)));
+    record(
+        "builtin",
+        "gcloud (raw)",
+        matched(analyze_gcloud_raw(command)),
+    );
+
+    for segment in &segments {
+        let words = words(&segment.tokens);
+        let Some(cmd_name) = words.iter().copied().find(|w| !w.starts_with('-')) else {
+            continue;
+        };
+
+        if config.is_read_command(cmd_name) {
+            let args = words.iter().skip_while(|w| **w != cmd_name).skip(1);
+            for word in args.filter(|w| !w.starts_with('-')) {
+                let name = format!("{} {}", cmd_name, word);
+                let outcome = matched(check_sensitive_path(word, config))
+                    .or_else(|| matched(check_sensitive_glob(word, config, cwd)));
+                record("sensitive", &name, outcome);
+            }
+        }
+
+        if words.len() >= 2 && words[0] == "git" && words[1] == "add" {
+            for path in words[2..].iter().filter(|w| !w.starts_with('-')) {
+                let outcome = matched(check_sensitive_path(path, config)).map(|_| {
+                    Decision::block(
+                        "git.add.sensitive",
+                        format!("git add on sensitive file: {}", path),
+                    )
+                });
+                record("git add", path, outcome);
+            }
+        }
+
+        record(
+            "protected path",
+            &segment.stripped,
+            matched(check_protected_write_tokens(&segment.tokens, config)),
+        );
+
+        if let Some(first) = words.first()
+            && let Some(decision) = analyze_builtin(first, &segment.tokens, config, cwd)
+        {
+            record("builtin", first, matched(decision));
+        }
+    }
+
+    let bash_input = BashInput {
+        command: command.to_string(),
+        timeout: None,
+        description: None,
+    };
+    let builtin = analyze_bash(&bash_input, config, cwd);
+    if config.raw.rewrite.enabled {
+        let outcome = builtin.is_rewrite().then(|| builtin.clone());
+        record("rewrite", "safer command", outcome);
+    }
+    if let Some(rule) = builtin.rule()
+        && let Some(exception) = config.matching_exception(rule, command)
+    {
+        let name = format!("{} until {}", rule, exception.expires.format("%Y-%m-%d"));
+        record("exception", &name, Some(Decision::allow()));
+    } else if let Some(rule) = builtin.rule() {
+        let adjusted = config.raw.severity.apply(builtin.clone());
+        let overridden = config.raw.apply_overrides(adjusted.clone());
+        let outcome = (overridden.label() != adjusted.label()).then_some(overridden);
+        let adjusted = (adjusted.label() != builtin.label()).then_some(adjusted);
+        record("severity", rule, adjusted);
+        record("override", rule, outcome);
+    }
+
+    // Validators and plugins are only consulted here
+    let decision = analyze_tool(&input, full_config);
+
+    Explanation {
+        segments,
+        rules,
+        decision,
+    }
+}
+
+/// `Some(decision)` unless the decision is a plain allow.
+fn matched(decision: Decision) -> Option<Decision> {
+    (!matches!(decision, Decision::Allow)).then_some(decision)
+}
+
+fn words(tokens: &[Token]) -> Vec<&str> {
+    tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_explain_segments() {
+        let config = Config::default().compile().unwrap();
+        let explanation = explain_bash("sudo cat notes.txt && ls", &config, None);
+        assert_eq!(explanation.segments.len(), 2);
+        assert_eq!(explanation.segments[0].stripped, "cat notes.txt");
+        assert_eq!(explanation.segments[0].operator, Some(Operator::And));
+        assert_eq!(
+            explanation.segments[1].tokens,
+            vec![Token::Word("ls".to_string())]
+        );
+        assert!(!explanation.decision.is_blocked());
+    }
+
+    #[test]
+    fn test_explain_records_match() {
+        let config = Config::default().compile().unwrap();
+        let explanation = explain_bash("cat .env", &config, None);
+        assert!(explanation.decision.is_blocked());
+        let hit = explanation
+            .rules
+            .iter()
+            .find(|rule| rule.outcome.is_some())
+            .unwrap();
+        assert_eq!(hit.stage, "sensitive");
+        assert_eq!(hit.name, "cat .env");
+        assert!(
+            explanation
+                .rules
+                .iter()
+                .any(|rule| rule.stage == "deny" && rule.outcome.is_none())
+        );
+    }
+
+    #[test]
+    fn test_explain_follows_full_pipeline() {
+        let config: Config = toml::from_str(
+            r#"
+[[allow]]
+tool = "Bash"
+pattern = '^git clean -n'
+
+[[exceptions]]
+rule = "git.push.force"
+pattern = 'origin main$'
+expires = "2999-01-01T00:00:00Z"
+
+[severity]
+min_block = "critical"
+
+[severity.rules]
+"git.reset.hard" = "warn"
+
+[overrides]
+"git.clean" = "warn"
+"#,
+        )
+        .unwrap();
+        let config = config.compile().unwrap();
+        let stage = |explanation: &Explanation, stage: &str| {
+            explanation
+                .rules
+                .iter()
+                .find(|rule| rule.stage == stage)
+                .and_then(|rule| rule.outcome.clone())
+        };
+
+        let explanation = explain_bash("git clean -n", &config, None);
+        assert!(stage(&explanation, "allow").is_some());
+
+        let explanation = explain_bash("git push --force origin main", &config, None);
+        assert!(stage(&explanation, "exception").is_some());
+        assert!(matches!(explanation.decision, Decision::Allow));
+
+        let explanation = explain_bash("git reset --hard", &config, None);
+        let adjusted = stage(&explanation, "severity").unwrap();
+        assert_eq!(adjusted.label(), "ask");
+        assert_eq!(explanation.decision.label(), "ask");
+
+        let explanation = explain_bash("git clean -f", &config, None);
+        let overridden = stage(&explanation, "override").unwrap();
+        assert_eq!(overridden.rule(), Some("git.clean"));
+        assert_eq!(explanation.decision.label(), "warn");
+    }
+}
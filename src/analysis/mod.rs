@@ -2,10 +2,132 @@
 
 mod bash;
 mod edit;
+mod explain;
 mod read;
+mod search;
+mod task;
+mod unparseable;
+mod web_fetch;
 mod write;
 
 pub use bash::analyze_bash;
 pub use edit::analyze_edit;
+pub use explain::{Explanation, RuleTrace, SegmentTrace, explain_bash};
 pub use read::analyze_read;
+pub use search::{analyze_glob, analyze_grep};
+pub use task::analyze_task;
+pub use unparseable::analyze_unparseable;
+pub use web_fetch::analyze_web_fetch;
 pub use write::analyze_write;
+
+use crate::config::{AllowRule, CompiledConfig};
+use crate::decision::Decision;
+use crate::input::HookInput;
+use crate::validator::run_validators;
+
+/// Analyze a tool call by dispatching on the tool name, then consult any
+/// external validators and WebAssembly plugins until something blocks.
+/// The configured severities decide last whether a finding blocks or asks.
+///
+/// Rules limited to `paths` the call does not reach are left out, and a
+/// finding with an unexpired `[[exceptions]]` entry is allowed.
+///
+/// Tools without an analyzer are allowed.
+pub fn analyze_tool(input: &HookInput, config: &CompiledConfig) -> Decision {
+    let _span = tracing::debug_span!("analyze", tool = %input.tool_name).entered();
+    let scoped = config.scoped_to(&input.locations());
+    let config = scoped.as_ref().unwrap_or(config);
+    // Exceptions, severities and overrides apply to each violation on its
+    // own, so an ask next to an allowed block still asks; the first block
+    // left decides
+    let violations = analyze_builtin_tool(input, config).into_violations();
+    let mut decision = Decision::from_violations(violations.into_iter().filter(|violation| {
+        let Some(rule) = violation.rule() else {
+            return true;
+        };
+        let exception = config.matching_exception(rule, input.target().unwrap_or_default());
+        if let Some(exception) = exception {
+            tracing::debug!(rule, expires = %exception.expires, "rule excepted");
+        }
+        exception.is_none()
+    }));
+    if !decision.is_blocked()
+        && let Some(validator_decision) = run_validators(input, config)
+    {
+        decision = decision.stricter(validator_decision);
+    }
+    #[cfg(feature = "wasm")]
+    if !decision.is_blocked()
+        && let Some(plugin_decision) = crate::plugin::analyze_plugins(input, config)
+    {
+        decision = decision.stricter(plugin_decision);
+    }
+    let decision = Decision::from_violations(
+        decision
+            .into_violations()
+            .into_iter()
+            .map(|violation| config.raw.severity.apply(violation))
+            .map(|violation| config.raw.apply_overrides(violation)),
+    );
+    tracing::debug!(decision = decision.label(), rule = ?decision.rule(), "analyzed");
+    decision
+}
+
+/// Decide a call with `check`, unless the allow rule matching it says
+/// otherwise. A user or policy allow rule skips every check; a project
+/// allow rule only lifts asks and warnings, so a repository cannot allow
+/// what a deny rule or a built-in check blocks.
+fn apply_allow_rule(rule: Option<&AllowRule>, check: impl FnOnce() -> Decision) -> Decision {
+    match rule {
+        None => check(),
+        Some(rule) if !rule.project => Decision::allow(),
+        Some(_) => {
+            let decision = check();
+            if decision.is_ask() || decision.is_warn() {
+                Decision::allow()
+            } else {
+                decision
+            }
+        }
+    }
+}
+
+fn analyze_builtin_tool(input: &HookInput, config: &CompiledConfig) -> Decision {
+    let tool = input.tool_name.as_str();
+    match tool {
+        "Bash" => match input.try_as_bash() {
+            Ok(bash_input) => analyze_bash(&bash_input, config, input.cwd.as_deref()),
+            Err(e) => analyze_unparseable(tool, &e, config),
+        },
+        "Read" => match input.try_as_read() {
+            Ok(read_input) => analyze_read(&read_input, config),
+            Err(e) => analyze_unparseable(tool, &e, config),
+        },
+        "Edit" => match input.try_as_edit() {
+            Ok(edit_input) => analyze_edit(&edit_input, config),
+            Err(e) => analyze_unparseable(tool, &e, config),
+        },
+        "Write" => match input.try_as_write() {
+            Ok(write_input) => analyze_write(&write_input, config),
+            Err(e) => analyze_unparseable(tool, &e, config),
+        },
+        "Glob" => match input.try_as_glob() {
+            Ok(glob_input) => analyze_glob(&glob_input, config, input.cwd.as_deref()),
+            Err(e) => analyze_unparseable(tool, &e, config),
+        },
+        "Grep" => match input.try_as_grep() {
+            Ok(grep_input) => analyze_grep(&grep_input, config, input.cwd.as_deref()),
+            Err(e) => analyze_unparseable(tool, &e, config),
+        },
+        "Task" => match input.try_as_task() {
+            Ok(task_input) => analyze_task(&task_input, &input.tool_input, config),
+            Err(e) => analyze_unparseable(tool, &e, config),
+        },
+        "WebFetch" => match input.try_as_web_fetch() {
+            Ok(web_fetch_input) => analyze_web_fetch(&web_fetch_input, config),
+            Err(e) => analyze_unparseable(tool, &e, config),
+        },
+        // Other tools pass through
+        _ => Decision::allow(),
+    }
+}
@@ -1,5 +1,6 @@
 //! Read tool analysis.
 
+use super::apply_allow_rule;
 use crate::config::CompiledConfig;
 use crate::decision::Decision;
 use crate::input::ReadInput;
@@ -7,13 +8,17 @@ use crate::rules::{check_custom_rules, check_sensitive_path};
 
 /// Analyze a Read tool invocation.
 pub fn analyze_read(input: &ReadInput, config: &CompiledConfig) -> Decision {
+    // Explicit allow rules take precedence over deny rules and built-ins
+    let allow_rule = config.matching_allow_rule("Read", &input.file_path);
+    apply_allow_rule(allow_rule, || check_read(input, config))
+}
+
+fn check_read(input: &ReadInput, config: &CompiledConfig) -> Decision {
     let path = &input.file_path;
 
     // 1. Check explicit deny rules
-    for (rule, re) in &config.deny_patterns {
-        if rule.tool == "Read" && re.is_match(path) {
-            return Decision::block(&rule.reason, &rule.reason);
-        }
+    if let Some(rule) = config.matching_deny_rule("Read", path) {
+        return rule.decision();
     }
 
     // 2. Check custom rules
@@ -31,13 +36,13 @@ pub fn analyze_read(input: &ReadInput, config: &CompiledConfig) -> Decision {
     }
 
     // 4. Check sensitive file patterns
-    check_sensitive_path(path, config)
+    check_sensitive_path(path, config).and_also(custom_decision)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, DenyRule};
+    use crate::config::{AllowRule, Config, DenyRule};
 
     fn test_config() -> CompiledConfig {
         Config {
@@ -51,6 +56,9 @@ mod tests {
                 tool: "Read".to_string(),
                 pattern: r"/etc/shadow".to_string(),
                 reason: "Cannot read shadow file".to_string(),
+                severity: None,
+                paths: vec![],
+                policy: false,
             }],
             ..Default::default()
         }
@@ -130,6 +138,30 @@ mod tests {
         assert!(decision.is_blocked());
     }
 
+    #[test]
+    fn test_allow_rule_overrides_block() {
+        let mut config = Config::default();
+        config.allow.push(crate::config::AllowRule {
+            tool: "Read".to_string(),
+            pattern: r"(^|/)\.env\.test$".to_string(),
+            reason: Some("test fixtures only".to_string()),
+            paths: vec![],
+            project: false,
+            policy: false,
+        });
+        let config = config.compile().unwrap();
+        let read = |path: &str| ReadInput {
+            file_path: path.to_string(),
+            offset: None,
+            limit: None,
+        };
+        assert!(matches!(
+            analyze_read(&read("config/.env.test"), &config),
+            Decision::Allow
+        ));
+        assert!(analyze_read(&read("config/.env"), &config).is_blocked());
+    }
+
     #[test]
     fn test_read_normal_file() {
         let config = test_config();
@@ -165,4 +197,28 @@ mod tests {
         let decision = analyze_read(&input, &config);
         assert!(!decision.is_blocked()); // .env\b pattern shouldn't match
     }
+
+    #[test]
+    fn test_project_allow_rule_does_not_lift_block() {
+        let mut config = test_config().raw;
+        config.allow.push(AllowRule {
+            tool: "Read".to_string(),
+            pattern: r"\.env$".to_string(),
+            reason: None,
+            paths: vec![],
+            project: true,
+            policy: false,
+        });
+        let input = ReadInput {
+            file_path: "/project/.env".to_string(),
+            offset: None,
+            limit: None,
+        };
+        let compiled = config.clone().compile().unwrap();
+        assert!(analyze_read(&input, &compiled).is_blocked());
+
+        config.allow[0].project = false;
+        let compiled = config.compile().unwrap();
+        assert!(matches!(analyze_read(&input, &compiled), Decision::Allow));
+    }
 }
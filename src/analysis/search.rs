@@ -0,0 +1,266 @@
+//! Glob and Grep tool analysis.
+//!
+//! Neither tool reads a file the way Read does, but listing `~/.ssh/**` or
+//! grepping a tree for `AKIA` shows where the secrets are and, with Grep's
+//! content mode, what they are.
+
+use super::apply_allow_rule;
+use crate::config::{AllowRule, CompiledConfig};
+use crate::decision::Decision;
+use crate::input::{GlobInput, GrepInput};
+use crate::rules::{
+    check_custom_rules, check_sensitive_glob, check_sensitive_path, is_sensitive_dir,
+};
+
+/// Analyze a Glob tool invocation.
+pub fn analyze_glob(input: &GlobInput, config: &CompiledConfig, cwd: Option<&str>) -> Decision {
+    let target = join(input.path.as_deref(), &input.pattern);
+    check_search("Glob", &[target.as_str()], config, cwd)
+}
+
+/// Analyze a Grep tool invocation.
+pub fn analyze_grep(input: &GrepInput, config: &CompiledConfig, cwd: Option<&str>) -> Decision {
+    let path = input.path.as_deref().unwrap_or(".");
+    let mut targets = vec![path.to_string()];
+    if let Some(glob) = &input.glob {
+        targets.push(join(Some(path), glob));
+    }
+    let targets: Vec<&str> = targets.iter().map(String::as_str).collect();
+    let decision = check_search("Grep", &targets, config, cwd);
+    if decision.is_blocked() {
+        return decision;
+    }
+
+    if config.raw.search.block_secret_hunting
+        && let Some(pattern) = config.matches_secret_hunting(&input.pattern)
+    {
+        return Decision::block(
+            "search.secret_hunting",
+            format!(
+                "grep pattern '{}' looks for secrets (matches '{}')",
+                input.pattern, pattern
+            ),
+        );
+    }
+    decision
+}
+
+/// Check the paths a search reaches, most specific rules first.
+fn check_search(
+    tool: &str,
+    targets: &[&str],
+    config: &CompiledConfig,
+    cwd: Option<&str>,
+) -> Decision {
+    // Explicit allow rules take precedence over deny rules and built-ins.
+    // Paths only a project allow rule matches are still checked, and it
+    // lifts asks and warnings when every path has an allow rule.
+    let rules: Vec<Option<&AllowRule>> = targets
+        .iter()
+        .map(|target| config.matching_allow_rule(tool, target))
+        .collect();
+    let checked: Vec<&str> = targets
+        .iter()
+        .zip(&rules)
+        .filter(|(_, rule)| rule.is_none_or(|rule| rule.project))
+        .map(|(target, _)| *target)
+        .collect();
+    let project_rule = rules
+        .iter()
+        .flatten()
+        .find(|rule| rule.project)
+        .copied()
+        .filter(|_| rules.iter().all(Option::is_some));
+    apply_allow_rule(project_rule, || check_targets(tool, &checked, config, cwd))
+}
+
+/// Check the paths no user or policy allow rule matches.
+fn check_targets(
+    tool: &str,
+    targets: &[&str],
+    config: &CompiledConfig,
+    cwd: Option<&str>,
+) -> Decision {
+    let mut custom_decision = Decision::allow();
+    for target in targets {
+        // 1. Check explicit deny rules
+        if let Some(rule) = config.matching_deny_rule(tool, target) {
+            return rule.decision();
+        }
+
+        // 2. Check custom rules
+        let decision = check_custom_rules(tool, target, config);
+        if decision.is_blocked() {
+            return decision;
+        }
+        custom_decision = custom_decision.stricter(decision);
+
+        // 3. Paranoid mode check
+        if let Some(pattern) = config.matches_paranoid(target) {
+            return Decision::block(
+                "paranoid.sensitive_file",
+                format!("search path matches sensitive pattern '{}'", pattern),
+            );
+        }
+    }
+
+    if !config.raw.search.block_sensitive_paths {
+        return custom_decision;
+    }
+    for target in targets {
+        // 4. Credential directories anywhere along the path
+        if let Some(dir) = sensitive_dir_prefix(target) {
+            return Decision::block(
+                "search.sensitive_dir",
+                format!(
+                    "{} '{}' searches sensitive directory '{}'",
+                    tool, target, dir
+                ),
+            );
+        }
+
+        // 5. Sensitive files, named or matched by the glob
+        let decision = check_sensitive_path(target, config);
+        if decision.is_blocked() {
+            return decision;
+        }
+        let decision = check_sensitive_glob(target, config, cwd);
+        if decision.is_blocked() {
+            return decision;
+        }
+    }
+    custom_decision
+}
+
+/// The first leading part of `path` that is a credential directory.
+fn sensitive_dir_prefix(path: &str) -> Option<&str> {
+    path.match_indices('/')
+        .map(|(i, _)| &path[..i])
+        .chain([path])
+        .find(|prefix| is_sensitive_dir(prefix))
+}
+
+/// `pattern` under `dir`, unless it is absolute or there is no `dir`.
+fn join(dir: Option<&str>, pattern: &str) -> String {
+    match dir {
+        Some(dir) if !pattern.starts_with('/') && !pattern.starts_with('~') => {
+            format!("{}/{}", dir.trim_end_matches('/'), pattern)
+        }
+        _ => pattern.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn glob(pattern: &str, path: Option<&str>) -> Decision {
+        let input = GlobInput {
+            pattern: pattern.to_string(),
+            path: path.map(String::from),
+        };
+        analyze_glob(&input, &Config::default().compile().unwrap(), None)
+    }
+
+    fn grep(pattern: &str, path: Option<&str>, glob: Option<&str>) -> Decision {
+        let input = GrepInput {
+            pattern: pattern.to_string(),
+            path: path.map(String::from),
+            glob: glob.map(String::from),
+        };
+        analyze_grep(&input, &Config::default().compile().unwrap(), None)
+    }
+
+    #[test]
+    fn test_sensitive_globs_blocked() {
+        for (pattern, path) in [
+            ("**/.env*", None),
+            ("~/.ssh/**", None),
+            ("*", Some("/home/user/.aws")),
+            ("**/*", Some("~/.ssh")),
+            ("**/.ssh/id_*", None),
+            ("**/*.pem", Some("/srv")),
+        ] {
+            assert!(
+                glob(pattern, path).is_blocked(),
+                "{} in {:?}",
+                pattern,
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_source_globs_allowed() {
+        for (pattern, path) in [
+            ("**/*.rs", None),
+            ("src/**/*.ts", Some("/home/user/project")),
+            ("**/.env.example", None),
+            ("**/environment*.ts", None),
+        ] {
+            assert!(
+                matches!(glob(pattern, path), Decision::Allow),
+                "{} in {:?}",
+                pattern,
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_sensitive_grep_paths_blocked() {
+        let decision = grep("Host", Some("~/.ssh"), None);
+        assert_eq!(decision.rule(), Some("search.sensitive_dir"));
+        assert!(grep("DB_HOST", Some("config/.env"), None).is_blocked());
+        assert!(grep("DB_HOST", None, Some(".env*")).is_blocked());
+    }
+
+    #[test]
+    fn test_secret_hunting_blocked() {
+        for pattern in [
+            "password=",
+            r"password\s*[=:]",
+            "\"api_key\":",
+            "AKIA[0-9A-Z]{16}",
+            "BEGIN RSA PRIVATE KEY",
+            "ghp_[A-Za-z0-9]+",
+        ] {
+            let decision = grep(pattern, None, None);
+            assert_eq!(
+                decision.rule(),
+                Some("search.secret_hunting"),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn test_code_search_allowed() {
+        for pattern in ["fn analyze_", "password", "api_key", "TODO|FIXME"] {
+            assert!(
+                matches!(grep(pattern, Some("src"), Some("*.rs")), Decision::Allow),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_checks_can_be_disabled() {
+        let mut config = Config::default();
+        config.search.block_sensitive_paths = false;
+        config.search.block_secret_hunting = false;
+        let config = config.compile().unwrap();
+        let input = GrepInput {
+            pattern: "password=".to_string(),
+            path: Some("~/.aws".to_string()),
+            glob: None,
+        };
+        assert!(matches!(
+            analyze_grep(&input, &config, None),
+            Decision::Allow
+        ));
+    }
+}
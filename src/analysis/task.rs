@@ -0,0 +1,125 @@
+//! Task tool analysis.
+//!
+//! A subagent works from its prompt alone, so a secret or a sensitive path
+//! handed over in the prompt leaves the parent's Bash and Read checks
+//! behind. The prompt is screened, and can carry a policy reminder.
+
+use super::apply_allow_rule;
+use crate::config::CompiledConfig;
+use crate::decision::{Decision, RewriteInfo};
+use crate::input::TaskInput;
+use crate::rules::{check_custom_rules, check_prompt_secrets};
+
+/// Analyze a Task tool invocation. `tool_input` is the raw input, which a
+/// policy reminder is added to.
+pub fn analyze_task(
+    input: &TaskInput,
+    tool_input: &serde_json::Value,
+    config: &CompiledConfig,
+) -> Decision {
+    // Explicit allow rules take precedence over deny rules and built-ins
+    let allow_rule = config.matching_allow_rule("Task", &input.prompt);
+    apply_allow_rule(allow_rule, || check_task(input, tool_input, config))
+}
+
+fn check_task(
+    input: &TaskInput,
+    tool_input: &serde_json::Value,
+    config: &CompiledConfig,
+) -> Decision {
+    let prompt = &input.prompt;
+
+    // 1. Check explicit deny rules
+    if let Some(rule) = config.matching_deny_rule("Task", prompt) {
+        return rule.decision();
+    }
+
+    // 2. Check custom rules
+    let custom_decision = check_custom_rules("Task", prompt, config);
+    if custom_decision.is_blocked() {
+        return custom_decision;
+    }
+
+    // 3. Paranoid mode check
+    if let Some(pattern) = config.matches_paranoid(prompt) {
+        return Decision::block(
+            "paranoid.sensitive_file",
+            format!("subagent prompt matches sensitive pattern '{}'", pattern),
+        );
+    }
+
+    // 4. Literal secrets
+    let decision = check_prompt_secrets(prompt, config);
+    if decision.is_blocked() {
+        return decision;
+    }
+
+    // 5. Policy reminder
+    match &config.raw.task.policy_reminder {
+        Some(reminder) if !prompt.contains(reminder.as_str()) => {
+            let mut updated_input = tool_input.clone();
+            updated_input["prompt"] = format!("{}\n\n{}", prompt, reminder).into();
+            Decision::Rewrite(RewriteInfo::new(
+                "task.policy_reminder",
+                "adds the project's policy reminder to the subagent prompt",
+                updated_input,
+            ))
+            .stricter(custom_decision)
+        }
+        _ => custom_decision,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn task(prompt: &str, config: Config) -> Decision {
+        let input = TaskInput {
+            prompt: prompt.to_string(),
+            description: Some("test".to_string()),
+            subagent_type: None,
+        };
+        let tool_input = serde_json::json!({ "prompt": prompt, "description": "test" });
+        analyze_task(&input, &tool_input, &config.compile().unwrap())
+    }
+
+    #[test]
+    fn test_prompt_secrets_blocked() {
+        let prompt = "Call the API with sk-ant-REDACTED";
+        assert!(task(prompt, Config::default()).is_blocked());
+        assert!(matches!(
+            task("Refactor the config loader", Config::default()),
+            Decision::Allow
+        ));
+    }
+
+    #[test]
+    fn test_paranoid_prompt_blocked() {
+        let mut config = Config::default();
+        config.paranoid.enabled = true;
+        let decision = task("Summarize what is in ~/.aws/credentials", config);
+        assert_eq!(decision.rule(), Some("paranoid.sensitive_file"));
+        assert!(matches!(
+            task("Summarize ~/.aws/credentials", Config::default()),
+            Decision::Allow
+        ));
+    }
+
+    #[test]
+    fn test_policy_reminder_added() {
+        let mut config = Config::default();
+        config.task.policy_reminder = Some("Never read .env files.".to_string());
+        let decision = task("Fix the failing test", config.clone());
+        let info = decision.rewrite_info().unwrap();
+        assert_eq!(
+            info.updated_input["prompt"],
+            "Fix the failing test\n\nNever read .env files."
+        );
+        assert_eq!(info.updated_input["description"], "test");
+
+        let prompt = "Fix the failing test\n\nNever read .env files.";
+        assert!(matches!(task(prompt, config), Decision::Allow));
+    }
+}
@@ -0,0 +1,50 @@
+//! Handling of recognized tools whose input could not be parsed.
+
+use crate::config::{CompiledConfig, UnparseableAction};
+use crate::decision::Decision;
+use crate::input::InputError;
+
+/// Decide what to do with a recognized tool whose input failed to parse.
+pub fn analyze_unparseable(tool: &str, error: &InputError, config: &CompiledConfig) -> Decision {
+    let reason = format!("could not parse {} tool input: {}", tool, error);
+    match config.raw.input.on_unparseable {
+        UnparseableAction::Allow => Decision::allow(),
+        UnparseableAction::Ask => Decision::ask("input.unparseable", reason),
+        UnparseableAction::Block => Decision::block("input.unparseable", reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn config_with(action: UnparseableAction) -> CompiledConfig {
+        let mut config = Config::default();
+        config.input.on_unparseable = action;
+        config.compile().unwrap()
+    }
+
+    #[test]
+    fn test_default_allows() {
+        let config = Config::default().compile().unwrap();
+        let decision = analyze_unparseable("Bash", &InputError::MissingField("command"), &config);
+        assert!(!decision.is_blocked() && !decision.is_ask());
+    }
+
+    #[test]
+    fn test_block() {
+        let config = config_with(UnparseableAction::Block);
+        let decision = analyze_unparseable("Bash", &InputError::MissingField("command"), &config);
+        let info = decision.block_info().unwrap();
+        assert_eq!(info.rule, "input.unparseable");
+        assert!(info.reason.contains("command"));
+    }
+
+    #[test]
+    fn test_ask() {
+        let config = config_with(UnparseableAction::Ask);
+        let decision = analyze_unparseable("Read", &InputError::MissingField("file_path"), &config);
+        assert!(decision.is_ask());
+    }
+}
@@ -1,19 +1,24 @@
 //! Write tool analysis.
 
+use super::apply_allow_rule;
 use crate::config::CompiledConfig;
 use crate::decision::{AskInfo, Decision};
 use crate::input::WriteInput;
-use crate::rules::check_custom_rules;
+use crate::rules::{check_content_secrets, check_custom_rules, check_protected_write};
 
 /// Analyze a Write tool invocation.
 pub fn analyze_write(input: &WriteInput, config: &CompiledConfig) -> Decision {
+    // Explicit allow rules take precedence over deny rules and built-ins
+    let allow_rule = config.matching_allow_rule("Write", &input.file_path);
+    apply_allow_rule(allow_rule, || check_write(input, config))
+}
+
+fn check_write(input: &WriteInput, config: &CompiledConfig) -> Decision {
     let path = &input.file_path;
 
     // 1. Check explicit deny rules
-    for (rule, re) in &config.deny_patterns {
-        if rule.tool == "Write" && re.is_match(path) {
-            return Decision::block(&rule.reason, &rule.reason);
-        }
+    if let Some(rule) = config.matching_deny_rule("Write", path) {
+        return rule.decision();
     }
 
     // 2. Check custom rules
@@ -22,7 +27,22 @@ pub fn analyze_write(input: &WriteInput, config: &CompiledConfig) -> Decision {
         return custom_decision;
     }
 
-    // 3. Check dependency file patterns (ask for approval)
+    // 3. Writes to .git internals and shell startup files
+    let protected = check_protected_write(path, config);
+    if protected.is_blocked() {
+        return protected;
+    }
+
+    // 4. Secrets in the written content
+    let decision = check_content_secrets(path, &input.content, config);
+    if decision.is_blocked() {
+        return decision;
+    }
+    if protected.is_ask() {
+        return protected;
+    }
+
+    // 5. Check dependency file patterns (ask for approval)
     if config.is_dependency_file(path) {
         let mut ask = AskInfo::new(
             "dependencies.write",
@@ -34,7 +54,7 @@ pub fn analyze_write(input: &WriteInput, config: &CompiledConfig) -> Decision {
         return Decision::Ask(ask);
     }
 
-    Decision::allow()
+    custom_decision
 }
 
 #[cfg(test)]
@@ -89,4 +109,15 @@ mod tests {
         let decision = analyze_write(&input, &config);
         assert!(decision.is_ask());
     }
+
+    #[test]
+    fn test_write_protectedfiles_blocked() {
+        let config = test_config();
+        let input = WriteInput {
+            file_path: "/repo/.protectedfiles".to_string(),
+            content: String::new(),
+        };
+        let decision = analyze_write(&input, &config);
+        assert_eq!(decision.rule(), Some("self_protection.hook_config"));
+    }
 }
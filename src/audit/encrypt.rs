@@ -0,0 +1,92 @@
+//! Per-line age encryption for audit logs.
+//!
+//! Each JSON line is encrypted to an X25519 recipient and written as one
+//! base64 line, so the log stays append-only and line-oriented. Decrypt with
+//! `aca-safety-net audit query --identity key.txt`, or line by line with
+//! `base64 -d | age -d -i key.txt`.
+
+use age::x25519::{Identity, Recipient};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors that can occur when encrypting or decrypting audit lines.
+#[derive(Debug, Error)]
+pub enum EncryptError {
+    #[error("invalid age recipient: {0}")]
+    InvalidRecipient(String),
+
+    #[error("no AGE-SECRET-KEY found in identity")]
+    InvalidIdentity,
+
+    #[error("encryption failed: {0}")]
+    Encrypt(#[from] age::EncryptError),
+
+    #[error("decryption failed: {0}")]
+    Decrypt(#[from] age::DecryptError),
+
+    #[error("line is not base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+}
+
+/// Encrypt one log line to `recipient` (an `age1...` public key).
+pub fn encrypt_line(recipient: &str, line: &str) -> Result<String, EncryptError> {
+    let recipient = Recipient::from_str(recipient.trim())
+        .map_err(|e| EncryptError::InvalidRecipient(e.to_string()))?;
+    let ciphertext = age::encrypt(&recipient, line.as_bytes())?;
+    Ok(STANDARD.encode(ciphertext))
+}
+
+/// Parse the secret key from an identity file as written by `age-keygen`.
+pub fn parse_identity(content: &str) -> Result<Identity, EncryptError> {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("AGE-SECRET-KEY-"))
+        .and_then(|line| Identity::from_str(line).ok())
+        .ok_or(EncryptError::InvalidIdentity)
+}
+
+/// Decrypt one line written by [`encrypt_line`].
+pub fn decrypt_line(identity: &Identity, line: &str) -> Result<String, EncryptError> {
+    let ciphertext = STANDARD.decode(line.trim())?;
+    let plaintext = age::decrypt(identity, &ciphertext)?;
+    Ok(String::from_utf8_lossy(&plaintext).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+
+    #[test]
+    fn test_round_trip() {
+        let identity = Identity::generate();
+        let recipient = identity.to_public().to_string();
+
+        let line = encrypt_line(&recipient, r#"{"tool":"Read","summary":".env"}"#).unwrap();
+        assert!(!line.contains(".env"));
+        assert!(!line.contains('\n'));
+
+        let key_file = format!(
+            "# created: 2026-01-01\n# public key: {}\n{}\n",
+            recipient,
+            identity.to_string().expose_secret()
+        );
+        let parsed = parse_identity(&key_file).unwrap();
+        assert_eq!(
+            decrypt_line(&parsed, &line).unwrap(),
+            r#"{"tool":"Read","summary":".env"}"#
+        );
+    }
+
+    #[test]
+    fn test_invalid_recipient() {
+        assert!(matches!(
+            encrypt_line("age1notakey", "x"),
+            Err(EncryptError::InvalidRecipient(_))
+        ));
+        assert!(parse_identity("# nothing here").is_err());
+    }
+}
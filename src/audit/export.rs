@@ -0,0 +1,351 @@
+//! Flat exports of the audit log (CSV and Parquet) for analysis tools.
+
+use std::io::Write;
+use thiserror::Error;
+
+use super::AuditEntry;
+
+/// Column names of a flattened entry, in output order.
+pub const COLUMNS: &[&str] = &[
+    "timestamp",
+    "session_id",
+    "tool",
+    "decision",
+    "blocked",
+    "asked",
+    "warned",
+    "rewritten",
+    "shadow",
+    "rule",
+    "reason",
+    "alternatives",
+    "summary",
+    "tool_input",
+    "duration_us",
+    "version",
+    "config_sources",
+];
+
+/// Separator between alternatives in the flattened `alternatives` column.
+const ALTERNATIVES_SEPARATOR: &str = "; ";
+
+/// Errors that can occur when exporting entries.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("failed to write export: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "parquet")]
+    #[error("failed to write parquet: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+/// An audit entry with the nested decision fields flattened to scalars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatEntry {
+    /// RFC 3339 timestamp.
+    pub timestamp: String,
+    pub session_id: Option<String>,
+    pub tool: String,
+    /// `block`, `ask`, `rewrite`, `warn` or `allow`.
+    pub decision: &'static str,
+    pub blocked: bool,
+    pub asked: bool,
+    pub warned: bool,
+    pub rewritten: bool,
+    pub shadow: bool,
+    pub rule: Option<String>,
+    pub reason: Option<String>,
+    /// Alternatives joined with `; `.
+    pub alternatives: String,
+    pub summary: String,
+    /// Redacted tool input as a JSON string.
+    pub tool_input: Option<String>,
+    pub duration_us: Option<u64>,
+    pub version: String,
+    /// Merged config files (path and SHA-256) as a JSON string.
+    pub config_sources: Option<String>,
+}
+
+impl FlatEntry {
+    /// Flatten an audit entry.
+    pub fn new(entry: &AuditEntry) -> Self {
+        Self {
+            timestamp: entry.timestamp.to_rfc3339(),
+            session_id: entry.session_id.clone(),
+            tool: entry.tool.clone(),
+            decision: entry.decision_label(),
+            blocked: entry.blocked,
+            asked: entry.asked,
+            warned: entry.warned,
+            rewritten: entry.rewritten,
+            shadow: entry.shadow,
+            rule: entry.rule.clone(),
+            reason: entry.reason.clone(),
+            alternatives: entry.alternatives.join(ALTERNATIVES_SEPARATOR),
+            summary: entry.summary.clone(),
+            tool_input: entry.tool_input.as_ref().map(|input| input.to_string()),
+            duration_us: entry.duration_us,
+            version: entry.version.clone(),
+            config_sources: (!entry.config_sources.is_empty())
+                .then(|| serde_json::to_string(&entry.config_sources).unwrap_or_default()),
+        }
+    }
+
+    /// Values in [`COLUMNS`] order; missing values are empty.
+    fn fields(&self) -> [String; 17] {
+        [
+            self.timestamp.clone(),
+            self.session_id.clone().unwrap_or_default(),
+            self.tool.clone(),
+            self.decision.to_string(),
+            self.blocked.to_string(),
+            self.asked.to_string(),
+            self.warned.to_string(),
+            self.rewritten.to_string(),
+            self.shadow.to_string(),
+            self.rule.clone().unwrap_or_default(),
+            self.reason.clone().unwrap_or_default(),
+            self.alternatives.clone(),
+            self.summary.clone(),
+            self.tool_input.clone().unwrap_or_default(),
+            self.duration_us
+                .map(|us| us.to_string())
+                .unwrap_or_default(),
+            self.version.clone(),
+            self.config_sources.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// Write entries as RFC 4180 CSV with a header row.
+pub fn write_csv<'a, W: Write>(
+    entries: impl IntoIterator<Item = &'a AuditEntry>,
+    mut out: W,
+) -> Result<(), ExportError> {
+    write_csv_row(&mut out, COLUMNS.iter().map(|c| c.to_string()))?;
+    for entry in entries {
+        write_csv_row(&mut out, FlatEntry::new(entry).fields())?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn write_csv_row<W: Write>(
+    out: &mut W,
+    fields: impl IntoIterator<Item = String>,
+) -> std::io::Result<()> {
+    let line = fields
+        .into_iter()
+        .map(|field| csv_field(&field))
+        .collect::<Vec<_>>()
+        .join(",");
+    write!(out, "{}\r\n", line)
+}
+
+/// Quote a field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Parquet schema of a flattened entry.
+#[cfg(feature = "parquet")]
+const PARQUET_SCHEMA: &str = "
+message audit_entry {
+    required int64 timestamp (TIMESTAMP(MILLIS,true));
+    optional binary session_id (UTF8);
+    required binary tool (UTF8);
+    required binary decision (UTF8);
+    required boolean blocked;
+    required boolean asked;
+    required boolean warned;
+    required boolean rewritten;
+    required boolean shadow;
+    optional binary rule (UTF8);
+    optional binary reason (UTF8);
+    required binary alternatives (UTF8);
+    required binary summary (UTF8);
+    optional binary tool_input (UTF8);
+    optional int64 duration_us;
+    required binary version (UTF8);
+    optional binary config_sources (UTF8);
+}
+";
+
+/// Write entries as a single-row-group, uncompressed Parquet file.
+#[cfg(feature = "parquet")]
+pub fn write_parquet<'a, W: Write + Send>(
+    entries: impl IntoIterator<Item = &'a AuditEntry>,
+    out: W,
+) -> Result<(), ExportError> {
+    use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    let entries: Vec<&AuditEntry> = entries.into_iter().collect();
+    let flat: Vec<FlatEntry> = entries.iter().map(|entry| FlatEntry::new(entry)).collect();
+
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let properties = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(out, schema, properties)?;
+    let mut row_group = writer.next_row_group()?;
+
+    // Required strings have no definition levels; optional ones mark presence
+    let strings = |get: fn(&FlatEntry) -> Option<&str>| {
+        let values: Vec<ByteArray> = flat
+            .iter()
+            .filter_map(|row| get(row).map(ByteArray::from))
+            .collect();
+        let levels: Vec<i16> = flat.iter().map(|row| get(row).is_some() as i16).collect();
+        (values, levels)
+    };
+    let bools = |get: fn(&FlatEntry) -> bool| flat.iter().map(get).collect::<Vec<_>>();
+
+    let mut column = 0;
+    while let Some(mut writer) = row_group.next_column()? {
+        match COLUMNS[column] {
+            "timestamp" => {
+                let millis: Vec<i64> = entries
+                    .iter()
+                    .map(|entry| entry.timestamp.timestamp_millis())
+                    .collect();
+                writer
+                    .typed::<Int64Type>()
+                    .write_batch(&millis, None, None)?;
+            }
+            "duration_us" => {
+                let values: Vec<i64> = flat
+                    .iter()
+                    .filter_map(|row| row.duration_us)
+                    .map(|us| i64::try_from(us).unwrap_or(i64::MAX))
+                    .collect();
+                let levels: Vec<i16> = flat
+                    .iter()
+                    .map(|row| row.duration_us.is_some() as i16)
+                    .collect();
+                writer
+                    .typed::<Int64Type>()
+                    .write_batch(&values, Some(&levels), None)?;
+            }
+            name @ ("blocked" | "asked" | "warned" | "rewritten" | "shadow") => {
+                let values = match name {
+                    "blocked" => bools(|row| row.blocked),
+                    "asked" => bools(|row| row.asked),
+                    "warned" => bools(|row| row.warned),
+                    "rewritten" => bools(|row| row.rewritten),
+                    _ => bools(|row| row.shadow),
+                };
+                writer
+                    .typed::<BoolType>()
+                    .write_batch(&values, None, None)?;
+            }
+            name => {
+                let (values, levels) = match name {
+                    "session_id" => strings(|row| row.session_id.as_deref()),
+                    "tool" => strings(|row| Some(&row.tool)),
+                    "decision" => strings(|row| Some(row.decision)),
+                    "rule" => strings(|row| row.rule.as_deref()),
+                    "reason" => strings(|row| row.reason.as_deref()),
+                    "alternatives" => strings(|row| Some(&row.alternatives)),
+                    "summary" => strings(|row| Some(&row.summary)),
+                    "tool_input" => strings(|row| row.tool_input.as_deref()),
+                    "version" => strings(|row| Some(&row.version)),
+                    _ => strings(|row| row.config_sources.as_deref()),
+                };
+                let optional = matches!(
+                    name,
+                    "session_id" | "rule" | "reason" | "tool_input" | "config_sources"
+                );
+                let levels = optional.then_some(levels.as_slice());
+                writer
+                    .typed::<ByteArrayType>()
+                    .write_batch(&values, levels, None)?;
+            }
+        }
+        writer.close()?;
+        column += 1;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::Decision;
+    use crate::input::HookInput;
+
+    fn entry(json: &str, decision: Decision) -> AuditEntry {
+        AuditEntry::new(&HookInput::parse(json).unwrap(), &decision)
+    }
+
+    #[test]
+    fn test_flatten() {
+        let entry = entry(
+            r#"{"tool_name":"Bash","session_id":"s1","tool_input":{"command":"git push -f"}}"#,
+            Decision::block("git.push.force", "rewrites history")
+                .with_alternatives(["git push --force-with-lease", "git push"]),
+        );
+        let flat = FlatEntry::new(&entry);
+        assert_eq!(flat.decision, "block");
+        assert_eq!(flat.rule.as_deref(), Some("git.push.force"));
+        assert_eq!(flat.alternatives, "git push --force-with-lease; git push");
+        assert_eq!(flat.fields().len(), COLUMNS.len());
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        let entry = entry(
+            r#"{"tool_name":"Bash","tool_input":{"command":"echo \"a,b\"\nls"}}"#,
+            Decision::allow(),
+        );
+        let mut out = Vec::new();
+        write_csv([&entry], &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let (header, row) = csv.split_once("\r\n").unwrap();
+        assert_eq!(header, COLUMNS.join(","));
+        assert!(row.contains(",Bash,allow,false,false,false,false,false,"));
+        assert!(row.contains("\"echo \"\"a,b\"\"\nls\""));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_roundtrip() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let entries = [
+            entry(
+                r#"{"tool_name":"Bash","session_id":"s1","tool_input":{"command":"rm -rf /"}}"#,
+                Decision::block("rm.root", "deletes everything"),
+            ),
+            entry(
+                r#"{"tool_name":"Read","tool_input":{"file_path":"src/lib.rs"}}"#,
+                Decision::allow(),
+            ),
+        ];
+        let file = tempfile::NamedTempFile::new().unwrap();
+        write_parquet(&entries, file.reopen().unwrap()).unwrap();
+
+        let reader = SerializedFileReader::new(file.reopen().unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 2);
+        assert_eq!(
+            metadata.file_metadata().schema_descr().num_columns(),
+            COLUMNS.len()
+        );
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        assert!(rows[0].contains("rule: \"rm.root\""));
+        assert!(rows[1].contains("rule: null"));
+    }
+}
@@ -0,0 +1,213 @@
+//! OpenTelemetry export (OTLP/HTTP with JSON encoding).
+
+use serde_json::{Value, json};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use thiserror::Error;
+
+use super::AuditEntry;
+
+/// Instrumentation scope and `service.name` reported with every export.
+const SERVICE_NAME: &str = "aca-safety-net";
+
+/// Timeout for each export request; telemetry must never stall the hook.
+const EXPORT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Errors that can occur when exporting telemetry.
+#[derive(Debug, Error)]
+pub enum OtelError {
+    #[error("OTLP export failed: {0}")]
+    Http(#[from] ureq::Error),
+}
+
+/// Export one span and its metrics for a hook invocation.
+///
+/// `endpoint` is the collector base URL (e.g. `http://localhost:4318`);
+/// spans go to `/v1/traces` and metrics to `/v1/metrics`.
+pub fn export(endpoint: &str, entry: &AuditEntry, latency: Duration) -> Result<(), OtelError> {
+    let agent = ureq::Agent::new_with_config(
+        ureq::Agent::config_builder()
+            .timeout_global(Some(EXPORT_TIMEOUT))
+            .build(),
+    );
+    let base = endpoint.trim_end_matches('/');
+    agent
+        .post(format!("{}/v1/traces", base))
+        .header("Content-Type", "application/json")
+        .send(traces_body(entry, latency).to_string())?;
+    agent
+        .post(format!("{}/v1/metrics", base))
+        .header("Content-Type", "application/json")
+        .send(metrics_body(entry, latency).to_string())?;
+    Ok(())
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn attributes(entry: &AuditEntry) -> Vec<Value> {
+    let mut attributes = vec![
+        attribute("tool", &entry.tool),
+        attribute("decision", entry.decision_label()),
+    ];
+    if let Some(rule) = &entry.rule {
+        attributes.push(attribute("rule", rule));
+    }
+    attributes
+}
+
+fn resource() -> Value {
+    json!({ "attributes": [attribute("service.name", SERVICE_NAME)] })
+}
+
+fn scope() -> Value {
+    json!({ "name": SERVICE_NAME, "version": env!("CARGO_PKG_VERSION") })
+}
+
+/// Start and end of the invocation in Unix nanoseconds, as OTLP JSON strings.
+fn time_range(entry: &AuditEntry, latency: Duration) -> (String, String) {
+    let end = entry.timestamp.timestamp_nanos_opt().unwrap_or_default();
+    let start = end - latency.as_nanos() as i64;
+    (start.to_string(), end.to_string())
+}
+
+/// A random ID of `bytes` length, hex encoded.
+fn random_id(bytes: usize) -> String {
+    let mut id = String::new();
+    while id.len() < bytes * 2 {
+        id.push_str(&format!(
+            "{:016x}",
+            RandomState::new().build_hasher().finish()
+        ));
+    }
+    id.truncate(bytes * 2);
+    id
+}
+
+fn traces_body(entry: &AuditEntry, latency: Duration) -> Value {
+    let (start, end) = time_range(entry, latency);
+    let mut attributes = attributes(entry);
+    if let Some(session_id) = &entry.session_id {
+        attributes.push(attribute("session.id", session_id));
+    }
+    // Blocks are reported as errors so they stand out in trace views
+    let status = if entry.blocked {
+        json!({ "code": 2, "message": entry.reason.as_deref().unwrap_or_default() })
+    } else {
+        json!({ "code": 1 })
+    };
+
+    json!({
+        "resourceSpans": [{
+            "resource": resource(),
+            "scopeSpans": [{
+                "scope": scope(),
+                "spans": [{
+                    "traceId": random_id(16),
+                    "spanId": random_id(8),
+                    "name": format!("hook {}", entry.tool),
+                    "kind": 1,
+                    "startTimeUnixNano": start,
+                    "endTimeUnixNano": end,
+                    "attributes": attributes,
+                    "status": status,
+                }]
+            }]
+        }]
+    })
+}
+
+fn metrics_body(entry: &AuditEntry, latency: Duration) -> Value {
+    let (start, end) = time_range(entry, latency);
+    let latency_ms = latency.as_secs_f64() * 1000.0;
+
+    json!({
+        "resourceMetrics": [{
+            "resource": resource(),
+            "scopeMetrics": [{
+                "scope": scope(),
+                "metrics": [
+                    {
+                        "name": "aca_safety_net.decisions",
+                        "description": "Hook decisions",
+                        "unit": "1",
+                        "sum": {
+                            "aggregationTemporality": 1,
+                            "isMonotonic": true,
+                            "dataPoints": [{
+                                "attributes": attributes(entry),
+                                "startTimeUnixNano": start,
+                                "timeUnixNano": end,
+                                "asInt": "1",
+                            }]
+                        }
+                    },
+                    {
+                        "name": "aca_safety_net.latency",
+                        "description": "Hook evaluation latency",
+                        "unit": "ms",
+                        "histogram": {
+                            "aggregationTemporality": 1,
+                            "dataPoints": [{
+                                "attributes": attributes(entry),
+                                "startTimeUnixNano": start,
+                                "timeUnixNano": end,
+                                "count": "1",
+                                "sum": latency_ms,
+                                "min": latency_ms,
+                                "max": latency_ms,
+                                "bucketCounts": ["1"],
+                                "explicitBounds": [],
+                            }]
+                        }
+                    }
+                ]
+            }]
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::Decision;
+    use crate::input::HookInput;
+
+    fn blocked_entry() -> AuditEntry {
+        let input = HookInput::parse(
+            r#"{"tool_name":"Bash","session_id":"s1","tool_input":{"command":"rm -rf /"}}"#,
+        )
+        .unwrap();
+        AuditEntry::new(&input, &Decision::block("rm.root", "deleting root"))
+    }
+
+    #[test]
+    fn test_span_attributes() {
+        let body = traces_body(&blocked_entry(), Duration::from_millis(3));
+        let span = &body["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["name"], "hook Bash");
+        assert_eq!(span["status"]["code"], 2);
+        assert_eq!(span["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(span["spanId"].as_str().unwrap().len(), 16);
+
+        let attrs = span["attributes"].as_array().unwrap();
+        assert!(attrs.contains(&attribute("decision", "block")));
+        assert!(attrs.contains(&attribute("rule", "rm.root")));
+        assert!(attrs.contains(&attribute("session.id", "s1")));
+
+        let start: i64 = span["startTimeUnixNano"].as_str().unwrap().parse().unwrap();
+        let end: i64 = span["endTimeUnixNano"].as_str().unwrap().parse().unwrap();
+        assert_eq!(end - start, 3_000_000);
+    }
+
+    #[test]
+    fn test_metrics_body() {
+        let body = metrics_body(&blocked_entry(), Duration::from_millis(3));
+        let metrics = &body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics[0]["name"], "aca_safety_net.decisions");
+        assert_eq!(metrics[0]["sum"]["dataPoints"][0]["asInt"], "1");
+        assert_eq!(metrics[1]["histogram"]["dataPoints"][0]["sum"], 3.0);
+    }
+}
@@ -0,0 +1,182 @@
+//! Reading and filtering audit log entries.
+
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+use super::AuditEntry;
+
+/// Errors that can occur when querying the audit log.
+#[derive(Debug, Error)]
+pub enum QueryError {
+    #[error("failed to read audit log: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid --since value '{0}' (expected e.g. 30m, 1h, 2d or an RFC 3339 time)")]
+    InvalidSince(String),
+}
+
+/// Filter for audit entries. Unset fields match everything.
+#[derive(Debug, Default)]
+pub struct AuditQuery {
+    /// Rule id pattern; `*` matches any run of characters.
+    pub rule: Option<Regex>,
+    /// Only entries that were blocked.
+    pub blocked: bool,
+    /// Only entries from this session.
+    pub session: Option<String>,
+    /// Only entries at or after this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl AuditQuery {
+    /// Set the rule pattern from a glob such as `git.*`.
+    pub fn with_rule_glob(mut self, glob: &str) -> Self {
+        let pattern = glob
+            .split('*')
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(".*");
+        self.rule = Some(Regex::new(&format!("^{}$", pattern)).expect("escaped glob is valid"));
+        self
+    }
+
+    /// Check if an entry passes every filter.
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.rule
+            .as_ref()
+            .is_none_or(|re| entry.rule.as_deref().is_some_and(|rule| re.is_match(rule)))
+            && (!self.blocked || entry.blocked)
+            && self
+                .session
+                .as_ref()
+                .is_none_or(|id| entry.session_id.as_ref() == Some(id))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+    }
+}
+
+/// Parse a `--since` value: a relative age (`45s`, `30m`, `1h`, `2d`) or an RFC 3339 time.
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, QueryError> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let invalid = || QueryError::InvalidSince(value.to_string());
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        _ => None,
+    };
+    age.and_then(|age| now.checked_sub_signed(age))
+        .ok_or_else(invalid)
+}
+
+/// Read all entries from a JSONL audit log, skipping lines that do not parse.
+pub fn read_entries(path: &Path) -> Result<Vec<AuditEntry>, QueryError> {
+    Ok(parse_entries(&fs::read_to_string(path)?))
+}
+
+/// Parse JSONL audit log content, skipping lines that do not parse.
+pub fn parse_entries(content: &str) -> Vec<AuditEntry> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Format an entry as a single human-readable line.
+pub fn format_entry(entry: &AuditEntry) -> String {
+    format!(
+        "{}  {:<7}  {:<6}  {:<24}  {}",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        entry.decision_label().to_uppercase(),
+        entry.tool,
+        entry.rule.as_deref().unwrap_or("-"),
+        entry.summary
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = r#"{"timestamp":"2026-01-01T10:00:00Z","session_id":"a","tool":"Bash","blocked":true,"rule":"git.push.force","reason":"r","summary":"git push -f"}
+{"timestamp":"2026-01-01T11:00:00Z","session_id":"b","tool":"Bash","blocked":false,"summary":"ls"}
+not json
+{"timestamp":"2026-01-01T12:00:00Z","session_id":"a","tool":"Read","blocked":false,"asked":true,"rule":"deps.read","reason":"r","summary":"Cargo.toml"}
+"#;
+
+    fn count(query: &AuditQuery) -> usize {
+        parse_entries(LOG)
+            .iter()
+            .filter(|e| query.matches(e))
+            .count()
+    }
+
+    #[test]
+    fn test_parse_skips_bad_lines() {
+        assert_eq!(parse_entries(LOG).len(), 3);
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(count(&AuditQuery::default()), 3);
+        assert_eq!(count(&AuditQuery::default().with_rule_glob("git.*")), 1);
+        assert_eq!(count(&AuditQuery::default().with_rule_glob("*")), 2);
+        assert_eq!(
+            count(&AuditQuery {
+                blocked: true,
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(&AuditQuery {
+                session: Some("a".to_string()),
+                ..Default::default()
+            }),
+            2
+        );
+        let now = DateTime::parse_from_rfc3339("2026-01-01T12:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            count(&AuditQuery {
+                since: Some(parse_since("1h", now).unwrap()),
+                ..Default::default()
+            }),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = Utc::now();
+        assert_eq!(
+            parse_since("30m", now).unwrap(),
+            now - Duration::minutes(30)
+        );
+        assert_eq!(parse_since("2d", now).unwrap(), now - Duration::days(2));
+        assert!(parse_since("2026-01-01T00:00:00Z", now).is_ok());
+        assert!(parse_since("", now).is_err());
+        assert!(parse_since("h", now).is_err());
+        assert!(parse_since("5w", now).is_err());
+        assert!(parse_since("5é", now).is_err());
+        assert!(parse_since("99999999999999d", now).is_err());
+    }
+
+    #[test]
+    fn test_format_entry() {
+        let entries = parse_entries(LOG);
+        let line = format_entry(&entries[0]);
+        assert!(line.starts_with("2026-01-01 10:00:00  BLOCK"));
+        assert!(line.contains("git.push.force"));
+        assert!(line.ends_with("git push -f"));
+    }
+}
@@ -0,0 +1,216 @@
+//! SIEM field mappings for audit entries (Elastic Common Schema and CEF).
+
+use serde_json::{Value, json};
+
+use super::AuditEntry;
+
+/// ECS version the mapping follows.
+const ECS_VERSION: &str = "8.11.0";
+
+const PRODUCT: &str = "aca-safety-net";
+
+/// Map an entry to Elastic Common Schema fields.
+///
+/// Fields without an ECS equivalent live under the `aca` namespace.
+pub fn to_ecs(entry: &AuditEntry) -> Value {
+    let action = entry.decision_label();
+    let (event_type, outcome) = match action {
+        "block" => ("denied", "failure"),
+        "allow" => ("allowed", "success"),
+        _ => ("info", "unknown"),
+    };
+    let is_command = entry.tool == "Bash";
+
+    let mut ecs = json!({
+        "@timestamp": entry.timestamp,
+        "ecs": { "version": ECS_VERSION },
+        "event": {
+            "kind": "event",
+            "category": [if is_command { "process" } else { "file" }],
+            "type": [event_type],
+            "action": action,
+            "outcome": outcome,
+            "module": PRODUCT,
+        },
+        "observer": {
+            "product": PRODUCT,
+            "vendor": PRODUCT,
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "aca": { "tool": entry.tool },
+    });
+    if is_command {
+        ecs["process"] = json!({ "command_line": entry.summary });
+    } else {
+        ecs["file"] = json!({ "path": entry.summary });
+    }
+    if let Some(rule) = &entry.rule {
+        ecs["rule"] = json!({ "name": rule });
+    }
+    if let Some(severity) = entry.severity {
+        ecs["event"]["severity"] = json!(severity.score());
+        ecs["aca"]["severity"] = json!(severity);
+    }
+    if let Some(reason) = &entry.reason {
+        ecs["event"]["reason"] = json!(reason);
+    }
+    if let Some(session_id) = &entry.session_id {
+        ecs["aca"]["session_id"] = json!(session_id);
+    }
+    if !entry.alternatives.is_empty() {
+        ecs["aca"]["alternatives"] = json!(entry.alternatives);
+    }
+    if let Some(tool_input) = &entry.tool_input {
+        ecs["aca"]["tool_input"] = tool_input.clone();
+    }
+    if let Some(duration_us) = entry.duration_us {
+        // ECS durations are nanoseconds
+        ecs["event"]["duration"] = json!(duration_us.saturating_mul(1000));
+    }
+    if entry.shadow {
+        ecs["aca"]["shadow"] = json!(true);
+    }
+    if !entry.config_sources.is_empty() {
+        ecs["aca"]["config_sources"] = json!(entry.config_sources);
+    }
+    ecs
+}
+
+/// Format an entry as an ArcSight Common Event Format (CEF) line.
+pub fn to_cef(entry: &AuditEntry) -> String {
+    let action = entry.decision_label();
+    let severity = match (entry.severity, action) {
+        (Some(severity), _) => severity.score(),
+        (None, "warn" | "rewrite") => 3,
+        (None, _) => 1,
+    };
+    let name = entry.reason.as_deref().unwrap_or("Allowed");
+
+    let mut extension = vec![
+        ("rt", entry.timestamp.timestamp_millis().to_string()),
+        ("act", action.to_string()),
+        ("cs1Label", "tool".to_string()),
+        ("cs1", entry.tool.clone()),
+    ];
+    if let Some(session_id) = &entry.session_id {
+        extension.push(("cs2Label", "session".to_string()));
+        extension.push(("cs2", session_id.clone()));
+    }
+    if entry.tool == "Bash" {
+        extension.push(("cs3Label", "command".to_string()));
+        extension.push(("cs3", entry.summary.clone()));
+    } else {
+        extension.push(("filePath", entry.summary.clone()));
+    }
+    if let Some(reason) = &entry.reason {
+        extension.push(("reason", reason.clone()));
+    }
+    if let Some(duration_us) = entry.duration_us {
+        extension.push(("cn1Label", "durationMicros".to_string()));
+        extension.push(("cn1", duration_us.to_string()));
+    }
+
+    format!(
+        "CEF:0|{}|{}|{}|{}|{}|{}|{}",
+        PRODUCT,
+        PRODUCT,
+        env!("CARGO_PKG_VERSION"),
+        escape_header(entry.rule.as_deref().unwrap_or("allow")),
+        escape_header(name),
+        severity,
+        extension
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, escape_extension(value)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    )
+}
+
+/// Escape a CEF header field (`\` and `|`).
+fn escape_header(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Escape a CEF extension value (`\`, `=` and line breaks).
+fn escape_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::Decision;
+    use crate::input::HookInput;
+
+    fn entry(json: &str, decision: Decision) -> AuditEntry {
+        AuditEntry::new(&HookInput::parse(json).unwrap(), &decision)
+    }
+
+    #[test]
+    fn test_ecs_block() {
+        let entry = entry(
+            r#"{"tool_name":"Bash","session_id":"s1","tool_input":{"command":"git reset --hard"}}"#,
+            Decision::block("git.reset.hard", "discards changes"),
+        );
+        let ecs = to_ecs(&entry);
+        assert_eq!(ecs["event"]["action"], "block");
+        assert_eq!(ecs["event"]["type"][0], "denied");
+        assert_eq!(ecs["event"]["category"][0], "process");
+        assert_eq!(ecs["rule"]["name"], "git.reset.hard");
+        assert_eq!(ecs["event"]["severity"], 8);
+        assert_eq!(ecs["aca"]["severity"], "critical");
+        assert_eq!(ecs["process"]["command_line"], "git reset --hard");
+        assert_eq!(ecs["aca"]["session_id"], "s1");
+        assert!(ecs["@timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_ecs_provenance() {
+        let sources = [crate::config::ConfigSource {
+            path: "/home/u/.config/aca-safety-net/config.toml".to_string(),
+            sha256: "ab".repeat(32),
+        }];
+        let entry = entry(
+            r#"{"tool_name":"Bash","tool_input":{"command":"ls"}}"#,
+            Decision::allow(),
+        )
+        .with_provenance(std::time::Duration::from_micros(250), &sources);
+        let ecs = to_ecs(&entry);
+        assert_eq!(ecs["event"]["duration"], 250_000);
+        assert_eq!(ecs["aca"]["config_sources"][0]["sha256"], "ab".repeat(32));
+        assert!(to_cef(&entry).contains("cn1Label=durationMicros cn1=250"));
+    }
+
+    #[test]
+    fn test_ecs_file_allow() {
+        let entry = entry(
+            r#"{"tool_name":"Read","tool_input":{"file_path":"src/main.rs"}}"#,
+            Decision::allow(),
+        );
+        let ecs = to_ecs(&entry);
+        assert_eq!(ecs["event"]["outcome"], "success");
+        assert_eq!(ecs["file"]["path"], "src/main.rs");
+        assert!(ecs.get("rule").is_none());
+    }
+
+    #[test]
+    fn test_cef_escaping() {
+        let entry = entry(
+            r#"{"tool_name":"Bash","tool_input":{"command":"FOO=1 echo a|b"}}"#,
+            Decision::block("custom|rule", "reason with = sign"),
+        );
+        let cef = to_cef(&entry);
+        assert!(cef.starts_with("CEF:0|aca-safety-net|aca-safety-net|"));
+        assert!(cef.contains("|custom\\|rule|reason with = sign|8|"));
+        assert!(cef.contains("cs3=FOO\\=1 echo a|b"));
+        assert!(cef.contains("act=block"));
+        assert!(!cef.contains('\n'));
+    }
+}
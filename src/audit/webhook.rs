@@ -0,0 +1,268 @@
+//! Webhook audit sink (POSTs entries to an HTTP(S) endpoint).
+
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+use super::AuditEntry;
+use crate::config::WebhookConfig;
+
+/// Errors that can occur when sending audit entries.
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("failed to spool audit entry: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to serialize audit entry: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("webhook request failed: {0}")]
+    Http(#[from] ureq::Error),
+}
+
+/// Sends audit entries to a webhook, spooling them locally until delivered.
+///
+/// Entries are appended to the spool and sent as a JSON array once
+/// `batch_size` are waiting. A sender claims the spool by renaming it, so
+/// concurrent hook processes never send the same entries twice; on failure
+/// the claimed entries go back into the spool for the next attempt.
+pub struct WebhookSink<'a> {
+    config: &'a WebhookConfig,
+    spool: PathBuf,
+}
+
+impl<'a> WebhookSink<'a> {
+    /// Create a sink, or `None` if no spool location is available or
+    /// `spool_path` is outside the state directory.
+    pub fn new(config: &'a WebhookConfig) -> Option<Self> {
+        let state_dir = dirs::state_dir()
+            .or_else(dirs::data_local_dir)?
+            .join("aca-safety-net");
+        Self::in_dir(config, &state_dir)
+    }
+
+    /// Create a sink spooling into `state_dir`.
+    ///
+    /// The spool is read, sent and deleted, so a path that leaves the
+    /// directory is refused rather than followed.
+    fn in_dir(config: &'a WebhookConfig, state_dir: &Path) -> Option<Self> {
+        let spool = match &config.spool_path {
+            Some(path) => {
+                let path = state_dir.join(path);
+                let escapes = path
+                    .components()
+                    .any(|c| matches!(c, Component::ParentDir));
+                if escapes || !path.starts_with(state_dir) {
+                    tracing::warn!(
+                        path = %path.display(),
+                        "ignoring webhook spool_path outside the state directory"
+                    );
+                    return None;
+                }
+                path
+            }
+            None => state_dir.join("webhook-spool.jsonl"),
+        };
+        Some(Self { config, spool })
+    }
+
+    /// Queue an entry and send the batch if it is full.
+    pub fn send(&self, entry: &AuditEntry) -> Result<(), WebhookError> {
+        self.append(&format!("{}\n", serde_json::to_string(entry)?))?;
+
+        let waiting = fs::read_to_string(&self.spool)?.lines().count();
+        if waiting < self.config.batch_size.max(1) {
+            return Ok(());
+        }
+
+        let claimed = self
+            .spool
+            .with_extension(format!("sending-{}", std::process::id()));
+        if fs::rename(&self.spool, &claimed).is_err() {
+            // Another process claimed the batch first
+            return Ok(());
+        }
+        let lines = fs::read_to_string(&claimed)?;
+        let body = format!("[{}]", lines.lines().collect::<Vec<_>>().join(","));
+
+        let result = self.post_with_retry(&body);
+        if result.is_err() {
+            self.append(&lines)?;
+        }
+        fs::remove_file(&claimed)?;
+        result
+    }
+
+    fn append(&self, lines: &str) -> std::io::Result<()> {
+        if let Some(parent) = self.spool.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.spool)?;
+        file.write_all(lines.as_bytes())
+    }
+
+    fn post_with_retry(&self, body: &str) -> Result<(), WebhookError> {
+        let agent = ureq::Agent::new_with_config(
+            ureq::Agent::config_builder()
+                .timeout_global(Some(Duration::from_millis(self.config.timeout_ms)))
+                .build(),
+        );
+
+        let mut attempt = 0;
+        loop {
+            let mut request = agent
+                .post(&self.config.url)
+                .header("Content-Type", "application/json");
+            if let Some(token) = env_secret(&self.config.bearer_token_env) {
+                request = request.header("Authorization", format!("Bearer {}", token));
+            }
+            if let Some(secret) = env_secret(&self.config.hmac_secret_env) {
+                request = request.header("X-Signature-256", sign(secret.as_bytes(), body));
+            }
+
+            match request.send(body) {
+                Ok(_) => return Ok(()),
+                Err(e) if attempt >= self.config.max_retries => return Err(e.into()),
+                Err(_) => {
+                    attempt += 1;
+                    std::thread::sleep(Duration::from_millis(100 * u64::from(attempt)));
+                }
+            }
+        }
+    }
+}
+
+/// Read a secret from the environment variable named in config.
+fn env_secret(var: &Option<String>) -> Option<String> {
+    var.as_ref().and_then(|name| std::env::var(name).ok())
+}
+
+/// HMAC-SHA256 signature of the body, as `sha256=<hex>`.
+fn sign(secret: &[u8], body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::Decision;
+    use crate::input::HookInput;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use tempfile::TempDir;
+
+    fn entry() -> AuditEntry {
+        let input =
+            HookInput::parse(r#"{"tool_name":"Bash","tool_input":{"command":"ls"}}"#).unwrap();
+        AuditEntry::new(&input, &Decision::allow())
+    }
+
+    /// Accept one request and return its headers and body.
+    fn serve_once(listener: TcpListener) -> std::thread::JoinHandle<(String, String)> {
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut headers = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                headers.push_str(&line);
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let mut stream = stream;
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            (headers, String::from_utf8(body).unwrap())
+        })
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign(b"Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_batches_until_full() {
+        let dir = TempDir::new().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = WebhookConfig {
+            url: format!("http://{}/ingest", listener.local_addr().unwrap()),
+            batch_size: 2,
+            spool_path: Some("spool.jsonl".to_string()),
+            ..Default::default()
+        };
+        let server = serve_once(listener);
+        let sink = WebhookSink::in_dir(&config, dir.path()).unwrap();
+
+        sink.send(&entry()).unwrap();
+        assert!(dir.path().join("spool.jsonl").exists());
+        sink.send(&entry()).unwrap();
+
+        let (headers, body) = server.join().unwrap();
+        assert!(headers.starts_with("POST /ingest"));
+        let sent: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(sent.as_array().unwrap().len(), 2);
+        assert!(!dir.path().join("spool.jsonl").exists());
+    }
+
+    #[test]
+    fn test_failed_send_keeps_spool() {
+        let dir = TempDir::new().unwrap();
+        // Bind then drop to get a port nothing listens on
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let spool = dir.path().join("webhook-spool.jsonl");
+        let config = WebhookConfig {
+            url: format!("http://{}/ingest", addr),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let sink = WebhookSink::in_dir(&config, dir.path()).unwrap();
+
+        assert!(sink.send(&entry()).is_err());
+        assert_eq!(fs::read_to_string(&spool).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_spool_path_stays_in_state_dir() {
+        let dir = TempDir::new().unwrap();
+        let sink = |spool: &str| {
+            let config = WebhookConfig {
+                spool_path: Some(spool.to_string()),
+                ..Default::default()
+            };
+            WebhookSink::in_dir(&config, dir.path()).map(|sink| sink.spool)
+        };
+        assert_eq!(sink("spool.jsonl"), Some(dir.path().join("spool.jsonl")));
+        let inside = dir.path().join("queue/spool.jsonl");
+        assert_eq!(sink(&inside.to_string_lossy()), Some(inside));
+        assert_eq!(sink("/etc/passwd"), None);
+        assert_eq!(sink("../.env"), None);
+    }
+}
@@ -0,0 +1,270 @@
+//! `audit tail`, `audit query` and `audit export`: read back the JSONL audit log.
+
+use aca_safety_net::audit::AuditEntry;
+use aca_safety_net::audit::export::{ExportError, write_csv};
+use aca_safety_net::audit::query::{AuditQuery, format_entry, parse_since, read_entries};
+use aca_safety_net::config::Config;
+use chrono::Utc;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use super::usage_error;
+
+const USAGE: &str = "\
+Usage: aca-safety-net audit tail [-n COUNT] [--follow] [filters]
+       aca-safety-net audit [query] [filters]
+       aca-safety-net audit export --format csv|parquet --out FILE [filters]
+
+Filters:
+  --rule GLOB      Rule id, `*` matches anything (e.g. 'git.*')
+  --blocked        Only blocked operations
+  --session ID     Only entries from one session
+  --since AGE      Only recent entries (30m, 1h, 2d or an RFC 3339 time)
+  --file PATH      Audit log to read (default: audit.path from config)
+  --identity FILE  age identity to decrypt an encrypted log (audit.encrypt)
+  --json           Print raw JSON lines instead of a table
+
+Export columns: timestamp, session_id, tool, decision, blocked, asked, warned,
+rewritten, shadow, rule, reason, alternatives (joined with '; '), summary,
+tool_input (JSON string, when audit.include_tool_input is set), duration_us,
+version, config_sources (JSON string).";
+
+/// Subcommands of `audit`.
+#[derive(Clone, Copy, PartialEq)]
+enum Command {
+    Tail,
+    Query,
+    Export,
+}
+
+/// File formats for `audit export`.
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+/// Parsed options shared by the audit subcommands.
+struct Options {
+    query: AuditQuery,
+    file: Option<PathBuf>,
+    identity: Option<PathBuf>,
+    json: bool,
+    count: Option<usize>,
+    follow: bool,
+    format: Option<ExportFormat>,
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) if command == "tail" => (Command::Tail, rest),
+        Some((command, rest)) if command == "query" => (Command::Query, rest),
+        Some((command, rest)) if command == "export" => (Command::Export, rest),
+        // Filters alone query the log
+        Some((option, _)) if option.starts_with('-') => (Command::Query, args),
+        Some((other, _)) => {
+            return usage_error(&format!("unknown audit command '{}'", other), USAGE);
+        }
+        None => return usage_error("missing audit command", USAGE),
+    };
+    let mut options = match parse_options(rest, command) {
+        Ok(options) => options,
+        Err(message) => return usage_error(&message, USAGE),
+    };
+    if command == Command::Tail && options.count.is_none() {
+        options.count = Some(20);
+    }
+    let export = match (command, options.format, options.out.take()) {
+        (Command::Export, Some(format), Some(out)) => Some((format, out)),
+        (Command::Export, None, _) => return usage_error("export requires --format", USAGE),
+        (Command::Export, _, None) => return usage_error("export requires --out", USAGE),
+        _ => None,
+    };
+
+    let Some(path) = options.file.take().or_else(configured_path) else {
+        eprintln!("error: no audit log configured (set audit.path or pass --file)");
+        return ExitCode::FAILURE;
+    };
+
+    let entries = match load_entries(&path, options.identity.as_deref()) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("error: {}: {}", path.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let matching: Vec<_> = entries
+        .iter()
+        .filter(|entry| options.query.matches(entry))
+        .collect();
+    if let Some((format, out)) = export {
+        return match write_export(&matching, format, &out) {
+            Ok(()) => {
+                eprintln!("exported {} entries to {}", matching.len(), out.display());
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("error: {}: {}", out.display(), e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+    let skip = options
+        .count
+        .map_or(0, |count| matching.len().saturating_sub(count));
+    for entry in &matching[skip..] {
+        print_entry(entry, options.json);
+    }
+
+    if options.follow {
+        follow(&path, entries.len(), &options);
+    }
+    ExitCode::SUCCESS
+}
+
+fn parse_options(args: &[String], command: Command) -> Result<Options, String> {
+    let tail = command == Command::Tail;
+    let export = command == Command::Export;
+    let mut options = Options {
+        query: AuditQuery::default(),
+        file: None,
+        identity: None,
+        json: false,
+        count: None,
+        follow: false,
+        format: None,
+        out: None,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} requires a value", arg))
+        };
+        match arg.as_str() {
+            "--rule" => options.query = options.query.with_rule_glob(&value()?),
+            "--blocked" => options.query.blocked = true,
+            "--session" => options.query.session = Some(value()?),
+            "--since" => {
+                let since = parse_since(&value()?, Utc::now()).map_err(|e| e.to_string())?;
+                options.query.since = Some(since);
+            }
+            "--file" => options.file = Some(PathBuf::from(value()?)),
+            "--identity" => options.identity = Some(PathBuf::from(value()?)),
+            "--json" => options.json = true,
+            "-n" if tail => {
+                let count = value()?;
+                options.count = Some(
+                    count
+                        .parse()
+                        .map_err(|_| format!("invalid count '{}'", count))?,
+                );
+            }
+            "--follow" | "-f" if tail => options.follow = true,
+            "--format" if export => {
+                options.format = Some(match value()?.as_str() {
+                    "csv" => ExportFormat::Csv,
+                    "parquet" => ExportFormat::Parquet,
+                    other => return Err(format!("unknown export format '{}'", other)),
+                });
+            }
+            "--out" | "-o" if export => options.out = Some(PathBuf::from(value()?)),
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+    Ok(options)
+}
+
+/// Read the log, decrypting each line first when an identity is given.
+fn load_entries(path: &Path, identity: Option<&Path>) -> Result<Vec<AuditEntry>, String> {
+    let Some(identity) = identity else {
+        return read_entries(path).map_err(|e| e.to_string());
+    };
+    decrypt_entries(path, identity)
+}
+
+#[cfg(feature = "encrypt")]
+fn decrypt_entries(path: &Path, identity: &Path) -> Result<Vec<AuditEntry>, String> {
+    use aca_safety_net::audit::encrypt::{decrypt_line, parse_identity};
+    use aca_safety_net::audit::query::parse_entries;
+    use std::fs;
+
+    let key = fs::read_to_string(identity).map_err(|e| e.to_string())?;
+    let identity = parse_identity(&key).map_err(|e| e.to_string())?;
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let plaintext: Vec<String> = content
+        .lines()
+        .filter_map(|line| decrypt_line(&identity, line).ok())
+        .collect();
+    Ok(parse_entries(&plaintext.join("\n")))
+}
+
+#[cfg(not(feature = "encrypt"))]
+fn decrypt_entries(_path: &Path, _identity: &Path) -> Result<Vec<AuditEntry>, String> {
+    Err("--identity requires the `encrypt` feature".to_string())
+}
+
+/// Write the entries to `out` in the chosen format.
+fn write_export(
+    entries: &[&AuditEntry],
+    format: ExportFormat,
+    out: &Path,
+) -> Result<(), ExportError> {
+    let file = BufWriter::new(File::create(out)?);
+    match format {
+        ExportFormat::Csv => write_csv(entries.iter().copied(), file),
+        ExportFormat::Parquet => write_parquet(entries, file),
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(entries: &[&AuditEntry], file: BufWriter<File>) -> Result<(), ExportError> {
+    aca_safety_net::audit::export::write_parquet(entries.iter().copied(), file)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_entries: &[&AuditEntry], _file: BufWriter<File>) -> Result<(), ExportError> {
+    Err(std::io::Error::other("parquet export requires the `parquet` feature").into())
+}
+
+/// The audit log path from the user and project config.
+fn configured_path() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok();
+    let config = Config::load(cwd.as_deref()).ok()?;
+    config.audit.path.map(PathBuf::from)
+}
+
+fn print_entry(entry: &AuditEntry, json: bool) {
+    if json {
+        if let Ok(line) = serde_json::to_string(entry) {
+            println!("{}", line);
+        }
+    } else {
+        println!("{}", format_entry(entry));
+    }
+}
+
+/// Print matching entries as they are appended, until interrupted.
+fn follow(path: &Path, mut seen: usize, options: &Options) {
+    loop {
+        std::thread::sleep(Duration::from_millis(500));
+        let Ok(entries) = load_entries(path, options.identity.as_deref()) else {
+            continue;
+        };
+        // The log was rotated or truncated; start over
+        if entries.len() < seen {
+            seen = 0;
+        }
+        for entry in &entries[seen..] {
+            if options.query.matches(entry) {
+                print_entry(entry, options.json);
+            }
+        }
+        seen = entries.len();
+    }
+}
@@ -0,0 +1,147 @@
+//! `check`: analyze one command or path without hand-crafted hook JSON.
+
+use aca_safety_net::config::{Config, EnforcementMode};
+use aca_safety_net::decision::Decision;
+use aca_safety_net::evaluate;
+use aca_safety_net::input::HookInput;
+use serde_json::json;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::usage_error;
+
+const USAGE: &str = "\
+Usage: aca-safety-net check [--tool TOOL] [--cwd DIR] [--json] <COMMAND|PATH>
+
+Prints the decision, rule and reason for one tool call, using the same merged
+config as the hook. Exits 2 when the call would be blocked.
+
+Options:
+  --tool TOOL  Bash (default), Read, Edit, Write, WebFetch (URL), Glob or
+               Grep (pattern), or Task (prompt)
+  --cwd DIR    Directory to evaluate in, for the project config and rm checks
+               (default: current directory)
+  --json       Print the decision as JSON";
+
+pub fn run(args: &[String]) -> ExitCode {
+    let mut tool = "Bash".to_string();
+    let mut cwd = None;
+    let mut json = false;
+    let mut target = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tool" | "--cwd" => {
+                let Some(value) = args.next() else {
+                    return usage_error(&format!("{} requires a value", arg), USAGE);
+                };
+                if arg == "--tool" {
+                    tool = value.clone();
+                } else {
+                    cwd = Some(PathBuf::from(value));
+                }
+            }
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            other if other.starts_with("--") => {
+                return usage_error(&format!("unknown option '{}'", other), USAGE);
+            }
+            _ if target.is_some() => {
+                return usage_error("expected a single command or path (quote it)", USAGE);
+            }
+            _ => target = Some(arg.clone()),
+        }
+    }
+    let Some(target) = target else {
+        return usage_error("missing command or path", USAGE);
+    };
+    let cwd = cwd.or_else(|| std::env::current_dir().ok());
+    let Some(input) = HookInput::for_tool(
+        &tool,
+        &target,
+        cwd.as_ref().map(|dir| dir.display().to_string()),
+    ) else {
+        return usage_error(&format!("unsupported tool '{}'", tool), USAGE);
+    };
+    let config = match Config::load(cwd.as_deref()).and_then(Config::compile) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: could not load config: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let decision = evaluate(&input, &config);
+    let shadow = config.raw.mode == EnforcementMode::Shadow;
+
+    let mut out = io::stdout().lock();
+    let printed = if json {
+        writeln!(out, "{}", decision_json(&decision, shadow))
+    } else {
+        print_decision(&mut out, &decision, shadow)
+    };
+    match printed {
+        Ok(()) => {}
+        // `check ... | head` closing the pipe early is not an error
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: could not write the decision: {}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+    if decision.is_blocked() {
+        ExitCode::from(2)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn decision_json(decision: &Decision, shadow: bool) -> serde_json::Value {
+    let mut value = json!({
+        "decision": decision.label(),
+        "rule": decision.rule(),
+        "reason": decision.reason(),
+    });
+    if let Some(severity) = decision.severity() {
+        value["severity"] = json!(severity);
+    }
+    if let Some(info) = decision.block_info()
+        && !info.alternatives.is_empty()
+    {
+        value["alternatives"] = json!(info.alternatives);
+    }
+    if shadow {
+        value["shadow"] = json!(true);
+    }
+    value
+}
+
+fn print_decision(out: &mut impl Write, decision: &Decision, shadow: bool) -> io::Result<()> {
+    writeln!(out, "Decision: {}", decision.label())?;
+    if let Some(rule) = decision.rule() {
+        writeln!(out, "Rule:     {}", rule)?;
+    }
+    if let Some(reason) = decision.reason() {
+        writeln!(out, "Reason:   {}", reason)?;
+    }
+    if let Some(severity) = decision.severity() {
+        writeln!(out, "Severity: {}", severity)?;
+    }
+    if let Some(info) = decision.block_info() {
+        for alternative in &info.alternatives {
+            writeln!(out, "Instead:  {}", alternative)?;
+        }
+    }
+    if shadow {
+        writeln!(
+            out,
+            "Note:     mode = \"shadow\", so the hook would not enforce this"
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,342 @@
+//! `--daemon`: answer hook calls from a long-lived process.
+//!
+//! Every hook call otherwise starts a process, loads the config and
+//! compiles its patterns. The daemon keeps compiled configs in memory and
+//! answers calls over a Unix socket. The hook forwards its input there when
+//! the socket exists, and checks the call itself when no daemon answers.
+//!
+//! Anything running as the user can listen on the socket, so the hook only
+//! trusts a daemon that runs its own binary as the same user, and the
+//! daemon only answers hooks whose config environment matches its own.
+//! Before the daemon checks a call, it sends the config files it would
+//! load for it, and the hook checks the call itself unless they are the
+//! files it would load.
+
+use aca_safety_net::config::{CompiledConfig, Config, ConfigError, ConfigSource};
+use aca_safety_net::input::HookInput;
+use aca_safety_net::output::HookOutput;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, Permissions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use super::usage_error;
+use crate::{Caller, hook};
+
+const USAGE: &str = "\
+Usage: aca-safety-net --daemon [--socket PATH]
+
+Answers hook calls over a Unix socket, keeping compiled configs in memory.
+Hooks forward their input to the socket when it exists and check the call
+themselves when no daemon answers. Config file changes apply from the next
+call; restart the daemon after updating the binary. Hooks whose
+ACO_SAFETY_NET_CONFIG, ACO_SAFETY_NET_CACHE_DIR or ACO_SAFETY_NET_NO_CACHE
+differ from the daemon's are not answered, and hooks that would load other
+config files (e.g. under another HOME) check calls themselves.
+
+Options:
+  --socket PATH  Socket to listen on (default: $ACO_SAFETY_NET_SOCKET, or
+                 aca-safety-net.sock in $XDG_RUNTIME_DIR or the cache
+                 directory)";
+
+/// Compiled configs kept in memory; beyond this they are all dropped.
+const MAX_CONFIGS: usize = 32;
+
+/// How long the daemon waits for a hook to send its call.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a hook waits for the answer before checking the call itself.
+/// Webhook and OpenTelemetry exports are part of answering.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Environment variables that decide which config is loaded. A daemon
+/// started with other values, pointing at another config, does not answer.
+const CONFIG_ENV: &[&str] = &[
+    "ACO_SAFETY_NET_CONFIG",
+    "ACO_SAFETY_NET_CACHE_DIR",
+    "ACO_SAFETY_NET_NO_CACHE",
+];
+
+/// One hook call, sent as a JSON line.
+#[derive(Serialize, Deserialize)]
+struct Request {
+    /// Version of the forwarding hook; a daemon of another version does
+    /// not answer.
+    version: String,
+    /// The hook's values of [`CONFIG_ENV`].
+    config_env: Vec<Option<String>>,
+    caller: Caller,
+    /// The hook input as read from stdin.
+    input: String,
+}
+
+/// The config files the daemon loads for a call, sent before it checks
+/// the call. The hook answers `true` to go ahead, or closes the connection.
+#[derive(Serialize, Deserialize)]
+struct Sources {
+    sources: Vec<ConfigSource>,
+}
+
+/// Compiled configs by tool and serialized config.
+type Compiled = HashMap<(String, Vec<u8>), Rc<CompiledConfig>>;
+
+pub fn run(args: &[String]) -> ExitCode {
+    let mut socket = socket_path();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--socket" => match args.next() {
+                Some(path) => socket = Some(PathBuf::from(path)),
+                None => return usage_error("--socket requires a value", USAGE),
+            },
+            other => return usage_error(&format!("unexpected argument '{}'", other), USAGE),
+        }
+    }
+    let Some(socket) = socket else {
+        return usage_error("no runtime or cache directory; pass --socket", USAGE);
+    };
+
+    let listener = match listen(&socket) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("error: could not listen on {}: {}", socket.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("aca-safety-net daemon listening on {}", socket.display());
+
+    // Calls are answered one at a time, so session state and the imported
+    // secret rules are never shared between two calls in flight
+    let mut compiled = Compiled::new();
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| serve(&stream, &mut compiled));
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "daemon call failed");
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Where the daemon listens and hooks look for it.
+///
+/// Respects `ACO_SAFETY_NET_SOCKET`.
+fn socket_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("ACO_SAFETY_NET_SOCKET") {
+        return Some(PathBuf::from(path));
+    }
+    let dir = dirs::runtime_dir().or_else(|| Some(dirs::cache_dir()?.join("aca-safety-net")))?;
+    Some(dir.join("aca-safety-net.sock"))
+}
+
+/// Bind `socket`, replacing one left behind by a daemon that exited.
+fn listen(socket: &Path) -> io::Result<UnixListener> {
+    if let Ok(metadata) = fs::symlink_metadata(socket) {
+        if !metadata.file_type().is_socket() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "the path exists and is not a socket",
+            ));
+        }
+        if UnixStream::connect(socket).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                "another daemon is listening",
+            ));
+        }
+        fs::remove_file(socket)?;
+    }
+    if let Some(dir) = socket.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+    // Only this user's hooks may ask, and only they can read the answers
+    fs::set_permissions(socket, Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Answer one hook call.
+fn serve(mut stream: &UnixStream, compiled: &mut Compiled) -> io::Result<()> {
+    let started = Instant::now();
+    // A stuck hook must not hold up the others
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let request: Request = serde_json::from_str(&line)?;
+    if request.version != env!("CARGO_PKG_VERSION") {
+        // Closing without an answer makes the hook check the call itself,
+        // with the rules of its own version
+        tracing::warn!(version = %request.version, "ignoring a hook of another version");
+        return Ok(());
+    }
+    if request.config_env != config_env() {
+        tracing::warn!("ignoring a hook with another config environment");
+        return Ok(());
+    }
+
+    // Nothing is checked, logged or recorded until the hook agrees these
+    // are the files it would load itself
+    let sources = Sources {
+        sources: Config::file_sources(input_cwd(&request.input).as_deref())
+            .map_err(io::Error::other)?,
+    };
+    let mut line = serde_json::to_vec(&sources)?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+    let mut proceed = String::new();
+    reader.read_line(&mut proceed)?;
+    if !serde_json::from_str::<bool>(&proceed).unwrap_or(false) {
+        tracing::debug!("the hook loads other config files");
+        return Ok(());
+    }
+
+    let mut compile = |config: Config, tool: &str| compile_cached(compiled, config, tool);
+    let output = hook(&request.input, &request.caller, started, &mut compile);
+    let mut reply = serde_json::to_vec(&output)?;
+    reply.push(b'\n');
+    stream.write_all(&reply)
+}
+
+/// `config` compiled for `tool`, reusing an earlier compilation of the same
+/// config.
+fn compile_cached(
+    compiled: &mut Compiled,
+    config: Config,
+    tool: &str,
+) -> Result<Rc<CompiledConfig>, ConfigError> {
+    let Ok(serialized) = serde_json::to_vec(&config) else {
+        return config.compile_for(tool).map(Rc::new);
+    };
+    let key = (tool.to_string(), serialized);
+    if let Some(config) = compiled.get(&key) {
+        tracing::debug!(tool, "using compiled config");
+        return Ok(Rc::clone(config));
+    }
+
+    let config = Rc::new(config.compile_for(tool)?);
+    if compiled.len() >= MAX_CONFIGS {
+        compiled.clear();
+    }
+    compiled.insert(key, Rc::clone(&config));
+    Ok(config)
+}
+
+/// Have a running daemon answer the hook call, or `None` if none does.
+pub fn forward(input: &str, caller: &Caller) -> Option<HookOutput> {
+    let socket = socket_path()?;
+    if !socket.exists() {
+        return None;
+    }
+    let request = Request {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        config_env: config_env(),
+        caller: caller.clone(),
+        input: input.to_string(),
+    };
+    ask(&socket, &request)
+        .inspect_err(|e| match e.kind() {
+            io::ErrorKind::PermissionDenied => {
+                tracing::warn!(error = %e, "not trusting the daemon")
+            }
+            _ => tracing::debug!(error = %e, "daemon did not answer"),
+        })
+        .ok()
+}
+
+fn ask(socket: &Path, request: &Request) -> io::Result<HookOutput> {
+    let mut stream = UnixStream::connect(socket)?;
+    verify_peer(&stream)?;
+    stream.set_read_timeout(Some(ANSWER_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut line = serde_json::to_vec(request)?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let sources: Sources = serde_json::from_str(&line)?;
+    let own =
+        Config::file_sources(input_cwd(&request.input).as_deref()).map_err(io::Error::other)?;
+    if sources.sources != own {
+        return Err(untrusted("the daemon loads other config files"));
+    }
+    (&stream).write_all(b"true\n")?;
+
+    let mut reply = String::new();
+    reader.read_line(&mut reply)?;
+    Ok(serde_json::from_str(&reply)?)
+}
+
+/// The working directory of the hook input, which selects the project
+/// config.
+fn input_cwd(input: &str) -> Option<PathBuf> {
+    HookInput::parse(input).ok()?.cwd.map(PathBuf::from)
+}
+
+fn config_env() -> Vec<Option<String>> {
+    CONFIG_ENV
+        .iter()
+        .map(|name| std::env::var(name).ok())
+        .collect()
+}
+
+/// Check that the daemon on the other end runs this binary as this user,
+/// so no other program can answer in its place.
+#[cfg(target_os = "linux")]
+fn verify_peer(stream: &UnixStream) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::MetadataExt;
+
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: `cred` and `len` are valid for writes and sized for
+    // SO_PEERCRED, and the descriptor is open for the call
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&mut cred as *mut libc::ucred).cast(),
+            &mut len,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: geteuid cannot fail
+    if cred.uid != unsafe { libc::geteuid() } {
+        return Err(untrusted("the daemon runs as another user"));
+    }
+    let peer = fs::metadata(format!("/proc/{}/exe", cred.pid))?;
+    let own = fs::metadata(std::env::current_exe()?)?;
+    if (peer.dev(), peer.ino()) != (own.dev(), own.ino()) {
+        return Err(untrusted("the daemon runs another binary"));
+    }
+    Ok(())
+}
+
+/// Only Linux reports the daemon's process, so elsewhere it is never
+/// trusted.
+#[cfg(not(target_os = "linux"))]
+fn verify_peer(_stream: &UnixStream) -> io::Result<()> {
+    Err(untrusted("the daemon cannot be verified on this platform"))
+}
+
+fn untrusted(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, reason)
+}
@@ -0,0 +1,362 @@
+//! `doctor`: check that the hook is wired up and working.
+//!
+//! A hook that is not registered, points at a binary that moved, or fails
+//! on a broken config lets every call through without a trace. This checks
+//! each link of the chain and says how to fix what is wrong.
+
+use aca_safety_net::config::Config;
+use regex::Regex;
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use super::install::{Scope, TOOL_MATCHER, is_our_hook, resolve_program, same_file};
+use super::usage_error;
+
+const USAGE: &str = "\
+Usage: aca-safety-net doctor [--cwd DIR] [--settings FILE]...
+
+Checks the hook registration in Claude Code's settings, that the config
+loads and compiles, that the audit log is writable and that this binary is
+as new as the config requires. Prints a fix for each problem and exits 1
+if any check fails.
+
+Options:
+  --cwd DIR        Project directory (default: current directory)
+  --settings FILE  Settings file to check instead of ~/.claude/settings.json,
+                   .claude/settings.json and .claude/settings.local.json;
+                   may be repeated";
+
+/// Check results, printed as they come in.
+#[derive(Default)]
+struct Report {
+    failures: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn ok(&mut self, message: &str) {
+        println!("  [ok]   {}", message);
+    }
+
+    fn warn(&mut self, message: &str, fix: &str) {
+        self.warnings += 1;
+        println!("  [warn] {}\n         fix: {}", message, fix);
+    }
+
+    fn fail(&mut self, message: &str, fix: &str) {
+        self.failures += 1;
+        println!("  [FAIL] {}\n         fix: {}", message, fix);
+    }
+}
+
+/// One of our hook commands found in a settings file.
+struct Registration {
+    settings: PathBuf,
+    event: String,
+    matcher: Option<String>,
+    command: String,
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let mut cwd = std::env::current_dir().ok();
+    let mut settings = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cwd" | "--settings" => {
+                let Some(value) = args.next() else {
+                    return usage_error(&format!("{} requires a value", arg), USAGE);
+                };
+                if arg == "--cwd" {
+                    cwd = Some(PathBuf::from(value));
+                } else {
+                    settings.push(PathBuf::from(value));
+                }
+            }
+            other => return usage_error(&format!("unexpected argument '{}'", other), USAGE),
+        }
+    }
+    if settings.is_empty() {
+        settings.extend(Scope::User.path());
+        if let Some(cwd) = &cwd {
+            settings.push(cwd.join(".claude/settings.json"));
+            settings.push(cwd.join(".claude/settings.local.json"));
+        }
+    }
+
+    let mut report = Report::default();
+    println!("Binary");
+    let exe = std::env::current_exe().ok();
+    match &exe {
+        Some(exe) => report.ok(&format!(
+            "aca-safety-net {} at {}",
+            env!("CARGO_PKG_VERSION"),
+            exe.display()
+        )),
+        None => report.ok(&format!("aca-safety-net {}", env!("CARGO_PKG_VERSION"))),
+    }
+
+    let config = check_config(&mut report, cwd.as_deref());
+    check_registration(&mut report, &settings, exe.as_deref(), config.as_ref());
+    if let Some(config) = &config {
+        check_audit_log(&mut report, config);
+    }
+
+    println!(
+        "\n{} failed, {} warning(s)",
+        report.failures, report.warnings
+    );
+    if report.failures > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Load and compile the merged config, returning it if it loads.
+fn check_config(report: &mut Report, cwd: Option<&Path>) -> Option<Config> {
+    println!("\nConfig");
+    let validate = "run `aca-safety-net --validate-config` for details";
+    let config = match Config::load(cwd) {
+        Ok(config) => config,
+        Err(e) => {
+            report.fail(
+                &format!("could not load config: {}; the hook fails every call", e),
+                validate,
+            );
+            return None;
+        }
+    };
+
+    if config.sources.is_empty() {
+        report.ok("no config files, using the built-in defaults");
+    }
+    for source in &config.sources {
+        report.ok(&format!("loaded {}", source.path));
+    }
+
+    let mut pattern_errors = config.pattern_errors().len();
+    if pattern_errors == 0 && config.clone().compile().is_err() {
+        pattern_errors = 1;
+    }
+    if pattern_errors > 0 {
+        report.fail(
+            &format!(
+                "{} invalid pattern(s); the hook fails every call",
+                pattern_errors
+            ),
+            validate,
+        );
+    } else {
+        report.ok("all patterns compile");
+    }
+
+    match Config::unknown_keys(cwd) {
+        Ok(keys) if !keys.is_empty() => report.warn(
+            &format!(
+                "{} unknown key(s) are ignored: {}",
+                keys.len(),
+                keys.join(", ")
+            ),
+            "check the spelling against `aca-safety-net --schema`",
+        ),
+        _ => {}
+    }
+    let shadowed = config.shadowed_rules().len();
+    if shadowed > 0 {
+        report.warn(&format!("{} rule(s) can never apply", shadowed), validate);
+    }
+
+    match config.unmet_min_version() {
+        Some(required) => report.fail(
+            &format!(
+                "the config needs aca-safety-net {} or newer, this is {}",
+                required,
+                env!("CARGO_PKG_VERSION")
+            ),
+            "update the source and run `just install`",
+        ),
+        None if config.min_version.is_some() => {
+            report.ok("binary is as new as the config's min_version")
+        }
+        None => {}
+    }
+    Some(config)
+}
+
+/// Check that PreToolUse runs an existing binary for every tool, plus the
+/// events the config's features rely on.
+fn check_registration(
+    report: &mut Report,
+    settings: &[PathBuf],
+    exe: Option<&Path>,
+    config: Option<&Config>,
+) {
+    println!("\nHook registration");
+    let mut registrations = Vec::new();
+    for path in settings {
+        match read_registrations(path) {
+            Ok(found) => registrations.extend(found),
+            Err(e) => report.fail(
+                &format!("{}: {}", path.display(), e),
+                "fix the file; Claude Code skips hooks it cannot parse",
+            ),
+        }
+    }
+
+    let pre_tool_use: Vec<&Registration> = registrations
+        .iter()
+        .filter(|registration| registration.event == "PreToolUse")
+        .collect();
+    if pre_tool_use.is_empty() {
+        report.fail(
+            "no PreToolUse hook runs aca-safety-net, so nothing is checked",
+            "run `aca-safety-net install` (or `init` in a project)",
+        );
+    }
+    for registration in &pre_tool_use {
+        let location = format!("PreToolUse in {}", registration.settings.display());
+        match resolve_program(&registration.command) {
+            None => report.fail(
+                &format!(
+                    "{} runs '{}', which was not found",
+                    location, registration.command
+                ),
+                "run `just install` and make sure ~/.local/bin is in your PATH, \
+                 then `aca-safety-net install`",
+            ),
+            Some(binary) if exe.is_some_and(|exe| !same_file(exe, &binary)) => report.warn(
+                &format!(
+                    "{} runs {}, not this binary; its version may differ",
+                    location,
+                    binary.display()
+                ),
+                "run `just install` to update it",
+            ),
+            Some(binary) => report.ok(&format!("{} runs {}", location, binary.display())),
+        }
+
+        let missed = missed_tools(registration.matcher.as_deref());
+        if !missed.is_empty() {
+            report.warn(
+                &format!("{} does not see {}", location, missed.join(", ")),
+                "run `aca-safety-net install` to register it for every tool",
+            );
+        }
+    }
+    if pre_tool_use.len() > 1 {
+        report.warn(
+            &format!(
+                "the hook is registered {} times and runs once per registration",
+                pre_tool_use.len()
+            ),
+            "remove all but one with `aca-safety-net uninstall --settings FILE`",
+        );
+    }
+
+    let Some(config) = config else {
+        return;
+    };
+    let registered = |event: &str| registrations.iter().any(|r| r.event == event);
+    if config.approvals.remember && !registered("PostToolUse") {
+        report.warn(
+            "approvals.remember is set but no PostToolUse hook records approvals",
+            "run `aca-safety-net install --post-tool-use`",
+        );
+    }
+    if config.audit.session_summary && !registered("SessionEnd") && !registered("Stop") {
+        report.warn(
+            "audit.session_summary is set but no SessionEnd or Stop hook writes it",
+            "run `aca-safety-net install --session-end`",
+        );
+    }
+}
+
+/// Our hook commands in a settings file; a missing file has none.
+fn read_registrations(path: &Path) -> Result<Vec<Registration>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let settings: Value =
+        serde_json::from_str(&content).map_err(|e| format!("invalid JSON: {}", e))?;
+
+    let mut registrations = Vec::new();
+    let Some(hooks) = settings.get("hooks").and_then(Value::as_object) else {
+        return Ok(registrations);
+    };
+    for (event, groups) in hooks {
+        for group in groups.as_array().into_iter().flatten() {
+            let matcher = group.get("matcher").and_then(Value::as_str);
+            let commands = group.get("hooks").and_then(Value::as_array);
+            for hook in commands
+                .into_iter()
+                .flatten()
+                .filter(|hook| is_our_hook(hook))
+            {
+                registrations.push(Registration {
+                    settings: path.to_path_buf(),
+                    event: event.clone(),
+                    matcher: matcher.map(str::to_string),
+                    command: hook["command"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+        }
+    }
+    Ok(registrations)
+}
+
+/// Tools the hook analyzes that `matcher` does not match. Claude Code
+/// treats a missing, empty or `*` matcher as matching every tool and
+/// anything else as a regex.
+fn missed_tools(matcher: Option<&str>) -> Vec<&'static str> {
+    let tools = TOOL_MATCHER.split('|');
+    match matcher {
+        None | Some("" | "*") => Vec::new(),
+        Some(matcher) => match Regex::new(&format!("^(?:{})$", matcher)) {
+            Ok(regex) => tools.filter(|tool| !regex.is_match(tool)).collect(),
+            Err(_) => tools.collect(),
+        },
+    }
+}
+
+/// Check that audit entries have somewhere to go.
+fn check_audit_log(report: &mut Report, config: &Config) {
+    println!("\nAudit log");
+    let audit = &config.audit;
+    if !audit.enabled {
+        report.ok("audit logging is disabled");
+        return;
+    }
+    if audit.encrypt && audit.recipient.is_none() {
+        report.fail(
+            "audit.encrypt is set without audit.recipient, so every write fails",
+            "set audit.recipient to an age public key (age1...)",
+        );
+    }
+    let Some(path) = &audit.path else {
+        if audit.webhook.is_none() && audit.otel_endpoint.is_none() {
+            report.warn(
+                "audit.enabled is set but no audit.path, webhook or otel_endpoint \
+                 receives entries",
+                "set audit.path",
+            );
+        }
+        return;
+    };
+    // Opened the way the hook opens it
+    match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(_) => report.ok(&format!("{} is writable", path)),
+        Err(e) => report.fail(
+            &format!("cannot write {}: {}", path, e),
+            "create its directory or fix its permissions",
+        ),
+    }
+}
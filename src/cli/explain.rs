@@ -0,0 +1,136 @@
+//! `explain`: show how a Bash command is parsed and which rules match.
+
+use aca_safety_net::analysis::{Explanation, explain_bash};
+use aca_safety_net::config::Config;
+use aca_safety_net::decision::Decision;
+use aca_safety_net::shell::{Operator, Token};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::usage_error;
+
+const USAGE: &str = "\
+Usage: aca-safety-net explain [--cwd DIR] [--all] <COMMAND>
+
+Prints the parsed segments, stripped wrappers and tokens of a Bash command,
+the rules evaluated against it (allow and deny rules, custom rules, paranoid
+mode, read commands, built-in analyzers, exceptions, severities and
+overrides), and the final decision of the full hook pipeline.
+
+Options:
+  --cwd DIR  Directory to evaluate in (default: current directory)
+  --all      List rules that did not match too";
+
+pub fn run(args: &[String]) -> ExitCode {
+    let mut cwd = None;
+    let mut all = false;
+    let mut command = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cwd" => {
+                let Some(value) = args.next() else {
+                    return usage_error("--cwd requires a value", USAGE);
+                };
+                cwd = Some(PathBuf::from(value));
+            }
+            "--all" => all = true,
+            other if other.starts_with("--") => {
+                return usage_error(&format!("unknown option '{}'", other), USAGE);
+            }
+            _ if command.is_some() => {
+                return usage_error("expected a single command (quote it)", USAGE);
+            }
+            _ => command = Some(arg.clone()),
+        }
+    }
+    let Some(command) = command else {
+        return usage_error("missing command", USAGE);
+    };
+
+    let cwd = cwd.or_else(|| std::env::current_dir().ok());
+    let config = match Config::load(cwd.as_deref()).and_then(Config::compile) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: could not load config: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let cwd = cwd.map(|dir| dir.display().to_string());
+    let explanation = explain_bash(&command, &config, cwd.as_deref());
+    print_explanation(&command, &explanation, all);
+    ExitCode::SUCCESS
+}
+
+fn print_explanation(command: &str, explanation: &Explanation, all: bool) {
+    println!("Command: {}", command);
+
+    println!("\nSegments:");
+    for (i, segment) in explanation.segments.iter().enumerate() {
+        println!("  {}. {}", i + 1, segment.command);
+        if segment.stripped != segment.command {
+            println!("     stripped: {}", segment.stripped);
+        }
+        println!("     tokens:   {}", format_tokens(&segment.tokens));
+        if let Some(operator) = segment.operator {
+            println!("     then:     {}", operator_str(operator));
+        }
+    }
+
+    println!("\nRules:");
+    let mut shown = 0;
+    for rule in &explanation.rules {
+        let result = match &rule.outcome {
+            Some(decision) => format_decision(decision),
+            None if all => "no match".to_string(),
+            None => continue,
+        };
+        println!("  {:<10} {:<32} {}", rule.stage, rule.name, result);
+        shown += 1;
+    }
+    let unmatched = explanation.rules.len() - shown;
+    if shown == 0 {
+        println!("  (none matched)");
+    }
+    if !all && unmatched > 0 {
+        println!(
+            "  {} more evaluated without a match (--all to list)",
+            unmatched
+        );
+    }
+
+    println!("\nDecision: {}", format_decision(&explanation.decision));
+}
+
+fn format_decision(decision: &Decision) -> String {
+    match (decision.rule(), decision.reason()) {
+        (Some(rule), Some(reason)) => {
+            format!("{} {}: {}", decision.label().to_uppercase(), rule, reason)
+        }
+        _ => decision.label().to_uppercase(),
+    }
+}
+
+fn format_tokens(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|token| match token {
+            Token::Word(word) => format!("{:?}", word),
+            Token::Redirect(op) => format!("redirect({})", op),
+            Token::Assignment(name, value) => format!("assign({}={})", name, value),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn operator_str(operator: Operator) -> &'static str {
+    match operator {
+        Operator::And => "&&",
+        Operator::Or => "||",
+        Operator::Pipe => "|",
+        Operator::Semicolon => ";",
+        Operator::Background => "&",
+    }
+}
@@ -0,0 +1,137 @@
+//! `init`: set up a project in one step.
+//!
+//! Writes a starter `.security-hook.toml`, checks that the hook command
+//! resolves to a binary Claude Code can run, and registers it the way
+//! `install` does.
+
+use aca_safety_net::config::CONFIG_EXTENSIONS;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use super::install::{self, Scope, TOOL_MATCHER, resolve_program, same_file};
+use super::usage_error;
+
+const USAGE: &str = "\
+Usage: aca-safety-net init [--user|--project|--settings FILE] [options]
+
+Writes a starter .security-hook.toml in the current directory (unless a
+project config already exists), checks that the hook command can be found
+and registers the PreToolUse hook in Claude Code's settings.json, keeping
+the rest of the file.
+
+Options:
+  --user           ~/.claude/settings.json (default)
+  --project        .claude/settings.json in the current directory
+  --settings FILE  Any other settings file
+  --command CMD    Command Claude Code runs (default: this binary's path for
+                   --user, aca-safety-net otherwise)
+  --force          Overwrite an existing project config";
+
+/// The starter project config. Everything is commented out, so it changes
+/// nothing until edited.
+const STARTER_CONFIG: &str = r#"# aca-safety-net project config
+#
+# Entries here extend the built-in defaults and the user config
+# (~/.config/aca-safety-net/config.toml). Run `aca-safety-net --validate-config`
+# after editing, and `aca-safety-net --schema` for every available setting.
+
+# Files in this project that must never be read
+# sensitive_files = ['\.secrets/', 'service-account\.json']
+
+# Commands to block here
+# [[deny]]
+# tool = "Bash"
+# pattern = '^\s*terraform\s+destroy'
+# reason = "Tear down infrastructure from CI, not from a session"
+
+# Commands that are safe here despite the defaults
+# [[allow]]
+# tool = "Bash"
+# pattern = '^cat \.env\.test$'
+# reason = "Test fixtures, no real secrets"
+
+# Checked by `aca-safety-net test`
+# [[tests]]
+# input = "git push -f origin main"
+# expect = "block"
+"#;
+
+pub fn run(args: &[String]) -> ExitCode {
+    let mut scope = Scope::User;
+    let mut command = None;
+    let mut force = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--user" => scope = Scope::User,
+            "--project" => scope = Scope::Project,
+            "--settings" | "--command" => {
+                let Some(value) = args.next() else {
+                    return usage_error(&format!("{} requires a value", arg), USAGE);
+                };
+                if arg == "--settings" {
+                    scope = Scope::File(PathBuf::from(value));
+                } else {
+                    command = Some(value.clone());
+                }
+            }
+            "--force" => force = true,
+            other => return usage_error(&format!("unexpected argument '{}'", other), USAGE),
+        }
+    }
+    let command = command.unwrap_or_else(|| install::default_command(&scope));
+
+    // A hook Claude Code cannot start protects nothing, so stop before
+    // registering it
+    let Some(binary) = resolve_program(&command) else {
+        eprintln!(
+            "error: hook command '{}' was not found\n  \
+             Run `just install` and make sure ~/.local/bin is in your PATH, \
+             or pass --command with the binary's path",
+            command
+        );
+        return ExitCode::FAILURE;
+    };
+    println!("Hook command: {}", binary.display());
+    if let Ok(exe) = std::env::current_exe()
+        && !same_file(&exe, &binary)
+    {
+        println!(
+            "  note: this is not the running binary ({}); check both are the same version",
+            exe.display()
+        );
+    }
+
+    let Ok(cwd) = std::env::current_dir() else {
+        eprintln!("error: could not determine the current directory");
+        return ExitCode::FAILURE;
+    };
+    if let Err(e) = write_starter_config(&cwd, force) {
+        eprintln!("error: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    install::register(&scope, &command, &[("PreToolUse", Some(TOOL_MATCHER))])
+}
+
+/// Write the starter config unless a project config exists.
+fn write_starter_config(cwd: &Path, force: bool) -> Result<(), String> {
+    let existing = CONFIG_EXTENSIONS
+        .iter()
+        .map(|extension| cwd.join(".security-hook").with_extension(extension))
+        .find(|path| path.exists());
+    if let Some(path) = existing
+        && !force
+    {
+        println!("Keeping existing {}", path.display());
+        return Ok(());
+    }
+
+    let path = cwd.join(".security-hook.toml");
+    fs::write(&path, STARTER_CONFIG)
+        .map_err(|e| format!("could not write {}: {}", path.display(), e))?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,316 @@
+//! `install` / `uninstall`: register the hook in a Claude Code settings.json.
+
+use serde_json::{Map, Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use super::usage_error;
+
+const INSTALL_USAGE: &str = "\
+Usage: aca-safety-net install [--user|--project|--settings FILE] [options]
+
+Adds the hook to Claude Code's settings.json. Running it again replaces the
+existing entries instead of adding duplicates.
+
+Options:
+  --user           ~/.claude/settings.json (default)
+  --project        .claude/settings.json in the current directory
+  --settings FILE  Any other settings file (e.g. .claude/settings.local.json)
+  --post-tool-use  Also register for PostToolUse (needed by [approvals])
+  --session-end    Also register for SessionEnd (needed by session_summary)
+  --stop           Also register for Stop and SubagentStop (session_summary
+                   after every response)
+  --command CMD    Command Claude Code runs (default: this binary's path for
+                   --user, aca-safety-net otherwise)";
+
+const UNINSTALL_USAGE: &str = "\
+Usage: aca-safety-net uninstall [--user|--project|--settings FILE]
+
+Removes every aca-safety-net hook entry from Claude Code's settings.json.";
+
+/// Tools the hook analyzes.
+pub(super) const TOOL_MATCHER: &str = "Bash|Read|Edit|Write|WebFetch|Glob|Grep|Task";
+
+/// Binary name used to recognize our entries, whatever path they use.
+const BINARY_NAME: &str = "aca-safety-net";
+
+/// Which settings file to edit.
+pub(super) enum Scope {
+    User,
+    Project,
+    File(PathBuf),
+}
+
+impl Scope {
+    pub(super) fn path(&self) -> Option<PathBuf> {
+        match self {
+            Scope::User => dirs::home_dir().map(|home| home.join(".claude/settings.json")),
+            Scope::Project => std::env::current_dir()
+                .ok()
+                .map(|dir| dir.join(".claude/settings.json")),
+            Scope::File(path) => Some(path.clone()),
+        }
+    }
+}
+
+pub fn install(args: &[String]) -> ExitCode {
+    let mut scope = Scope::User;
+    let mut post_tool_use = false;
+    let mut session_end = false;
+    let mut stop = false;
+    let mut command = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--user" => scope = Scope::User,
+            "--project" => scope = Scope::Project,
+            "--settings" | "--command" => {
+                let Some(value) = args.next() else {
+                    return usage_error(&format!("{} requires a value", arg), INSTALL_USAGE);
+                };
+                if arg == "--settings" {
+                    scope = Scope::File(PathBuf::from(value));
+                } else {
+                    command = Some(value.clone());
+                }
+            }
+            "--post-tool-use" => post_tool_use = true,
+            "--session-end" => session_end = true,
+            "--stop" => stop = true,
+            other => {
+                return usage_error(&format!("unexpected argument '{}'", other), INSTALL_USAGE);
+            }
+        }
+    }
+
+    let command = command.unwrap_or_else(|| default_command(&scope));
+    let mut events = vec![("PreToolUse", Some(TOOL_MATCHER))];
+    if post_tool_use {
+        events.push(("PostToolUse", Some(TOOL_MATCHER)));
+    }
+    if session_end {
+        events.push(("SessionEnd", None));
+    }
+    if stop {
+        events.extend([("Stop", None), ("SubagentStop", None)]);
+    }
+
+    register(&scope, &command, &events)
+}
+
+/// The command to register: a user install can use this binary's absolute
+/// path; shared project settings can't.
+pub(super) fn default_command(scope: &Scope) -> String {
+    match scope {
+        Scope::User => std::env::current_exe()
+            .map(|exe| exe.display().to_string())
+            .unwrap_or_else(|_| BINARY_NAME.to_string()),
+        _ => BINARY_NAME.to_string(),
+    }
+}
+
+/// Replace our hook entries in the settings with `command` for `events`.
+pub(super) fn register(scope: &Scope, command: &str, events: &[(&str, Option<&str>)]) -> ExitCode {
+    edit_settings(scope, |settings| {
+        remove_hooks(settings);
+        add_hooks(settings, command, events)?;
+        prune_empty_events(settings);
+        let names: Vec<&str> = events.iter().map(|(event, _)| *event).collect();
+        Ok(format!("Registered {} for {}", command, names.join(", ")))
+    })
+}
+
+pub fn uninstall(args: &[String]) -> ExitCode {
+    let scope = match args {
+        [] => Scope::User,
+        [flag] if flag == "--user" => Scope::User,
+        [flag] if flag == "--project" => Scope::Project,
+        [flag, path] if flag == "--settings" => Scope::File(PathBuf::from(path)),
+        _ => return usage_error("unexpected arguments", UNINSTALL_USAGE),
+    };
+
+    edit_settings(&scope, |settings| {
+        let removed = remove_hooks(settings);
+        prune_empty_events(settings);
+        Ok(format!("Removed {} hook entries", removed))
+    })
+}
+
+/// Load the settings file, apply `edit` and write it back if it changed.
+fn edit_settings(
+    scope: &Scope,
+    edit: impl FnOnce(&mut Value) -> Result<String, String>,
+) -> ExitCode {
+    let Some(path) = scope.path() else {
+        eprintln!("error: could not determine the settings file location");
+        return ExitCode::FAILURE;
+    };
+    let original = match read_settings(&path) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("error: {}: {}", path.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut settings = original.clone();
+    let message = match edit(&mut settings) {
+        Ok(message) => message,
+        Err(e) => {
+            eprintln!("error: {}: {}", path.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if settings == original {
+        println!("Nothing to change in {}", path.display());
+        return ExitCode::SUCCESS;
+    }
+    if let Err(e) = write_settings(&path, &settings) {
+        eprintln!("error: could not write {}: {}", path.display(), e);
+        return ExitCode::FAILURE;
+    }
+    println!("{} in {}", message, path.display());
+    ExitCode::SUCCESS
+}
+
+/// Read settings, treating a missing file as empty. Refuses anything that
+/// is not a JSON object so a broken file is never overwritten.
+fn read_settings(path: &Path) -> Result<Value, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(json!({})),
+        Err(e) => return Err(e.to_string()),
+    };
+    if content.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    match serde_json::from_str(&content) {
+        Ok(settings @ Value::Object(_)) => Ok(settings),
+        Ok(_) => Err("settings are not a JSON object".to_string()),
+        Err(e) => Err(format!("invalid JSON: {}", e)),
+    }
+}
+
+fn write_settings(path: &Path, settings: &Value) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut content = serde_json::to_string_pretty(settings)?;
+    content.push('\n');
+    fs::write(path, content)
+}
+
+/// Append one matcher group per event, running `command`.
+fn add_hooks(
+    settings: &mut Value,
+    command: &str,
+    events: &[(&str, Option<&str>)],
+) -> Result<(), String> {
+    let hooks = settings
+        .as_object_mut()
+        .ok_or("settings are not a JSON object")?
+        .entry("hooks")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or("\"hooks\" is not a JSON object")?;
+
+    for (event, matcher) in events {
+        let mut group = Map::new();
+        if let Some(matcher) = matcher {
+            group.insert("matcher".to_string(), json!(matcher));
+        }
+        group.insert(
+            "hooks".to_string(),
+            json!([{ "type": "command", "command": command, "timeout": 1 }]),
+        );
+        hooks
+            .entry(*event)
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or_else(|| format!("\"hooks.{}\" is not a JSON array", event))?
+            .push(Value::Object(group));
+    }
+    Ok(())
+}
+
+/// Remove our hook commands from every event, dropping groups left empty.
+/// Returns the number of commands removed.
+fn remove_hooks(settings: &mut Value) -> usize {
+    let Some(hooks) = settings.get_mut("hooks").and_then(Value::as_object_mut) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for groups in hooks.values_mut().filter_map(Value::as_array_mut) {
+        for group in groups.iter_mut() {
+            if let Some(commands) = group.get_mut("hooks").and_then(Value::as_array_mut) {
+                let before = commands.len();
+                commands.retain(|hook| !is_our_hook(hook));
+                removed += before - commands.len();
+            }
+        }
+        groups.retain(|group| {
+            group
+                .get("hooks")
+                .and_then(Value::as_array)
+                .is_none_or(|commands| !commands.is_empty())
+        });
+    }
+    removed
+}
+
+/// Drop events without groups, and `hooks` itself once empty.
+fn prune_empty_events(settings: &mut Value) {
+    let Some(settings) = settings.as_object_mut() else {
+        return;
+    };
+    if let Some(hooks) = settings.get_mut("hooks").and_then(Value::as_object_mut) {
+        hooks.retain(|_, groups| groups.as_array().is_none_or(|groups| !groups.is_empty()));
+        if hooks.is_empty() {
+            settings.shift_remove("hooks");
+        }
+    }
+}
+
+/// Check if a hook entry runs this binary, by any path.
+pub(super) fn is_our_hook(hook: &Value) -> bool {
+    hook.get("command")
+        .and_then(Value::as_str)
+        .and_then(|command| command.split_whitespace().next())
+        .and_then(|program| Path::new(program).file_stem())
+        .is_some_and(|stem| stem == BINARY_NAME)
+}
+
+/// The executable `command` runs: its first word, as a path if it has a
+/// slash and otherwise looked up in `PATH`.
+pub(super) fn resolve_program(command: &str) -> Option<PathBuf> {
+    let program = command.split_whitespace().next()?;
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Check if two paths name the same file.
+pub(super) fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
@@ -0,0 +1,64 @@
+//! Command-line subcommands. Without arguments the binary runs as a hook.
+
+mod audit;
+mod check;
+#[cfg(unix)]
+pub mod daemon;
+mod doctor;
+mod explain;
+mod init;
+mod install;
+mod rules;
+mod schema;
+mod test;
+mod unlock;
+mod validate;
+
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: aca-safety-net                 Run as a Claude Code hook (reads JSON on stdin)
+       aca-safety-net audit [tail|query|export] [options]
+       aca-safety-net check [--tool TOOL] <COMMAND|PATH>   Show the decision for one call
+       aca-safety-net doctor              Check the hook registration, config and audit log
+       aca-safety-net explain <COMMAND>   Trace how a Bash command is evaluated
+       aca-safety-net init [--user|--project]   Write a starter project config and register the hook
+       aca-safety-net install [--user|--project]   Register the hook with Claude Code
+       aca-safety-net rules [--json]      List the effective rules, flagging inactive ones
+       aca-safety-net test                Run the [[tests]] from the config
+       aca-safety-net uninstall [--user|--project]   Remove the hook from Claude Code
+       aca-safety-net unlock <SESSION_ID>   Lift a session lockdown
+       aca-safety-net --validate-config [--cwd DIR]   Report every config problem
+       aca-safety-net --schema            Print the JSON Schema of the config format
+       aca-safety-net --daemon [--socket PATH]   Answer hook calls from a long-lived process";
+
+/// Run the subcommand named by `args[0]`.
+pub fn run(args: &[String]) -> ExitCode {
+    match args[0].as_str() {
+        "audit" => audit::run(&args[1..]),
+        "check" => check::run(&args[1..]),
+        "doctor" => doctor::run(&args[1..]),
+        "explain" => explain::run(&args[1..]),
+        "init" => init::run(&args[1..]),
+        "install" => install::install(&args[1..]),
+        "rules" => rules::run(&args[1..]),
+        "test" => test::run(&args[1..]),
+        "uninstall" => install::uninstall(&args[1..]),
+        "unlock" => unlock::run(&args[1..]),
+        "--validate-config" => validate::run(&args[1..]),
+        "--schema" => schema::run(&args[1..]),
+        #[cfg(unix)]
+        "--daemon" => daemon::run(&args[1..]),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        other => usage_error(&format!("unknown command '{}'", other), USAGE),
+    }
+}
+
+/// Report a usage error and return the usage exit code.
+fn usage_error(message: &str, usage: &str) -> ExitCode {
+    eprintln!("error: {}\n\n{}", message, usage);
+    ExitCode::from(64)
+}
@@ -0,0 +1,246 @@
+//! `rules`: list the effective ruleset.
+//!
+//! Shows what the merged user and project config leaves in force: every
+//! built-in rule id with the setting that turns it off, the deny and custom
+//! rules, and the sensitive file patterns. Entries that are off, never
+//! apply or were replaced are listed too, with the reason.
+
+use aca_safety_net::config::Config;
+use aca_safety_net::rules::BUILTIN_RULES;
+use chrono::Utc;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::usage_error;
+use super::validate::{describe_custom, describe_deny};
+
+const USAGE: &str = "\
+Usage: aca-safety-net rules [--json] [--cwd DIR]
+
+Lists every built-in rule id, deny rule, custom rule and sensitive file
+pattern of the merged user and project config. Rules that are turned off,
+can never apply, have a configured severity, override or an active
+exception, and built-in entries a config replaced, are flagged.
+
+Options:
+  --json     Print one JSON object instead of text
+  --cwd DIR  Directory whose project config to merge (default: current
+             directory)";
+
+/// One listed rule or pattern.
+#[derive(Serialize)]
+struct Entry {
+    /// Rule id, deny rule or pattern, as printed.
+    name: String,
+    /// Whether the entry can affect a decision.
+    active: bool,
+    /// Why it is off, and settings that change what it does.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Listing {
+    builtin: Vec<Entry>,
+    deny: Vec<Entry>,
+    rules: Vec<Entry>,
+    sensitive_files: Vec<Entry>,
+    allowed_files: Vec<Entry>,
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let mut json = false;
+    let mut cwd = std::env::current_dir().ok();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--cwd" => match args.next() {
+                Some(dir) => cwd = Some(PathBuf::from(dir)),
+                None => return usage_error("--cwd requires a value", USAGE),
+            },
+            other => return usage_error(&format!("unexpected argument '{}'", other), USAGE),
+        }
+    }
+    let config = match Config::load(cwd.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: could not load config: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let listing = list(&config);
+    if json {
+        match serde_json::to_string_pretty(&listing) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("error: could not serialize rules: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        let sections = [
+            ("Built-in rules", &listing.builtin),
+            ("Deny rules", &listing.deny),
+            ("Custom rules", &listing.rules),
+            ("Sensitive files", &listing.sensitive_files),
+            ("Allowed files", &listing.allowed_files),
+        ];
+        for (i, (title, entries)) in sections.into_iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            print_section(title, entries);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn list(config: &Config) -> Listing {
+    let defaults = Config::default();
+
+    let builtin = BUILTIN_RULES
+        .iter()
+        .map(|rule| {
+            let mut notes = Vec::new();
+            let active = rule.is_enabled(config);
+            if let (false, Some(setting)) = (active, rule.setting) {
+                notes.push(format!("off: {}", setting));
+            }
+            let mut entry = Entry {
+                name: rule.id.to_string(),
+                active,
+                notes,
+            };
+            annotate(&mut entry, rule.id, config);
+            entry
+        })
+        .collect();
+
+    // Every config file brings the built-in deny rules along; list them once
+    let mut deny: Vec<Entry> = Vec::new();
+    let mut listed = Vec::new();
+    for (i, rule) in config.deny.iter().enumerate() {
+        let name = describe_deny(rule);
+        if listed.contains(&name) {
+            continue;
+        }
+        listed.push(name.clone());
+        let shadowed = config.shadowed_deny_rule(i);
+        let mut entry = Entry {
+            name,
+            active: shadowed.is_none(),
+            notes: shadowed
+                .map(|reason| format!("never applies: {}", reason))
+                .into_iter()
+                .collect(),
+        };
+        annotate(&mut entry, &rule.reason, config);
+        deny.push(entry);
+    }
+    for rule in &defaults.deny {
+        let name = describe_deny(rule);
+        if !listed.contains(&name) {
+            deny.push(replaced(name));
+        }
+    }
+
+    let rules = config
+        .rules
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            let shadowed = config.shadowed_custom_rule(i);
+            let mut entry = Entry {
+                name: describe_custom(rule),
+                active: shadowed.is_none(),
+                notes: shadowed
+                    .map(|reason| format!("never applies: {}", reason))
+                    .into_iter()
+                    .collect(),
+            };
+            annotate(&mut entry, &rule.name, config);
+            entry
+        })
+        .collect();
+
+    Listing {
+        builtin,
+        deny,
+        rules,
+        sensitive_files: patterns(&config.sensitive_files, &defaults.sensitive_files),
+        allowed_files: patterns(&config.allowed_files, &defaults.allowed_files),
+    }
+}
+
+/// Note the configured severity, override and active exceptions of rule
+/// `id`.
+fn annotate(entry: &mut Entry, id: &str, config: &Config) {
+    if let Some(severity) = config.severity.rules.get(id) {
+        entry.notes.push(format!("severity {}", severity));
+    }
+    if let Some(action) = config.overrides.get(id) {
+        entry.notes.push(format!("overridden: {}", action));
+    }
+    let now = Utc::now();
+    for exception in &config.exceptions {
+        if exception.rule == id && exception.expires > now {
+            entry.notes.push(format!(
+                "exception for '{}' until {}",
+                exception.pattern,
+                exception.expires.format("%Y-%m-%d")
+            ));
+        }
+    }
+}
+
+/// A built-in entry a config's `[merge]` setting replaced.
+fn replaced(name: String) -> Entry {
+    Entry {
+        name,
+        active: false,
+        notes: vec!["built-in, replaced by [merge]".to_string()],
+    }
+}
+
+/// `effective` patterns, deduplicated, followed by the `defaults` missing
+/// from them.
+fn patterns(effective: &[String], defaults: &[String]) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+    for pattern in effective {
+        if entries.iter().any(|entry| entry.name == *pattern) {
+            continue;
+        }
+        entries.push(Entry {
+            name: pattern.clone(),
+            active: true,
+            notes: Vec::new(),
+        });
+    }
+    for pattern in defaults {
+        if !effective.contains(pattern) {
+            entries.push(replaced(pattern.clone()));
+        }
+    }
+    entries
+}
+
+fn print_section(title: &str, entries: &[Entry]) {
+    let off = entries.iter().filter(|entry| !entry.active).count();
+    if off > 0 {
+        println!("{} ({}, {} off):", title, entries.len(), off);
+    } else {
+        println!("{} ({}):", title, entries.len());
+    }
+    for entry in entries {
+        let marker = if entry.active { ' ' } else { '-' };
+        if entry.notes.is_empty() {
+            println!(" {} {}", marker, entry.name);
+        } else {
+            println!(" {} {}  [{}]", marker, entry.name, entry.notes.join("; "));
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! `--schema`: print the JSON Schema of the config format.
+
+use aca_safety_net::config::Config;
+use std::process::ExitCode;
+
+use super::usage_error;
+
+const USAGE: &str = "\
+Usage: aca-safety-net --schema
+
+Prints a JSON Schema of the config file format, for editor completion and
+validation of config.toml and .security-hook.toml in CI.";
+
+pub fn run(args: &[String]) -> ExitCode {
+    if !args.is_empty() {
+        return usage_error("unexpected arguments", USAGE);
+    }
+    let schema = schemars::schema_for!(Config);
+    match serde_json::to_string_pretty(&schema) {
+        Ok(json) => {
+            println!("{}", json);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: could not serialize schema: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
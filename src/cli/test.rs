@@ -0,0 +1,95 @@
+//! `test`: run the `[[tests]]` policy regression tests from the config.
+
+use aca_safety_net::config::{CompiledConfig, Config, PolicyTest};
+use aca_safety_net::decision::Decision;
+use aca_safety_net::evaluate;
+use aca_safety_net::input::HookInput;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::usage_error;
+
+const USAGE: &str = "\
+Usage: aca-safety-net test [--cwd DIR]
+
+Runs every [[tests]] entry of the merged user and project config and reports
+pass/fail. Exits 1 if any test fails.
+
+  [[tests]]
+  name = \"force push is blocked\"   # optional
+  tool = \"Bash\"                    # Bash (default), Read, Edit or Write
+  input = \"git push -f origin main\"
+  expect = \"block\"                 # allow, block, ask, warn or rewrite
+  rule = \"git.push.force\"          # optional";
+
+pub fn run(args: &[String]) -> ExitCode {
+    let cwd = match args {
+        [] => std::env::current_dir().ok(),
+        [flag, dir] if flag == "--cwd" => Some(PathBuf::from(dir)),
+        _ => return usage_error("unexpected arguments", USAGE),
+    };
+    let config = match Config::load(cwd.as_deref()).and_then(Config::compile) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: could not load config: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if config.raw.tests.is_empty() {
+        println!("No [[tests]] in config.");
+        return ExitCode::SUCCESS;
+    }
+
+    let cwd = cwd.map(|dir| dir.display().to_string());
+    let mut failed = 0;
+    for test in &config.raw.tests {
+        let name = test.name.as_deref().unwrap_or(&test.input);
+        match run_test(test, &config, cwd.clone()) {
+            Ok(()) => println!("PASS  {}", name),
+            Err(message) => {
+                failed += 1;
+                println!("FAIL  {}\n      {}", name, message);
+            }
+        }
+    }
+
+    let total = config.raw.tests.len();
+    println!("\n{} passed, {} failed", total - failed, failed);
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Evaluate one test; the error describes the mismatch.
+fn run_test(test: &PolicyTest, config: &CompiledConfig, cwd: Option<String>) -> Result<(), String> {
+    let input = HookInput::for_tool(&test.tool, &test.input, cwd)
+        .ok_or_else(|| format!("unsupported tool '{}'", test.tool))?;
+    let decision = evaluate(&input, config);
+
+    if !test.expect.matches(&decision) {
+        return Err(format!(
+            "expected {}, got {}",
+            format!("{:?}", test.expect).to_lowercase(),
+            describe(&decision)
+        ));
+    }
+    if let Some(rule) = &test.rule
+        && decision.rule() != Some(rule.as_str())
+    {
+        return Err(format!(
+            "expected rule {}, got {}",
+            rule,
+            describe(&decision)
+        ));
+    }
+    Ok(())
+}
+
+fn describe(decision: &Decision) -> String {
+    match decision.rule() {
+        Some(rule) => format!("{} ({})", decision.label(), rule),
+        None => decision.label().to_string(),
+    }
+}
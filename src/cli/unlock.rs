@@ -0,0 +1,43 @@
+//! `unlock`: lift a session lockdown after reviewing what happened.
+
+use aca_safety_net::config::Config;
+use aca_safety_net::session::{SessionStore, unlock_session};
+use std::process::ExitCode;
+
+use super::usage_error;
+
+const USAGE: &str = "Usage: aca-safety-net unlock <SESSION_ID>";
+
+pub fn run(args: &[String]) -> ExitCode {
+    let [session_id] = args else {
+        return usage_error("expected exactly one session id", USAGE);
+    };
+
+    let cwd = std::env::current_dir().ok();
+    let config = match Config::load(cwd.as_deref()).and_then(Config::compile) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: could not load config: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(store) = SessionStore::from_config(&config) else {
+        eprintln!("error: no session state directory available");
+        return ExitCode::FAILURE;
+    };
+
+    match unlock_session(&store, session_id) {
+        Ok(true) => {
+            println!("Session {} unlocked.", session_id);
+            ExitCode::SUCCESS
+        }
+        Ok(false) => {
+            println!("Session {} was not locked.", session_id);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
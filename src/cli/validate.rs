@@ -0,0 +1,148 @@
+//! `--validate-config`: report every problem in the merged config.
+//!
+//! The hook stops at the first invalid pattern and fails with a one-line
+//! error, ignores misspelled keys and never compiles a custom rule until a
+//! call reaches it. This checks all of them at once, before a session does.
+
+use aca_safety_net::config::{Config, CustomRule, DenyRule};
+use chrono::Utc;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::usage_error;
+
+const USAGE: &str = "\
+Usage: aca-safety-net --validate-config [--cwd DIR]
+
+Loads and compiles the merged user and project config, then prints every
+invalid pattern, unknown key and rule that can never apply, followed by the
+effective deny, allow and custom rules and exceptions. Exits 1 on problems.
+
+Options:
+  --cwd DIR  Directory whose project config to merge (default: current
+             directory)";
+
+pub fn run(args: &[String]) -> ExitCode {
+    let cwd = match args {
+        [] => std::env::current_dir().ok(),
+        [flag, dir] if flag == "--cwd" => Some(PathBuf::from(dir)),
+        _ => return usage_error("unexpected arguments", USAGE),
+    };
+    let loaded = Config::load(cwd.as_deref())
+        .and_then(|config| Ok((Config::unknown_keys(cwd.as_deref())?, config)));
+    let (unknown_keys, config) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("error: could not load config: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut pattern_errors = config.pattern_errors();
+    let shadowed = config.shadowed_rules();
+    if pattern_errors.is_empty()
+        && let Err(e) = config.clone().compile()
+    {
+        pattern_errors.push(e.to_string());
+    }
+
+    println!("Config files:");
+    if config.sources.is_empty() {
+        println!("  (none, built-in defaults only)");
+    }
+    for source in &config.sources {
+        println!("  {}", source.path);
+    }
+    print_rules(&config);
+
+    let problems = pattern_errors.len() + unknown_keys.len() + shadowed.len();
+    print_problems("Invalid patterns", &pattern_errors);
+    print_problems("Unknown keys", &unknown_keys);
+    print_problems("Shadowed rules", &shadowed);
+    if problems > 0 {
+        println!("\n{} problem(s) found", problems);
+        ExitCode::FAILURE
+    } else {
+        println!("\nConfig OK");
+        ExitCode::SUCCESS
+    }
+}
+
+/// Print the rules in the order the hook consults them.
+fn print_rules(config: &Config) {
+    println!("\nAllow rules ({}):", config.allow.len());
+    for rule in &config.allow {
+        println!("  {:<9} {}{}", rule.tool, rule.pattern, scope(&rule.paths));
+    }
+
+    println!("\nDeny rules ({}):", config.deny.len());
+    for rule in &config.deny {
+        println!("  {}", describe_deny(rule));
+    }
+
+    println!("\nCustom rules ({}):", config.rules.len());
+    for rule in &config.rules {
+        println!("  {}", describe_custom(rule));
+    }
+
+    println!("\nExceptions ({}):", config.exceptions.len());
+    let now = Utc::now();
+    for exception in &config.exceptions {
+        let expired = if exception.expires <= now {
+            " (expired)"
+        } else {
+            ""
+        };
+        println!(
+            "  {:<24} {}  until {}{}",
+            exception.rule,
+            exception.pattern,
+            exception.expires.to_rfc3339(),
+            expired
+        );
+    }
+}
+
+pub(super) fn describe_deny(rule: &DenyRule) -> String {
+    let severity = rule
+        .severity
+        .map(|severity| format!(" [{}]", severity))
+        .unwrap_or_default();
+    format!(
+        "{:<9} {}  -> {}{}{}",
+        rule.tool,
+        rule.pattern,
+        rule.reason,
+        severity,
+        scope(&rule.paths)
+    )
+}
+
+pub(super) fn describe_custom(rule: &CustomRule) -> String {
+    format!(
+        "{:<24} {:<9} {:<5} {}{}",
+        rule.name,
+        rule.tool,
+        rule.action,
+        rule.pattern,
+        scope(&rule.paths)
+    )
+}
+
+fn scope(paths: &[String]) -> String {
+    if paths.is_empty() {
+        String::new()
+    } else {
+        format!("  (in {})", paths.join(", "))
+    }
+}
+
+fn print_problems(title: &str, problems: &[String]) {
+    if problems.is_empty() {
+        return;
+    }
+    println!("\n{} ({}):", title, problems.len());
+    for problem in problems {
+        println!("  {}", problem.replace('\n', "\n    "));
+    }
+}
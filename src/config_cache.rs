@@ -0,0 +1,143 @@
+//! On-disk cache of merged configs, keyed by a hash of their sources.
+//!
+//! The hook runs once per tool call, so every invocation would otherwise
+//! re-parse the same TOML files. Entries hold the merged [`Config`], or the
+//! rules converted from an imported gitleaks file, as JSON; they are
+//! written atomically and any read or write failure is treated as a cache
+//! miss.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Entries older than this are removed when a new one is written.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// One cache entry location.
+pub(crate) struct ConfigCache {
+    dir: PathBuf,
+    key: String,
+}
+
+impl ConfigCache {
+    /// The entry for `key`, or `None` when caching is disabled or there is
+    /// no cache directory.
+    ///
+    /// Respects `ACO_SAFETY_NET_NO_CACHE` (disable) and
+    /// `ACO_SAFETY_NET_CACHE_DIR` (location).
+    pub(crate) fn open(key: &str) -> Option<Self> {
+        // Unit tests never touch the user's cache
+        if cfg!(test) || std::env::var_os("ACO_SAFETY_NET_NO_CACHE").is_some() {
+            return None;
+        }
+        let dir = match std::env::var_os("ACO_SAFETY_NET_CACHE_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::cache_dir()?.join("aca-safety-net/config"),
+        };
+        Some(Self::in_dir(dir, key))
+    }
+
+    pub(crate) fn in_dir(dir: PathBuf, key: &str) -> Self {
+        Self {
+            dir,
+            key: key.to_string(),
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(format!("{}.json", self.key))
+    }
+
+    /// The cached value, if present and readable.
+    pub(crate) fn get<T: DeserializeOwned>(&self) -> Option<T> {
+        let content = fs::read(self.path()).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// Store `value`, ignoring failures.
+    pub(crate) fn put<T: Serialize>(&self, value: &T) {
+        if let Err(e) = self.try_put(value) {
+            tracing::debug!(dir = %self.dir.display(), error = %e, "could not write config cache");
+        }
+        self.prune();
+    }
+
+    fn try_put<T: Serialize>(&self, value: &T) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        // Concurrent hooks may race on the same key; rename keeps each
+        // reader from seeing a partial file
+        let tmp = self
+            .dir
+            .join(format!(".{}.{}.tmp", self.key, std::process::id()));
+        fs::write(&tmp, serde_json::to_vec(value)?)?;
+        fs::rename(&tmp, self.path()).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+    }
+
+    /// Remove entries for configs that have not been written in a while.
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let now = SystemTime::now();
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() > MAX_AGE);
+            if expired {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = ConfigCache::in_dir(dir.path().join("config"), "abc");
+        assert!(cache.get::<Config>().is_none());
+
+        let mut config: Config = toml::from_str(
+            r#"
+            mode = "shadow"
+            [[rules]]
+            name = "no-curl"
+            tool = "Bash"
+            pattern = 'curl\s'
+            reason = "no network"
+            "#,
+        )
+        .unwrap();
+        config.git.block_destructive = false;
+        cache.put(&config);
+
+        let cached: Config = cache.get().unwrap();
+        assert_eq!(cached.mode, config.mode);
+        assert_eq!(cached.rules.len(), 1);
+        assert!(!cached.git.block_destructive);
+        assert_eq!(cached.sensitive_files, config.sensitive_files);
+        // Other keys are separate entries
+        assert!(
+            ConfigCache::in_dir(cache.dir.clone(), "def")
+                .get::<Config>()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_corrupt_entry_is_a_miss() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = ConfigCache::in_dir(dir.path().to_path_buf(), "abc");
+        fs::write(cache.path(), "{not json").unwrap();
+        assert!(cache.get::<Config>().is_none());
+    }
+}
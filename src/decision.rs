@@ -1,6 +1,8 @@
 //! Decision types for hook responses.
 
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 /// The result of analyzing a tool invocation.
 #[derive(Debug, Clone)]
@@ -11,6 +13,77 @@ pub enum Decision {
     Block(BlockInfo),
     /// Ask the user for approval.
     Ask(AskInfo),
+    /// Allow the tool, but tell Claude why the action is risky.
+    Warn(WarnInfo),
+    /// Run a safer version of the tool input instead.
+    Rewrite(RewriteInfo),
+}
+
+/// How serious the risk behind a block or ask is.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth a second look, unlikely to do harm.
+    Info,
+    /// Risky, but often legitimate.
+    Warn,
+    /// Leaks a secret or does damage that is hard to undo.
+    Critical,
+}
+
+impl Severity {
+    /// The lowercase name used in config, messages and audit entries.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warn => "warn",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// CEF severity (0-10).
+    pub fn score(self) -> u8 {
+        match self {
+            Severity::Info => 3,
+            Severity::Warn => 5,
+            Severity::Critical => 8,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a rule does when it fires, as set in `[overrides]`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Let the call through, as if the rule had not fired.
+    Allow,
+    /// Allow the call, but tell Claude why it is risky.
+    Warn,
+    /// Ask the user for approval.
+    Ask,
+    /// Block the call.
+    Block,
+}
+
+impl fmt::Display for RuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RuleAction::Allow => "allow",
+            RuleAction::Warn => "warn",
+            RuleAction::Ask => "ask",
+            RuleAction::Block => "block",
+        })
+    }
 }
 
 /// Information about why a tool was blocked.
@@ -20,9 +93,27 @@ pub struct BlockInfo {
     pub reason: String,
     /// The rule that triggered the block.
     pub rule: String,
+    /// How serious the blocked operation is (default: critical).
+    pub severity: Severity,
     /// Optional details (e.g., matched pattern).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
+    /// Safe alternatives to try instead of the blocked operation.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<String>,
+    /// Terse message for Claude; falls back to `reason`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_reason: Option<String>,
+    /// Explanation for the user (policy, how to override); falls back to `reason`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_reason: Option<String>,
+    /// Further blocks found in the same call, reported along with this one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub also: Vec<BlockInfo>,
+    /// Asks, warnings and rewrites found in the same call, which decide
+    /// once every block is excepted or overridden.
+    #[serde(skip)]
+    pub findings: Vec<Decision>,
 }
 
 /// Information about why user approval is required.
@@ -32,9 +123,43 @@ pub struct AskInfo {
     pub reason: String,
     /// The rule that triggered the ask.
     pub rule: String,
+    /// How serious the operation is (default: warn).
+    pub severity: Severity,
     /// Suggestion for alternative approach.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggestion: Option<String>,
+    /// Terse message for Claude; falls back to `reason`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_reason: Option<String>,
+    /// Explanation for the user (policy, how to override); falls back to `reason`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_reason: Option<String>,
+    /// Stable key for remembering the user's approval.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_key: Option<String>,
+}
+
+/// Information about a risky-but-allowed operation.
+#[derive(Debug, Clone, Serialize)]
+pub struct WarnInfo {
+    /// Human-readable explanation of the risk.
+    pub reason: String,
+    /// The rule that triggered the warning.
+    pub rule: String,
+    /// Suggestion for a safer approach.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Information about a safe rewrite of the tool input.
+#[derive(Debug, Clone, Serialize)]
+pub struct RewriteInfo {
+    /// Human-readable explanation of what was changed and why.
+    pub reason: String,
+    /// The rule that produced the rewrite.
+    pub rule: String,
+    /// The complete replacement tool input.
+    pub updated_input: serde_json::Value,
 }
 
 impl BlockInfo {
@@ -42,14 +167,73 @@ impl BlockInfo {
         Self {
             rule: rule.into(),
             reason: reason.into(),
+            severity: Severity::Critical,
             details: None,
+            alternatives: Vec::new(),
+            model_reason: None,
+            user_reason: None,
+            also: Vec::new(),
+            findings: Vec::new(),
         }
     }
 
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
     pub fn with_details(mut self, details: impl Into<String>) -> Self {
         self.details = Some(details.into());
         self
     }
+
+    pub fn with_alternatives<I, S>(mut self, alternatives: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.alternatives
+            .extend(alternatives.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_model_reason(mut self, model_reason: impl Into<String>) -> Self {
+        self.model_reason = Some(model_reason.into());
+        self
+    }
+
+    pub fn with_user_reason(mut self, user_reason: impl Into<String>) -> Self {
+        self.user_reason = Some(user_reason.into());
+        self
+    }
+
+    /// The message meant for Claude.
+    pub fn model_message(&self) -> &str {
+        self.model_reason.as_deref().unwrap_or(&self.reason)
+    }
+
+    /// The message meant for the user.
+    pub fn user_message(&self) -> &str {
+        self.user_reason.as_deref().unwrap_or(&self.reason)
+    }
+
+    /// The same finding as a request for approval, suggesting the first
+    /// alternative.
+    pub fn into_ask(self) -> AskInfo {
+        let reason = match self.details {
+            Some(details) => format!("{} ({})", self.reason, details),
+            None => self.reason,
+        };
+        AskInfo {
+            rule: self.rule,
+            reason,
+            severity: self.severity,
+            suggestion: self.alternatives.into_iter().next(),
+            model_reason: self.model_reason,
+            user_reason: self.user_reason,
+            approval_key: None,
+        }
+    }
 }
 
 impl AskInfo {
@@ -57,14 +241,137 @@ impl AskInfo {
         Self {
             rule: rule.into(),
             reason: reason.into(),
+            severity: Severity::Warn,
             suggestion: None,
+            model_reason: None,
+            user_reason: None,
+            approval_key: None,
         }
     }
 
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_approval_key(mut self, approval_key: impl Into<String>) -> Self {
+        self.approval_key = Some(approval_key.into());
+        self
+    }
+
     pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
         self.suggestion = Some(suggestion.into());
         self
     }
+
+    pub fn with_model_reason(mut self, model_reason: impl Into<String>) -> Self {
+        self.model_reason = Some(model_reason.into());
+        self
+    }
+
+    pub fn with_user_reason(mut self, user_reason: impl Into<String>) -> Self {
+        self.user_reason = Some(user_reason.into());
+        self
+    }
+
+    /// The message meant for Claude.
+    pub fn model_message(&self) -> &str {
+        self.model_reason.as_deref().unwrap_or(&self.reason)
+    }
+
+    /// The message meant for the user.
+    pub fn user_message(&self) -> &str {
+        self.user_reason.as_deref().unwrap_or(&self.reason)
+    }
+
+    /// The same finding as an advisory warning.
+    pub fn into_warn(self) -> WarnInfo {
+        WarnInfo {
+            reason: self.reason,
+            rule: self.rule,
+            suggestion: self.suggestion,
+        }
+    }
+
+    /// The same finding as a block, with the suggestion as alternative.
+    pub fn into_block(self) -> BlockInfo {
+        BlockInfo {
+            reason: self.reason,
+            rule: self.rule,
+            severity: self.severity,
+            details: None,
+            alternatives: self.suggestion.into_iter().collect(),
+            model_reason: self.model_reason,
+            user_reason: self.user_reason,
+            also: Vec::new(),
+            findings: Vec::new(),
+        }
+    }
+}
+
+impl WarnInfo {
+    pub fn new(rule: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            rule: rule.into(),
+            reason: reason.into(),
+            suggestion: None,
+        }
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    /// The same finding as a request for approval.
+    pub fn into_ask(self) -> AskInfo {
+        AskInfo {
+            suggestion: self.suggestion,
+            ..AskInfo::new(self.rule, self.reason)
+        }
+    }
+}
+
+/// A decision reported by an external analyzer (a plugin or validator) as
+/// `{"decision": "block", "reason": "...", "rule": "..."}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalVerdict {
+    /// "block", "ask", "warn" or "allow".
+    pub decision: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub rule: Option<String>,
+}
+
+impl ExternalVerdict {
+    /// Convert to a decision, with `rule` and `reason` as fallbacks.
+    /// `Ok(None)` means allow; an unknown decision is an error.
+    pub fn into_decision(self, rule: &str, reason: &str) -> Result<Option<Decision>, String> {
+        let rule = self.rule.unwrap_or_else(|| rule.to_string());
+        let reason = self.reason.unwrap_or_else(|| reason.to_string());
+        match self.decision.as_str() {
+            "allow" => Ok(None),
+            "block" => Ok(Some(Decision::block(rule, reason))),
+            "ask" => Ok(Some(Decision::ask(rule, reason))),
+            "warn" => Ok(Some(Decision::warn(rule, reason))),
+            other => Err(format!("unknown decision '{}'", other)),
+        }
+    }
+}
+
+impl RewriteInfo {
+    pub fn new(
+        rule: impl Into<String>,
+        reason: impl Into<String>,
+        updated_input: serde_json::Value,
+    ) -> Self {
+        Self {
+            rule: rule.into(),
+            reason: reason.into(),
+            updated_input,
+        }
+    }
 }
 
 impl Decision {
@@ -83,6 +390,23 @@ impl Decision {
         Decision::Ask(AskInfo::new(rule, reason))
     }
 
+    /// Create a warn decision (allowed, with advisory context for Claude).
+    pub fn warn(rule: impl Into<String>, reason: impl Into<String>) -> Self {
+        Decision::Warn(WarnInfo::new(rule, reason))
+    }
+
+    /// Attach safe alternatives to a block; other decisions are unchanged.
+    pub fn with_alternatives<I, S>(self, alternatives: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        match self {
+            Decision::Block(info) => Decision::Block(info.with_alternatives(alternatives)),
+            other => other,
+        }
+    }
+
     /// Check if this is a block decision.
     pub fn is_blocked(&self) -> bool {
         matches!(self, Decision::Block(_))
@@ -93,6 +417,16 @@ impl Decision {
         matches!(self, Decision::Ask(_))
     }
 
+    /// Check if this is a warning.
+    pub fn is_warn(&self) -> bool {
+        matches!(self, Decision::Warn(_))
+    }
+
+    /// Check if this rewrites the tool input.
+    pub fn is_rewrite(&self) -> bool {
+        matches!(self, Decision::Rewrite(_))
+    }
+
     /// Get the block info if blocked.
     pub fn block_info(&self) -> Option<&BlockInfo> {
         match self {
@@ -108,6 +442,180 @@ impl Decision {
             _ => None,
         }
     }
+
+    /// Get the warn info if warning.
+    pub fn warn_info(&self) -> Option<&WarnInfo> {
+        match self {
+            Decision::Warn(info) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// Get the rewrite info if rewriting.
+    pub fn rewrite_info(&self) -> Option<&RewriteInfo> {
+        match self {
+            Decision::Rewrite(info) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// Get the rule that produced this decision (`None` for allow).
+    pub fn rule(&self) -> Option<&str> {
+        match self {
+            Decision::Allow => None,
+            Decision::Block(info) => Some(&info.rule),
+            Decision::Ask(info) => Some(&info.rule),
+            Decision::Warn(info) => Some(&info.rule),
+            Decision::Rewrite(info) => Some(&info.rule),
+        }
+    }
+
+    /// Get the severity of a block or ask.
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            Decision::Block(info) => Some(info.severity),
+            Decision::Ask(info) => Some(info.severity),
+            _ => None,
+        }
+    }
+
+    /// Set the severity of a block or ask; other decisions are unchanged.
+    pub fn with_severity(self, severity: Severity) -> Self {
+        match self {
+            Decision::Block(info) => Decision::Block(info.with_severity(severity)),
+            Decision::Ask(info) => Decision::Ask(info.with_severity(severity)),
+            other => other,
+        }
+    }
+
+    /// Make the rule that fired do `action` instead: block, ask, warn or
+    /// let the call through. Allows and rewrites are unchanged. Meant for a
+    /// single violation: a block that becomes something else drops `also`
+    /// and `findings`.
+    pub fn with_action(self, action: RuleAction) -> Self {
+        let ask = match self {
+            Decision::Allow | Decision::Rewrite(_) => return self,
+            Decision::Block(info) if action == RuleAction::Block => {
+                return Decision::Block(info);
+            }
+            Decision::Block(info) => info.into_ask(),
+            Decision::Ask(info) => info,
+            Decision::Warn(info) => info.into_ask(),
+        };
+        match action {
+            RuleAction::Allow => Decision::Allow,
+            RuleAction::Warn => Decision::Warn(ask.into_warn()),
+            RuleAction::Ask => Decision::Ask(ask),
+            RuleAction::Block => Decision::Block(ask.into_block()),
+        }
+    }
+
+    /// Get the human-readable reason for this decision (`None` for allow).
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Decision::Allow => None,
+            Decision::Block(info) => Some(&info.reason),
+            Decision::Ask(info) => Some(&info.reason),
+            Decision::Warn(info) => Some(&info.reason),
+            Decision::Rewrite(info) => Some(&info.reason),
+        }
+    }
+
+    /// The decision as a single lowercase label ("block", "ask", ...).
+    pub fn label(&self) -> &'static str {
+        match self {
+            Decision::Allow => "allow",
+            Decision::Block(_) => "block",
+            Decision::Ask(_) => "ask",
+            Decision::Warn(_) => "warn",
+            Decision::Rewrite(_) => "rewrite",
+        }
+    }
+
+    /// Report the blocks of `other` along with this decision's.
+    ///
+    /// The first block decides and later ones go into its `also`, so fixing
+    /// one violation does not just reveal the next. Exact repeats are
+    /// dropped. Other findings next to a block go into its `findings`, in
+    /// order; without a block they combine like [`Decision::stricter`].
+    pub fn and_also(self, other: Decision) -> Decision {
+        let (mut first, other) = match (self, other) {
+            (Decision::Block(first), Decision::Block(other)) => (first, other),
+            (Decision::Block(first), Decision::Allow)
+            | (Decision::Allow, Decision::Block(first)) => {
+                return Decision::Block(first);
+            }
+            (Decision::Block(mut first), finding) => {
+                first.findings.push(finding);
+                return Decision::Block(first);
+            }
+            (finding, Decision::Block(mut other)) => {
+                other.findings.insert(0, finding);
+                return Decision::Block(other);
+            }
+            (first, other) => return first.stricter(other),
+        };
+        for violation in Decision::Block(other).into_violations() {
+            let Decision::Block(violation) = violation else {
+                first.findings.push(violation);
+                continue;
+            };
+            let repeated = |seen: &BlockInfo| {
+                seen.rule == violation.rule
+                    && seen.reason == violation.reason
+                    && seen.details == violation.details
+            };
+            if !repeated(&first) && !first.also.iter().any(repeated) {
+                first.also.push(violation);
+            }
+        }
+        Decision::Block(first)
+    }
+
+    /// One decision per violation: the deciding block first, then the
+    /// other blocks and the other findings next to them. Anything but a
+    /// block is its own only violation.
+    pub fn into_violations(self) -> Vec<Decision> {
+        let Decision::Block(mut info) = self else {
+            return vec![self];
+        };
+        let also = std::mem::take(&mut info.also);
+        let findings = std::mem::take(&mut info.findings);
+        std::iter::once(info)
+            .chain(also)
+            .map(Decision::Block)
+            .chain(findings)
+            .collect()
+    }
+
+    /// Combine violations with [`Decision::and_also`], so the first
+    /// remaining block decides.
+    pub fn from_violations(violations: impl IntoIterator<Item = Decision>) -> Decision {
+        violations
+            .into_iter()
+            .fold(Decision::Allow, Decision::and_also)
+    }
+
+    /// Return whichever decision is stricter (Block > Ask > Rewrite > Warn > Allow).
+    ///
+    /// On a tie `self` wins, so the earliest finding is reported.
+    pub fn stricter(self, other: Decision) -> Decision {
+        if other.strictness() > self.strictness() {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn strictness(&self) -> u8 {
+        match self {
+            Decision::Allow => 0,
+            Decision::Warn(_) => 1,
+            Decision::Rewrite(_) => 2,
+            Decision::Ask(_) => 3,
+            Decision::Block(_) => 4,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -130,9 +638,136 @@ mod tests {
         assert_eq!(info.reason, "test reason");
     }
 
+    #[test]
+    fn test_severity_defaults_and_downgrades() {
+        let block = Decision::Block(
+            BlockInfo::new("git.push.force", "rewrites history")
+                .with_details("main")
+                .with_alternatives(["git push --force-with-lease"]),
+        );
+        assert_eq!(block.severity(), Some(Severity::Critical));
+        assert_eq!(
+            Decision::ask("rule", "reason").severity(),
+            Some(Severity::Warn)
+        );
+        assert_eq!(Decision::warn("rule", "reason").severity(), None);
+
+        let block = block.with_severity(Severity::Info);
+        let ask = block.block_info().unwrap().clone().into_ask();
+        assert_eq!(ask.severity, Severity::Info);
+        assert_eq!(ask.reason, "rewrites history (main)");
+        assert_eq!(
+            ask.suggestion.as_deref(),
+            Some("git push --force-with-lease")
+        );
+        let warn = ask.into_warn();
+        assert_eq!(warn.rule, "git.push.force");
+        assert_eq!(
+            warn.suggestion.as_deref(),
+            Some("git push --force-with-lease")
+        );
+    }
+
     #[test]
     fn test_block_with_details() {
         let d = Decision::Block(BlockInfo::new("rule", "reason").with_details("matched: .env"));
         assert!(d.block_info().unwrap().details.is_some());
     }
+
+    #[test]
+    fn test_block_with_alternatives() {
+        let d = Decision::block("rule", "reason").with_alternatives(["git stash"]);
+        assert_eq!(d.block_info().unwrap().alternatives, vec!["git stash"]);
+        let d = Decision::allow().with_alternatives(["git stash"]);
+        assert!(d.block_info().is_none());
+    }
+
+    #[test]
+    fn test_model_and_user_reasons() {
+        let info = BlockInfo::new("rule", "reason");
+        assert_eq!(info.model_message(), "reason");
+        assert_eq!(info.user_message(), "reason");
+
+        let info = info
+            .with_model_reason("do not retry")
+            .with_user_reason("see the policy page");
+        assert_eq!(info.model_message(), "do not retry");
+        assert_eq!(info.user_message(), "see the policy page");
+    }
+
+    #[test]
+    fn test_warn() {
+        let d = Decision::warn("rule", "risky");
+        assert!(d.is_warn());
+        assert!(!d.is_blocked());
+        assert_eq!(d.warn_info().unwrap().rule, "rule");
+    }
+
+    #[test]
+    fn test_stricter() {
+        let warn = Decision::warn("w", "w");
+        assert!(Decision::allow().stricter(warn.clone()).is_warn());
+        assert!(warn.clone().stricter(Decision::allow()).is_warn());
+        assert!(warn.stricter(Decision::block("b", "b")).is_blocked());
+        let first = Decision::warn("first", "x").stricter(Decision::warn("second", "x"));
+        assert_eq!(first.warn_info().unwrap().rule, "first");
+    }
+
+    #[test]
+    fn test_and_also_collects_blocks() {
+        let decision = Decision::warn("w", "w")
+            .and_also(Decision::block("first", "a"))
+            .and_also(Decision::block("second", "b"))
+            .and_also(Decision::block("first", "a"))
+            .and_also(Decision::ask("q", "q"));
+        let info = decision.block_info().unwrap();
+        assert_eq!(info.rule, "first");
+        assert_eq!(info.also.len(), 1);
+        assert_eq!(info.also[0].rule, "second");
+
+        let findings: Vec<_> = info.findings.iter().map(Decision::label).collect();
+        assert_eq!(findings, ["warn", "ask"]);
+
+        let violations = decision.into_violations();
+        let rules: Vec<_> = violations.iter().filter_map(Decision::rule).collect();
+        assert_eq!(rules, ["first", "second", "w", "q"]);
+        let rest = Decision::from_violations(violations.into_iter().skip(1));
+        assert_eq!(rest.rule(), Some("second"));
+        assert!(rest.block_info().unwrap().also.is_empty());
+        assert_eq!(rest.block_info().unwrap().findings.len(), 2);
+        let rest = Decision::from_violations(rest.into_violations().into_iter().skip(1));
+        assert_eq!(rest.label(), "ask");
+        assert_eq!(Decision::from_violations([]).label(), "allow");
+    }
+
+    #[test]
+    fn test_with_action() {
+        let block = Decision::block("rule", "reason")
+            .with_alternatives(["safer"])
+            .with_severity(Severity::Warn);
+        let ask = block.clone().with_action(RuleAction::Ask);
+        assert_eq!(ask.severity(), Some(Severity::Warn));
+        let warn = ask.clone().with_action(RuleAction::Warn);
+        assert_eq!(
+            warn.warn_info().unwrap().suggestion.as_deref(),
+            Some("safer")
+        );
+        let block = warn.with_action(RuleAction::Block);
+        assert_eq!(block.block_info().unwrap().alternatives, vec!["safer"]);
+        assert_eq!(block.rule(), Some("rule"));
+        assert_eq!(ask.with_action(RuleAction::Allow).label(), "allow");
+        assert_eq!(
+            Decision::allow().with_action(RuleAction::Block).label(),
+            "allow"
+        );
+    }
+
+    #[test]
+    fn test_label_and_reason() {
+        let ask = Decision::ask("deps.edit", "edits a manifest");
+        assert_eq!(ask.label(), "ask");
+        assert_eq!(ask.reason(), Some("edits a manifest"));
+        assert_eq!(Decision::allow().label(), "allow");
+        assert_eq!(Decision::allow().reason(), None);
+    }
 }
@@ -1,6 +1,6 @@
 //! Input parsing for Claude Code hook invocations.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Errors that can occur when parsing hook input.
@@ -11,15 +11,45 @@ pub enum InputError {
 
     #[error("missing required field: {0}")]
     MissingField(&'static str),
+
+    #[error("field '{field}' has the wrong type (expected {expected})")]
+    WrongType {
+        field: &'static str,
+        expected: &'static str,
+    },
+
+    #[error("expected {expected} input, got {actual}")]
+    WrongTool {
+        expected: &'static str,
+        actual: String,
+    },
 }
 
+/// Accepted spellings for each tool_input field, canonical name first.
+const COMMAND_FIELDS: &[&str] = &["command", "cmd"];
+const FILE_PATH_FIELDS: &[&str] = &["file_path", "filePath", "path"];
+const CONTENT_FIELDS: &[&str] = &["content"];
+const OLD_STRING_FIELDS: &[&str] = &["old_string", "oldString"];
+const NEW_STRING_FIELDS: &[&str] = &["new_string", "newString"];
+const REPLACE_ALL_FIELDS: &[&str] = &["replace_all", "replaceAll"];
+const URL_FIELDS: &[&str] = &["url"];
+const PROMPT_FIELDS: &[&str] = &["prompt"];
+const PATTERN_FIELDS: &[&str] = &["pattern"];
+const SEARCH_PATH_FIELDS: &[&str] = &["path"];
+const GLOB_FIELDS: &[&str] = &["glob"];
+const DESCRIPTION_FIELDS: &[&str] = &["description"];
+const SUBAGENT_TYPE_FIELDS: &[&str] = &["subagent_type", "subagentType"];
+
 /// The raw input from Claude Code's PreToolUse hook.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HookInput {
     /// The tool being invoked (e.g., "Bash", "Read", "Write").
+    /// Empty for session events such as SessionEnd.
+    #[serde(default)]
     pub tool_name: String,
 
     /// The tool's input parameters as raw JSON.
+    #[serde(default)]
     pub tool_input: serde_json::Value,
 
     /// Current working directory (optional).
@@ -29,6 +59,10 @@ pub struct HookInput {
     /// Session ID for audit logging (optional).
     #[serde(default)]
     pub session_id: Option<String>,
+
+    /// Hook event ("PreToolUse" when absent).
+    #[serde(default)]
+    pub hook_event_name: Option<String>,
 }
 
 /// Parsed input for the Bash tool.
@@ -71,81 +105,305 @@ pub struct EditInput {
     pub old_string: String,
     /// The new string.
     pub new_string: String,
+    /// Replace every occurrence instead of just the first.
+    pub replace_all: bool,
+}
+
+/// Parsed input for the WebFetch tool.
+#[derive(Debug, Clone)]
+pub struct WebFetchInput {
+    /// The URL to fetch.
+    pub url: String,
+    /// What to extract from the page.
+    pub prompt: String,
+}
+
+/// Parsed input for the Glob tool.
+#[derive(Debug, Clone)]
+pub struct GlobInput {
+    /// The glob pattern, such as `**/*.rs`.
+    pub pattern: String,
+    /// The directory to search (the cwd when absent).
+    pub path: Option<String>,
+}
+
+/// Parsed input for the Grep tool.
+#[derive(Debug, Clone)]
+pub struct GrepInput {
+    /// The regex to search for.
+    pub pattern: String,
+    /// The file or directory to search (the cwd when absent).
+    pub path: Option<String>,
+    /// Glob filtering the files searched.
+    pub glob: Option<String>,
+}
+
+/// Parsed input for the Task tool, which starts a subagent.
+#[derive(Debug, Clone)]
+pub struct TaskInput {
+    /// The instructions the subagent starts with.
+    pub prompt: String,
+    /// Short description of the task.
+    pub description: Option<String>,
+    /// The kind of subagent to start.
+    pub subagent_type: Option<String>,
 }
 
 impl HookInput {
     /// Parse from JSON string.
     pub fn parse(json: &str) -> Result<Self, InputError> {
-        Ok(serde_json::from_str(json)?)
+        let input: Self = serde_json::from_str(json)?;
+        tracing::debug!(
+            tool = %input.tool_name,
+            event = ?input.hook_event_name,
+            cwd = ?input.cwd,
+            "parsed hook input"
+        );
+        Ok(input)
+    }
+
+    /// Build the input Claude Code would send for a command (Bash) or a
+    /// file path (Read, Edit, Write) or a URL (WebFetch) or a pattern (Glob,
+    /// Grep) or a prompt (Task). `None` for other tools.
+    pub fn for_tool(tool: &str, target: &str, cwd: Option<String>) -> Option<Self> {
+        let tool_input = match tool {
+            "Bash" => serde_json::json!({ "command": target }),
+            "Read" => serde_json::json!({ "file_path": target }),
+            "WebFetch" => serde_json::json!({ "url": target, "prompt": "" }),
+            "Glob" | "Grep" => serde_json::json!({ "pattern": target }),
+            "Task" => serde_json::json!({ "prompt": target, "description": "" }),
+            "Write" => serde_json::json!({ "file_path": target, "content": "" }),
+            "Edit" => {
+                serde_json::json!({ "file_path": target, "old_string": "", "new_string": "" })
+            }
+            _ => return None,
+        };
+        Some(Self {
+            tool_name: tool.to_string(),
+            tool_input,
+            cwd,
+            session_id: None,
+            hook_event_name: None,
+        })
+    }
+
+    /// Check if this is a PostToolUse event (the tool already ran).
+    pub fn is_post_tool_use(&self) -> bool {
+        self.hook_event_name.as_deref() == Some("PostToolUse")
+    }
+
+    /// Check if this is a SessionEnd event (the conversation is over).
+    pub fn is_session_end(&self) -> bool {
+        self.hook_event_name.as_deref() == Some("SessionEnd")
+    }
+
+    /// Check if this is a Stop or SubagentStop event (Claude or a subagent
+    /// finished responding).
+    pub fn is_stop(&self) -> bool {
+        matches!(
+            self.hook_event_name.as_deref(),
+            Some("Stop" | "SubagentStop")
+        )
     }
 
     /// Try to extract as Bash input.
     pub fn as_bash(&self) -> Option<BashInput> {
-        if self.tool_name != "Bash" {
-            return None;
-        }
-        let command = self.tool_input.get("command")?.as_str()?.to_string();
-        let timeout = self.tool_input.get("timeout").and_then(|v| v.as_u64());
-        let description = self
-            .tool_input
-            .get("description")
-            .and_then(|v| v.as_str())
-            .map(String::from);
-        Some(BashInput {
-            command,
-            timeout,
-            description,
-        })
+        self.try_as_bash().ok()
     }
 
     /// Try to extract as Read input.
     pub fn as_read(&self) -> Option<ReadInput> {
-        if self.tool_name != "Read" {
-            return None;
-        }
-        let file_path = self.tool_input.get("file_path")?.as_str()?.to_string();
-        let offset = self.tool_input.get("offset").and_then(|v| v.as_u64());
-        let limit = self.tool_input.get("limit").and_then(|v| v.as_u64());
-        Some(ReadInput {
-            file_path,
-            offset,
-            limit,
-        })
+        self.try_as_read().ok()
     }
 
     /// Try to extract as Write input.
     pub fn as_write(&self) -> Option<WriteInput> {
-        if self.tool_name != "Write" {
-            return None;
-        }
-        let file_path = self.tool_input.get("file_path")?.as_str()?.to_string();
-        let content = self.tool_input.get("content")?.as_str()?.to_string();
-        Some(WriteInput { file_path, content })
+        self.try_as_write().ok()
     }
 
     /// Try to extract as Edit input.
     pub fn as_edit(&self) -> Option<EditInput> {
-        if self.tool_name != "Edit" {
-            return None;
-        }
-        let file_path = self.tool_input.get("file_path")?.as_str()?.to_string();
-        let old_string = self.tool_input.get("old_string")?.as_str()?.to_string();
-        let new_string = self.tool_input.get("new_string")?.as_str()?.to_string();
-        Some(EditInput {
-            file_path,
-            old_string,
-            new_string,
+        self.try_as_edit().ok()
+    }
+
+    /// Extract as Bash input, reporting which field was missing or malformed.
+    pub fn try_as_bash(&self) -> Result<BashInput, InputError> {
+        self.expect_tool("Bash")?;
+        Ok(BashInput {
+            command: self.required_str(COMMAND_FIELDS)?,
+            timeout: self.optional_u64(&["timeout"]),
+            description: self.optional_str(&["description"]),
+        })
+    }
+
+    /// Extract as Read input, reporting which field was missing or malformed.
+    pub fn try_as_read(&self) -> Result<ReadInput, InputError> {
+        self.expect_tool("Read")?;
+        Ok(ReadInput {
+            file_path: self.required_str(FILE_PATH_FIELDS)?,
+            offset: self.optional_u64(&["offset"]),
+            limit: self.optional_u64(&["limit"]),
+        })
+    }
+
+    /// Extract as Write input, reporting which field was missing or malformed.
+    pub fn try_as_write(&self) -> Result<WriteInput, InputError> {
+        self.expect_tool("Write")?;
+        Ok(WriteInput {
+            file_path: self.required_str(FILE_PATH_FIELDS)?,
+            content: self.required_str(CONTENT_FIELDS)?,
+        })
+    }
+
+    /// Extract as Edit input, reporting which field was missing or malformed.
+    pub fn try_as_edit(&self) -> Result<EditInput, InputError> {
+        self.expect_tool("Edit")?;
+        Ok(EditInput {
+            file_path: self.required_str(FILE_PATH_FIELDS)?,
+            old_string: self.required_str(OLD_STRING_FIELDS)?,
+            new_string: self.required_str(NEW_STRING_FIELDS)?,
+            replace_all: self.optional_bool(REPLACE_ALL_FIELDS).unwrap_or(false),
+        })
+    }
+
+    /// Extract as WebFetch input, reporting which field was missing or
+    /// malformed. A missing prompt is treated as empty.
+    pub fn try_as_web_fetch(&self) -> Result<WebFetchInput, InputError> {
+        self.expect_tool("WebFetch")?;
+        Ok(WebFetchInput {
+            url: self.required_str(URL_FIELDS)?,
+            prompt: self.optional_str(PROMPT_FIELDS).unwrap_or_default(),
+        })
+    }
+
+    /// Extract as Glob input, reporting which field was missing or malformed.
+    pub fn try_as_glob(&self) -> Result<GlobInput, InputError> {
+        self.expect_tool("Glob")?;
+        Ok(GlobInput {
+            pattern: self.required_str(PATTERN_FIELDS)?,
+            path: self.optional_str(SEARCH_PATH_FIELDS),
+        })
+    }
+
+    /// Extract as Grep input, reporting which field was missing or malformed.
+    pub fn try_as_grep(&self) -> Result<GrepInput, InputError> {
+        self.expect_tool("Grep")?;
+        Ok(GrepInput {
+            pattern: self.required_str(PATTERN_FIELDS)?,
+            path: self.optional_str(SEARCH_PATH_FIELDS),
+            glob: self.optional_str(GLOB_FIELDS),
+        })
+    }
+
+    /// Extract as Task input, reporting which field was missing or malformed.
+    pub fn try_as_task(&self) -> Result<TaskInput, InputError> {
+        self.expect_tool("Task")?;
+        Ok(TaskInput {
+            prompt: self.required_str(PROMPT_FIELDS)?,
+            description: self.optional_str(DESCRIPTION_FIELDS),
+            subagent_type: self.optional_str(SUBAGENT_TYPE_FIELDS),
         })
     }
 
     /// Get the primary path being accessed (for any file-based tool).
     pub fn file_path(&self) -> Option<&str> {
-        self.tool_input.get("file_path").and_then(|v| v.as_str())
+        self.lookup(FILE_PATH_FIELDS).and_then(|v| v.as_str())
+    }
+
+    /// The text the tool acts on: its command, file path, URL, prompt or
+    /// search pattern.
+    pub fn target(&self) -> Option<&str> {
+        [
+            COMMAND_FIELDS,
+            FILE_PATH_FIELDS,
+            URL_FIELDS,
+            PROMPT_FIELDS,
+            PATTERN_FIELDS,
+        ]
+        .iter()
+        .find_map(|names| self.lookup(names).and_then(|v| v.as_str()))
+    }
+
+    /// Where the call takes effect: its file or search path, resolved
+    /// against `cwd` when relative, and `cwd` itself.
+    pub fn locations(&self) -> Vec<String> {
+        let path = self
+            .file_path()
+            .or_else(|| self.lookup(SEARCH_PATH_FIELDS).and_then(|v| v.as_str()));
+        let path = match (path, self.cwd.as_deref()) {
+            (Some(path), Some(cwd)) if !path.starts_with('/') => {
+                Some(format!("{}/{}", cwd.trim_end_matches('/'), path))
+            }
+            (path, _) => path.map(String::from),
+        };
+        path.into_iter().chain(self.cwd.clone()).collect()
     }
 
     /// Get the command (for Bash tool).
     pub fn command(&self) -> Option<&str> {
-        self.tool_input.get("command").and_then(|v| v.as_str())
+        self.lookup(COMMAND_FIELDS).and_then(|v| v.as_str())
+    }
+
+    fn expect_tool(&self, expected: &'static str) -> Result<(), InputError> {
+        if self.tool_name == expected {
+            Ok(())
+        } else {
+            Err(InputError::WrongTool {
+                expected,
+                actual: self.tool_name.clone(),
+            })
+        }
+    }
+
+    /// Find the first present (non-null) field among the accepted spellings.
+    fn lookup(&self, names: &[&str]) -> Option<&serde_json::Value> {
+        names
+            .iter()
+            .find_map(|name| self.tool_input.get(*name).filter(|v| !v.is_null()))
+    }
+
+    fn required_str(&self, names: &'static [&'static str]) -> Result<String, InputError> {
+        match self.lookup(names) {
+            None => Err(InputError::MissingField(names[0])),
+            Some(v) => v
+                .as_str()
+                .map(str::to_string)
+                .ok_or(InputError::WrongType {
+                    field: names[0],
+                    expected: "string",
+                }),
+        }
+    }
+
+    fn optional_str(&self, names: &'static [&'static str]) -> Option<String> {
+        self.optional(names, "string", |v| v.as_str().map(str::to_string))
+    }
+
+    fn optional_u64(&self, names: &'static [&'static str]) -> Option<u64> {
+        self.optional(names, "unsigned integer", serde_json::Value::as_u64)
+    }
+
+    fn optional_bool(&self, names: &'static [&'static str]) -> Option<bool> {
+        self.optional(names, "boolean", serde_json::Value::as_bool)
+    }
+
+    /// Look up an optional field, dropping it when it has the wrong type.
+    ///
+    /// Failing the whole input instead would make it unparseable, which
+    /// skips every check under the default `on_unparseable = "allow"`, so
+    /// `"offset": -1` would be a way around them.
+    fn optional<T>(
+        &self,
+        names: &'static [&'static str],
+        expected: &str,
+        convert: impl FnOnce(&serde_json::Value) -> Option<T>,
+    ) -> Option<T> {
+        let value = self.lookup(names)?;
+        let converted = convert(value);
+        if converted.is_none() {
+            tracing::debug!(field = names[0], expected, "ignoring malformed optional field");
+        }
+        converted
     }
 }
 
@@ -178,10 +436,134 @@ mod tests {
         assert_eq!(input.cwd, Some("/home/user".to_string()));
     }
 
+    #[test]
+    fn test_parse_session_end() {
+        let json = r#"{"hook_event_name":"SessionEnd","session_id":"abc","reason":"exit"}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert!(input.is_session_end());
+        assert!(input.tool_name.is_empty());
+    }
+
+    #[test]
+    fn test_parse_stop() {
+        let json =
+            r#"{"hook_event_name":"SubagentStop","session_id":"abc","stop_hook_active":false}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert!(input.is_stop());
+        assert!(!input.is_session_end());
+    }
+
     #[test]
     fn test_wrong_tool_type() {
         let json = r#"{"tool_name":"Read","tool_input":{"file_path":"/etc/passwd"}}"#;
         let input = HookInput::parse(json).unwrap();
         assert!(input.as_bash().is_none());
     }
+
+    #[test]
+    fn test_missing_command_is_reported() {
+        let json = r#"{"tool_name":"Bash","tool_input":{"description":"x"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert!(matches!(
+            input.try_as_bash(),
+            Err(InputError::MissingField("command"))
+        ));
+    }
+
+    #[test]
+    fn test_wrong_type_is_reported() {
+        let json = r#"{"tool_name":"Read","tool_input":{"file_path":42}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert!(matches!(
+            input.try_as_read(),
+            Err(InputError::WrongType {
+                field: "file_path",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_malformed_optional_fields_ignored() {
+        let json = r#"{"tool_name":"Read","tool_input":{"file_path":".env","offset":-1}}"#;
+        let read = HookInput::parse(json).unwrap().try_as_read().unwrap();
+        assert_eq!(read.file_path, ".env");
+        assert_eq!(read.offset, None);
+
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":"cat .env","timeout":1.5}}"#;
+        let bash = HookInput::parse(json).unwrap().try_as_bash().unwrap();
+        assert_eq!(bash.command, "cat .env");
+        assert_eq!(bash.timeout, None);
+
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":42,"timeout":1}}"#;
+        assert!(matches!(
+            HookInput::parse(json).unwrap().try_as_bash(),
+            Err(InputError::WrongType {
+                field: "command",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_wrong_tool_is_reported() {
+        let json = r#"{"tool_name":"Read","tool_input":{"file_path":"a"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert!(matches!(
+            input.try_as_edit(),
+            Err(InputError::WrongTool { .. })
+        ));
+    }
+
+    #[test]
+    fn test_alternative_field_spellings() {
+        let json = r#"{"tool_name":"Edit","tool_input":{"filePath":".env","oldString":"a","newString":"b"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        let edit = input.as_edit().unwrap();
+        assert_eq!(edit.file_path, ".env");
+        assert_eq!(input.file_path(), Some(".env"));
+
+        let json = r#"{"tool_name":"Bash","tool_input":{"cmd":"ls"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert_eq!(input.as_bash().unwrap().command, "ls");
+        assert_eq!(input.command(), Some("ls"));
+    }
+
+    #[test]
+    fn test_parse_grep_input() {
+        let json = r#"{"tool_name":"Grep","tool_input":{"pattern":"fn main","glob":"*.rs","output_mode":"content"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        let grep = input.try_as_grep().unwrap();
+        assert_eq!(grep.pattern, "fn main");
+        assert_eq!(grep.path, None);
+        assert_eq!(grep.glob.as_deref(), Some("*.rs"));
+        assert!(input.try_as_glob().is_err());
+    }
+
+    #[test]
+    fn test_locations() {
+        let json = r#"{"tool_name":"Read","cwd":"/repo/","tool_input":{"file_path":"src/lib.rs"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert_eq!(input.locations(), ["/repo/src/lib.rs", "/repo/"]);
+
+        let json = r#"{"tool_name":"Grep","tool_input":{"pattern":"x","path":"/srv"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert_eq!(input.locations(), ["/srv"]);
+
+        let json = r#"{"tool_name":"Bash","cwd":"/repo","tool_input":{"command":"ls"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert_eq!(input.locations(), ["/repo"]);
+        assert_eq!(input.target(), Some("ls"));
+    }
+
+    #[test]
+    fn test_edit_replace_all() {
+        let json = r#"{"tool_name":"Edit","tool_input":{"file_path":"a","old_string":"x","new_string":"y","replace_all":true}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert!(input.as_edit().unwrap().replace_all);
+
+        let json = r#"{"tool_name":"Edit","tool_input":{"file_path":"a","old_string":"x","new_string":"y"}}"#;
+        let input = HookInput::parse(json).unwrap();
+        assert!(!input.as_edit().unwrap().replace_all);
+    }
 }
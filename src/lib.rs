@@ -2,18 +2,75 @@
 //!
 //! A Rust-based PreToolUse hook for Claude Code that blocks access to
 //! sensitive files, dangerous commands, and environment variable exposure.
+//!
+//! # Embedding
+//!
+//! Other tools can run the same policy engine in-process. Build a config
+//! with [`ConfigBuilder`] and pass each tool call to [`evaluate`]; these,
+//! with [`HookInput`] and [`Decision`], are the stable API; everything it
+//! needs is re-exported at the crate root. The modules behind it are the
+//! hook's internals, hidden from these docs, and may change between
+//! releases.
+//!
+//! ```
+//! use aca_safety_net::{ConfigBuilder, HookInput, evaluate};
+//!
+//! let config = ConfigBuilder::new()
+//!     .deny("Bash", r"^\s*curl\b", "No network access")
+//!     .compile()
+//!     .unwrap();
+//!
+//! let input = HookInput::for_tool("Bash", "curl example.com", None).unwrap();
+//! assert!(evaluate(&input, &config).is_blocked());
+//!
+//! let input = HookInput::for_tool("Read", "src/main.rs", None).unwrap();
+//! assert!(!evaluate(&input, &config).is_blocked());
+//! ```
 
+// The hook binary and the fuzz targets reach into these, so they stay
+// public, but they are internals and are kept out of the documented API.
+#[doc(hidden)]
 pub mod analysis;
+#[doc(hidden)]
 pub mod audit;
+#[doc(hidden)]
 pub mod config;
+mod config_cache;
+#[doc(hidden)]
 pub mod decision;
+#[doc(hidden)]
 pub mod input;
+#[doc(hidden)]
 pub mod output;
+mod parallel;
+#[doc(hidden)]
+pub mod pattern_set;
+#[cfg(feature = "wasm")]
+#[doc(hidden)]
+pub mod plugin;
+#[cfg(feature = "policy")]
+mod policy;
+mod protected_files;
+#[doc(hidden)]
 pub mod rules;
+#[doc(hidden)]
+pub mod secrets;
+#[doc(hidden)]
+pub mod session;
+#[doc(hidden)]
 pub mod shell;
+#[doc(hidden)]
+pub mod validator;
 
-pub use analysis::{analyze_bash, analyze_edit, analyze_read, analyze_write};
-pub use config::{CompiledConfig, Config};
-pub use decision::Decision;
+pub use config::{CompiledConfig, Config, ConfigBuilder, ConfigError};
+pub use decision::{AskInfo, BlockInfo, Decision, RewriteInfo, Severity, WarnInfo};
 pub use input::HookInput;
-pub use output::format_response;
+
+/// Decide a tool call under `config`: the policy decision the hook starts from.
+///
+/// Stateless: remembered approvals, session escalation and lockdown, audit
+/// logging and shadow mode are applied by the hook around this call, not
+/// here. Tools without an analyzer are allowed.
+pub fn evaluate(input: &HookInput, config: &CompiledConfig) -> Decision {
+    analysis::analyze_tool(input, config)
+}
@@ -1,103 +1,361 @@
 //! ACO Safety Net - Claude Code security hook entry point.
 
-use aca_safety_net::analysis::{analyze_bash, analyze_edit, analyze_read, analyze_write};
-use aca_safety_net::audit::AuditLogger;
-use aca_safety_net::config::Config;
+mod cli;
+
+#[cfg(feature = "webhook")]
+use aca_safety_net::audit::WebhookSink;
+#[cfg(feature = "notify")]
+use aca_safety_net::audit::notify;
+#[cfg(feature = "otel")]
+use aca_safety_net::audit::otel;
+use aca_safety_net::audit::{AuditEntry, AuditLogger, EscalationRecord, SessionSummary};
+use aca_safety_net::config::{
+    AuditConfig, CompiledConfig, Config, ConfigError, EnforcementMode, FailMode, OutputConfig,
+};
 use aca_safety_net::decision::Decision;
+use aca_safety_net::evaluate;
 use aca_safety_net::input::HookInput;
-use aca_safety_net::output::format_response;
+use aca_safety_net::output::{
+    HookOutput, render_output, render_output_for_terminal, use_secret_rules,
+};
+use aca_safety_net::session::{
+    apply_lockdown, apply_remembered_approval, apply_session_policy, record_approval,
+    record_decision, session_stats, track_escalation,
+};
 
-use std::io::{self, Read, Write};
+use std::collections::hash_map::RandomState;
+use std::fs::OpenOptions;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, IsTerminal, Read, Write};
 use std::path::Path;
 use std::process::ExitCode;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::EnvFilter;
+
+/// The parts of the hook's environment that shape its output, passed along
+/// when a daemon answers the call.
+#[derive(Clone, Serialize, Deserialize)]
+struct Caller {
+    /// `CLAUDE_CODE_VERSION`.
+    claude_code_version: Option<String>,
+    /// `ACO_SAFETY_NET_FAIL_MODE`.
+    fail_mode: Option<String>,
+    /// Whether stderr is a terminal and `NO_COLOR` is unset.
+    color: bool,
+}
+
+impl Caller {
+    fn current() -> Self {
+        Self {
+            claude_code_version: std::env::var("CLAUDE_CODE_VERSION").ok(),
+            fail_mode: std::env::var("ACO_SAFETY_NET_FAIL_MODE").ok(),
+            // Color is only for humans watching a terminal; NO_COLOR opts out
+            color: io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        }
+    }
+}
 
 fn main() -> ExitCode {
+    init_logging();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&args);
+    }
+
+    let started = Instant::now();
+    let caller = Caller::current();
+
     // Read JSON from stdin
     let mut input_str = String::new();
-    if io::stdin().read_to_string(&mut input_str).is_err() {
-        return ExitCode::SUCCESS; // Fail-open on read error
+    if let Err(e) = io::stdin().read_to_string(&mut input_str) {
+        let reason = format!("could not read hook input: {}", e);
+        return print(handle_failure(None, &caller, reason));
+    }
+
+    // A running daemon has the config loaded and compiled already
+    #[cfg(unix)]
+    if let Some(output) = cli::daemon::forward(&input_str, &caller) {
+        return print(output);
     }
 
+    let mut compile = |config: Config, tool: &str| config.compile_for(tool).map(Rc::new);
+    print(hook(&input_str, &caller, started, &mut compile))
+}
+
+/// Compiles a loaded config for the called tool.
+type Compile<'a> = dyn FnMut(Config, &str) -> Result<Rc<CompiledConfig>, ConfigError> + 'a;
+
+/// Check one hook call, returning what to print.
+///
+/// `compile` compiles the loaded config for the called tool; the daemon
+/// reuses earlier compilations.
+fn hook(
+    input_str: &str,
+    caller: &Caller,
+    started: Instant,
+    compile: &mut Compile<'_>,
+) -> HookOutput {
     // Parse input
-    let hook_input = match HookInput::parse(&input_str) {
+    let hook_input = match HookInput::parse(input_str) {
         Ok(v) => v,
-        Err(_) => return ExitCode::SUCCESS, // Fail-open on parse error
+        Err(e) => {
+            let reason = format!("could not parse hook input: {}", e);
+            return handle_failure(None, caller, reason);
+        }
     };
 
     // Load config
     let cwd = hook_input.cwd.as_deref().map(Path::new);
-    let config = match Config::load(cwd) {
+    let config = match Config::load_cached(cwd) {
         Ok(c) => c,
-        Err(_) => return ExitCode::SUCCESS, // Fail-open if no config
+        Err(e) => {
+            let reason = format!("could not load config: {}", e);
+            return handle_failure(None, caller, reason);
+        }
     };
 
     // Compile config patterns
-    let compiled = match config.compile() {
+    let (fail_mode, output_config) = (config.fail_mode, config.output.clone());
+    let compiled = match compile(config, &hook_input.tool_name) {
         Ok(c) => c,
         Err(e) => {
-            eprintln!("Config error: {}", e);
-            return ExitCode::SUCCESS; // Fail-open on config error
+            let reason = format!("invalid config: {}", e);
+            let mut output = handle_failure(Some((fail_mode, &output_config)), caller, reason);
+            output.stderr = Some(match output.stderr {
+                Some(message) => format!("Config error: {}\n{}", e, message),
+                None => format!("Config error: {}", e),
+            });
+            return output;
         }
     };
 
-    // Analyze based on tool type
-    let decision = match hook_input.tool_name.as_str() {
-        "Bash" => {
-            if let Some(bash_input) = hook_input.as_bash() {
-                analyze_bash(&bash_input, &compiled, hook_input.cwd.as_deref())
-            } else {
-                Decision::allow()
-            }
-        }
-        "Read" => {
-            if let Some(read_input) = hook_input.as_read() {
-                analyze_read(&read_input, &compiled)
-            } else {
-                Decision::allow()
-            }
-        }
-        "Edit" => {
-            if let Some(edit_input) = hook_input.as_edit() {
-                analyze_edit(&edit_input, &compiled)
-            } else {
-                Decision::allow()
-            }
-        }
-        "Write" => {
-            if let Some(write_input) = hook_input.as_write() {
-                analyze_write(&write_input, &compiled)
-            } else {
-                Decision::allow()
-            }
+    // Redact what imported secret rules detect in audit entries and output.
+    // Set on every call, as a daemon's previous call may have used other rules
+    use_secret_rules(compiled.secret_rules());
+
+    // SessionEnd, Stop and SubagentStop: no tool to check, only summarize
+    // the session so far
+    if hook_input.is_session_end() || hook_input.is_stop() {
+        let audit = &compiled.raw.audit;
+        if audit.enabled
+            && audit.session_summary
+            && let Some(session_id) = &hook_input.session_id
+            && let Some(stats) = session_stats(&hook_input, &compiled)
+            && let Some(mut logger) = open_audit_log(audit)
+        {
+            let summary = SessionSummary::new(session_id.as_str(), stats)
+                .with_hook_event(hook_input.hook_event_name.as_deref());
+            let _ = logger.log_summary(&summary);
         }
-        // Other tools pass through
-        _ => Decision::allow(),
-    };
+        return HookOutput::default();
+    }
+
+    // Analyze based on tool type
+    let decision = evaluate(&hook_input, &compiled);
+
+    // PostToolUse: the tool already ran, so only remember the approval
+    if hook_input.is_post_tool_use() {
+        let _ = record_approval(&hook_input, &decision, &compiled);
+        return HookOutput::default();
+    }
+    let decision = apply_remembered_approval(&hook_input, decision, &compiled);
+    let decision = apply_session_policy(&hook_input, decision, &compiled);
+    let decision = apply_lockdown(&hook_input, decision, &compiled);
 
     // Audit logging (if enabled)
-    if compiled.raw.audit.enabled
-        && let Some(path) = &compiled.raw.audit.path
-        && let Ok(mut logger) = AuditLogger::open(Path::new(path))
+    let audit = &compiled.raw.audit;
+    let shadow = compiled.raw.mode == EnforcementMode::Shadow;
+    let recorded = audit.enabled && audit.records(&decision, sample_roll());
+    // Building the entry redacts and copies the input, so only do it for a sink
+    let has_sink = recorded || compiled.raw.notify.url.is_some() || audit.otel_endpoint.is_some();
+    let entry = has_sink.then(|| {
+        let mut entry = AuditEntry::new(&hook_input, &decision)
+            .with_provenance(started.elapsed(), &compiled.raw.sources);
+        entry.shadow = shadow;
+        if audit.include_tool_input {
+            entry = entry.with_tool_input(&hook_input);
+        }
+        entry
+    });
+    if recorded
+        && let Some(entry) = &entry
+        && let Some(mut logger) = open_audit_log(audit)
+    {
+        let _ = logger.log(entry);
+    }
+    if audit.enabled && audit.session_summary {
+        let _ = record_decision(&hook_input, &decision, &compiled);
+    }
+    #[cfg(feature = "webhook")]
+    if recorded
+        && let Some(entry) = &entry
+        && let Some(webhook) = &audit.webhook
+        && let Some(sink) = WebhookSink::new(webhook)
     {
-        let _ = logger.log_decision(&hook_input, &decision);
+        let _ = sink.send(entry);
+    }
+    #[cfg(feature = "notify")]
+    if let Some(entry) = &entry {
+        let _ = notify::notify_block(&hook_input, entry, &compiled);
+    }
+
+    // Escalation on repeated blocks of one rule
+    let escalation = track_escalation(&hook_input, &decision, &compiled)
+        .ok()
+        .flatten();
+    if let Some(escalation) = &escalation
+        && audit.enabled
+        && let Some(mut logger) = open_audit_log(audit)
+    {
+        let _ = logger.log_escalation(&EscalationRecord::new(&hook_input, escalation));
+    }
+    #[cfg(feature = "notify")]
+    if let Some(escalation) = &escalation {
+        let _ = notify::notify_escalation(&hook_input, escalation, &compiled);
+    }
+
+    #[cfg(feature = "otel")]
+    if let Some(endpoint) = &audit.otel_endpoint
+        && let Some(entry) = &entry
+    {
+        let _ = otel::export(endpoint, entry, started.elapsed());
+    }
+
+    // Shadow mode: everything above ran as normal, but nothing is enforced
+    if shadow {
+        return emit(&Decision::allow(), &compiled.raw.output, caller);
     }
 
     // Output result
-    match &decision {
-        Decision::Allow => ExitCode::SUCCESS,
-        Decision::Block(_) => {
-            if let Some(msg) = format_response(&decision) {
-                eprintln!("{}", msg);
-            }
-            ExitCode::from(2)
+    emit(&decision, &compiled.raw.output, caller)
+}
+
+/// Install a debug log subscriber when `ACO_SAFETY_NET_LOG` is set.
+///
+/// The variable takes a level (`debug`) or `tracing` filter directives
+/// (`aca_safety_net::rules=trace`). Logs are appended to
+/// `ACO_SAFETY_NET_LOG_FILE` if set, otherwise written to stderr; stdout is
+/// left to the hook JSON.
+fn init_logging() {
+    let Some(filter) = std::env::var("ACO_SAFETY_NET_LOG")
+        .ok()
+        .filter(|filter| !filter.is_empty())
+    else {
+        return;
+    };
+    let filter = match EnvFilter::try_new(&filter) {
+        Ok(filter) => filter,
+        Err(e) => {
+            eprintln!("aca-safety-net: ignoring ACO_SAFETY_NET_LOG: {}", e);
+            return;
+        }
+    };
+    let logger = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let file = std::env::var_os("ACO_SAFETY_NET_LOG_FILE").map(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .inspect_err(|e| {
+                eprintln!(
+                    "aca-safety-net: could not open {}: {}",
+                    Path::new(&path).display(),
+                    e
+                )
+            })
+    });
+    let _ = match file {
+        Some(Ok(file)) => logger.with_writer(Mutex::new(file)).try_init(),
+        Some(Err(_)) => return,
+        None => logger.with_writer(io::stderr).try_init(),
+    };
+}
+
+/// Open the configured audit log, encrypting lines when `audit.encrypt` is set.
+fn open_audit_log(audit: &AuditConfig) -> Option<AuditLogger> {
+    let logger = AuditLogger::open(Path::new(audit.path.as_ref()?))
+        .ok()?
+        .with_format(audit.format);
+    if audit.encrypt {
+        // A missing recipient makes every write fail rather than fall back to plain text
+        return Some(logger.with_recipient(audit.recipient.clone().unwrap_or_default()));
+    }
+    Some(logger)
+}
+
+/// A uniform random number in [0, 1) for audit sampling.
+fn sample_roll() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Handle an internal error according to the fail mode.
+///
+/// `ACO_SAFETY_NET_FAIL_MODE` wins over config. Without a loaded config, the
+/// user-level config is consulted so a broken project file cannot turn a
+/// fail-closed setup into fail-open.
+fn handle_failure(
+    loaded: Option<(FailMode, &OutputConfig)>,
+    caller: &Caller,
+    reason: String,
+) -> HookOutput {
+    let fallback;
+    let (config_mode, output_config) = match loaded {
+        Some(loaded) => loaded,
+        None => {
+            fallback = Config::load(None).unwrap_or_default();
+            (fallback.fail_mode, &fallback.output)
         }
-        Decision::Ask(_) => {
-            // Ask decisions output JSON to stdout for Claude Code to parse
-            if let Some(json) = format_response(&decision) {
-                let _ = io::stdout().write_all(json.as_bytes());
-                let _ = io::stdout().write_all(b"\n");
-            }
-            ExitCode::SUCCESS
+    };
+    let fail_mode = caller
+        .fail_mode
+        .as_deref()
+        .and_then(FailMode::parse)
+        .unwrap_or(config_mode);
+
+    match fail_mode {
+        FailMode::Open => HookOutput::default(),
+        FailMode::Closed => {
+            let decision = Decision::block(
+                "hook.internal_error",
+                format!("{}; blocking because fail_mode = \"closed\"", reason),
+            );
+            emit(&decision, output_config, caller)
         }
     }
 }
+
+/// Render a decision for the caller.
+fn emit(decision: &Decision, output_config: &OutputConfig, caller: &Caller) -> HookOutput {
+    let output_config = &OutputConfig {
+        claude_code_api: output_config
+            .claude_code_api
+            .resolve(caller.claude_code_version.as_deref()),
+        ..output_config.clone()
+    };
+
+    if caller.color {
+        render_output_for_terminal(decision, output_config)
+    } else {
+        render_output(decision, output_config)
+    }
+}
+
+/// Print hook output and return its exit code.
+fn print(output: HookOutput) -> ExitCode {
+    if let Some(msg) = &output.stderr {
+        eprintln!("{}", msg);
+    }
+    if let Some(json) = &output.stdout {
+        // Hook JSON goes to stdout for Claude Code to parse
+        let _ = io::stdout().write_all(json.as_bytes());
+        let _ = io::stdout().write_all(b"\n");
+    }
+    ExitCode::from(output.exit_code)
+}
@@ -2,6 +2,9 @@
 
 mod redaction;
 mod response;
+mod template;
+mod tty;
 
-pub use redaction::redact_secrets;
-pub use response::format_response;
+pub use redaction::{contains_secrets, redact_decision, redact_secrets, use_secret_rules};
+pub use response::{HookOutput, format_response, render_output, render_output_for_terminal};
+pub use template::{TemplateFields, render_template};
@@ -0,0 +1,113 @@
+//! Message templates for block, ask and warn output.
+
+/// Values substituted into a message template.
+#[derive(Debug, Default)]
+pub struct TemplateFields<'a> {
+    pub rule: &'a str,
+    pub reason: &'a str,
+    pub model_reason: &'a str,
+    pub user_reason: &'a str,
+    pub details: Option<&'a str>,
+    pub suggestion: Option<&'a str>,
+    pub alternatives: &'a [String],
+    pub also: &'a [String],
+    pub docs_url: Option<&'a str>,
+    pub severity: Option<&'a str>,
+}
+
+/// Render a template, replacing `{rule}`, `{reason}`, `{model_reason}`,
+/// `{user_reason}`, `{details}`, `{suggestion}`, `{alternatives}`, `{also}`,
+/// `{docs_url}` and `{severity}`.
+///
+/// Missing values render as empty strings, and `{alternatives}` and `{also}`
+/// render one `- item` per line. Unknown placeholders are left untouched, and values
+/// are never re-expanded, so a reason containing `{rule}` stays literal.
+pub fn render_template(template: &str, fields: &TemplateFields) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        match substitute(&after[..end], fields) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn substitute(name: &str, fields: &TemplateFields) -> Option<String> {
+    let value = match name {
+        "rule" => fields.rule.to_string(),
+        "reason" => fields.reason.to_string(),
+        "model_reason" => fields.model_reason.to_string(),
+        "user_reason" => fields.user_reason.to_string(),
+        "details" => fields.details.unwrap_or_default().to_string(),
+        "suggestion" => fields.suggestion.unwrap_or_default().to_string(),
+        "docs_url" => fields.docs_url.unwrap_or_default().to_string(),
+        "severity" => fields.severity.unwrap_or_default().to_string(),
+        "alternatives" => bullet_list(fields.alternatives),
+        "also" => bullet_list(fields.also),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn bullet_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("- {}", item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitutes_fields() {
+        let fields = TemplateFields {
+            rule: "git.reset.hard",
+            reason: "discards changes",
+            details: Some("HEAD~1"),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template("[{rule}] {reason} ({details}){suggestion}", &fields),
+            "[git.reset.hard] discards changes (HEAD~1)"
+        );
+    }
+
+    #[test]
+    fn test_alternatives_as_list() {
+        let alternatives = vec!["git stash".to_string(), "git restore".to_string()];
+        let fields = TemplateFields {
+            alternatives: &alternatives,
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template("Try:\n{alternatives}", &fields),
+            "Try:\n- git stash\n- git restore"
+        );
+    }
+
+    #[test]
+    fn test_unknown_and_unclosed_placeholders_kept() {
+        let fields = TemplateFields {
+            reason: "uses {rule}",
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template("{ticket} {reason} {oops", &fields),
+            "{ticket} uses {rule} {oops"
+        );
+    }
+}
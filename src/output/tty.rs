@@ -0,0 +1,63 @@
+//! Human-friendly block output for terminals.
+
+use crate::config::OutputConfig;
+use crate::decision::BlockInfo;
+use crate::output::response::{WORKAROUND_WARNING, format_also};
+
+const RED_BOLD: &str = "\x1b[1;31m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Format a block with color, a ⛔ prefix and aligned fields.
+pub fn format_block_tty(info: &BlockInfo, config: &OutputConfig) -> String {
+    let mut msg = format!(
+        "{RED_BOLD}\u{26d4} BLOCKED{RESET} {BOLD}{}{RESET}\n",
+        info.model_message()
+    );
+    push_field(&mut msg, "rule", &info.rule);
+    push_field(&mut msg, "severity", info.severity.as_str());
+    if let Some(details) = &info.details {
+        push_field(&mut msg, "details", details);
+    }
+    if let Some(url) = config.docs_url(&info.rule) {
+        push_field(&mut msg, "docs", &url);
+    }
+    for (i, alternative) in info.alternatives.iter().enumerate() {
+        push_field(&mut msg, if i == 0 { "instead" } else { "" }, alternative);
+    }
+    for (i, other) in info.also.iter().enumerate() {
+        push_field(
+            &mut msg,
+            if i == 0 { "also" } else { "" },
+            &format_also(other),
+        );
+    }
+    msg.push_str(&format!("\n{DIM}{}{RESET}", WORKAROUND_WARNING));
+    msg
+}
+
+fn push_field(msg: &mut String, label: &str, value: &str) {
+    msg.push_str(&format!("  {DIM}{:<8}{RESET} {}\n", label, value));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tty_block_layout() {
+        let info = BlockInfo::new("git.reset.hard", "discards changes")
+            .with_details("HEAD")
+            .with_alternatives(["git stash", "git restore <path>"]);
+        let msg = format_block_tty(&info, &OutputConfig::default());
+
+        assert!(msg.starts_with("\x1b[1;31m\u{26d4} BLOCKED"));
+        assert!(msg.contains("rule    \x1b[0m git.reset.hard\n"));
+        assert!(msg.contains("severity\x1b[0m critical\n"));
+        assert!(msg.contains("details \x1b[0m HEAD\n"));
+        assert!(msg.contains("instead \x1b[0m git stash\n"));
+        assert!(msg.contains("        \x1b[0m git restore <path>\n"));
+        assert!(msg.contains("MUST NOT ATTEMPT"));
+    }
+}
@@ -0,0 +1,66 @@
+//! Order-preserving parallel map over scoped threads.
+//!
+//! A typical hook call has a handful of command segments and is fastest on
+//! one thread; a pasted script can have hundreds. Callers decide when the
+//! work is large enough to be worth the threads, and read results in input
+//! order so "the first segment that blocks" means the same thing either way.
+
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// Command segments from which a Bash call is analyzed in parallel.
+pub(crate) const PARALLEL_SEGMENTS: usize = 32;
+
+/// Total patterns from which a config's pattern groups compile in parallel.
+pub(crate) const PARALLEL_PATTERNS: usize = 256;
+
+/// Apply `f` to every item, on several threads when `parallel` is set,
+/// returning the results in the order of `items`.
+pub(crate) fn map_ordered<T, U, F>(items: &[T], parallel: bool, f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    if !parallel || threads < 2 || items.len() < 2 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<U>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_results_in_input_order() {
+        let items: Vec<usize> = (0..1000).collect();
+        let sequential = map_ordered(&items, false, |i| i * 2);
+        let parallel = map_ordered(&items, true, |i| i * 2);
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel[999], 1998);
+    }
+
+    #[test]
+    fn test_empty_and_single() {
+        assert!(map_ordered(&[] as &[u8], true, |b| *b).is_empty());
+        assert_eq!(map_ordered(&[7], true, |b| *b), vec![7]);
+    }
+}
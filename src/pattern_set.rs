@@ -0,0 +1,180 @@
+//! A group of regex patterns matched in a single pass.
+
+use once_cell::sync::OnceCell;
+use regex::{Regex, RegexSet};
+
+use crate::config::ConfigError;
+
+/// Patterns compiled into one `RegexSet`, with the individual regexes built
+/// only when a caller needs one (e.g. to report or inspect a match).
+#[derive(Debug, Clone)]
+pub struct PatternSet {
+    set: RegexSet,
+    patterns: Vec<String>,
+    regexes: Vec<OnceCell<Regex>>,
+}
+
+impl PatternSet {
+    /// Compile patterns; an invalid one is reported by its source text.
+    pub fn new<I, S>(patterns: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let patterns: Vec<String> = patterns.into_iter().map(Into::into).collect();
+        let set = RegexSet::new(&patterns).map_err(|e| {
+            // Find the pattern at fault so the error names it
+            patterns
+                .iter()
+                .find_map(|p| {
+                    Regex::new(p).err().map(|source| ConfigError::Regex {
+                        pattern: p.clone(),
+                        source,
+                    })
+                })
+                .unwrap_or_else(|| ConfigError::Regex {
+                    pattern: patterns.join(" | "),
+                    source: e,
+                })
+        })?;
+        let regexes = patterns.iter().map(|_| OnceCell::new()).collect();
+        Ok(Self {
+            set,
+            patterns,
+            regexes,
+        })
+    }
+
+    /// A set that never matches.
+    pub fn empty() -> Self {
+        Self {
+            set: RegexSet::empty(),
+            patterns: Vec::new(),
+            regexes: Vec::new(),
+        }
+    }
+
+    /// Check if any pattern matches.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.set.is_match(text)
+    }
+
+    /// Indices of all matching patterns, in pattern order.
+    pub fn matches(&self, text: &str) -> impl Iterator<Item = usize> + use<> {
+        self.set.matches(text).into_iter()
+    }
+
+    /// Index of the first matching pattern.
+    pub fn first_match(&self, text: &str) -> Option<usize> {
+        self.matches(text).next()
+    }
+
+    /// Source text of pattern `index`.
+    pub fn pattern(&self, index: usize) -> &str {
+        &self.patterns[index]
+    }
+
+    /// The regex for pattern `index`, compiled on first use.
+    pub fn regex(&self, index: usize) -> &Regex {
+        self.regexes[index].get_or_init(|| {
+            // Already validated as part of the set
+            Regex::new(&self.patterns[index]).expect("pattern compiled in RegexSet")
+        })
+    }
+
+    /// Number of patterns.
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Check if there are no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+/// Translate a gitignore-style glob (`*`, `?`, `**`, `[...]`) into an
+/// unanchored regex where only `**` crosses a `/`.
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    out.push_str("(?:.*/)?");
+                } else {
+                    out.push_str(".*");
+                }
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => {
+                let rest: String = chars.clone().collect();
+                match rest.find(']') {
+                    Some(end) => {
+                        out.push_str(&char_class(&rest[..end]));
+                        for _ in rest[..=end].chars() {
+                            chars.next();
+                        }
+                    }
+                    None => out.push_str(r"\["),
+                }
+            }
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    out.push_str(&regex::escape(&next.to_string()));
+                }
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out
+}
+
+/// Translate the inside of a `[...]` glob class.
+fn char_class(class: &str) -> String {
+    let (negated, class) = match class.strip_prefix(['!', '^']) {
+        Some(rest) => (true, rest),
+        None => (false, class),
+    };
+    let mut out = String::from(if negated { "[^/" } else { "[" });
+    for c in class.chars() {
+        if matches!(c, '\\' | '[' | '&' | '~') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push(']');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_match_in_pattern_order() {
+        let set = PatternSet::new([r"\.env\b", r"env", r"id_rsa"]).unwrap();
+        assert_eq!(set.first_match("cat .env"), Some(0));
+        assert_eq!(set.first_match("environment"), Some(1));
+        assert_eq!(set.matches(".env").collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(set.first_match("README.md"), None);
+        assert!(set.regex(2).is_match("~/.ssh/id_rsa"));
+        assert_eq!(set.pattern(2), "id_rsa");
+    }
+
+    #[test]
+    fn test_invalid_pattern_named() {
+        let err = PatternSet::new(["ok", "[invalid"]).unwrap_err();
+        assert!(err.to_string().contains("'[invalid'"));
+    }
+
+    #[test]
+    fn test_empty() {
+        let set = PatternSet::empty();
+        assert!(set.is_empty());
+        assert!(!set.is_match("anything"));
+    }
+}
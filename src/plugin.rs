@@ -0,0 +1,376 @@
+//! WebAssembly analyzer plugins.
+//!
+//! Every `*.wasm` file in `[plugins] dir` is a core WebAssembly module that
+//! exports:
+//!
+//! - `memory`: its linear memory
+//! - `alloc(len: i32) -> i32`: space for the host to write `len` bytes of input
+//! - `analyze(ptr: i32, len: i32) -> i64`: decide the call whose JSON input
+//!   is at `ptr`, returning `(result_ptr << 32) | result_len`, or 0 for no
+//!   opinion
+//!
+//! The input is `{"tool_name", "tool_input", "cwd"}` as Claude Code sent it,
+//! plus `"segments"` for Bash: each command of the line with wrappers
+//! stripped, as `{"command": "...", "words": [...]}`. The result is
+//! `{"decision": "block"|"ask"|"warn"|"allow", "reason": "...", "rule": "..."}`,
+//! where `rule` defaults to `plugin.<file stem>`.
+//!
+//! Modules get no imports, so they cannot touch files, the network or the
+//! clock; each call is limited by fuel, a wall-clock timeout and a memory
+//! cap. A plugin that fails in any way (traps, runs out of fuel, returns
+//! malformed JSON, a result outside its memory) is logged and has no
+//! effect.
+
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use wasmtime::{Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::config::{CompiledConfig, PluginConfig};
+use crate::decision::{Decision, ExternalVerdict};
+use crate::input::HookInput;
+use crate::shell::{Token, split_commands, strip_wrappers, tokenize};
+
+/// Errors from loading or running a plugin.
+#[derive(Debug, Error)]
+pub enum PluginError {
+    #[error("failed to read plugin: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Wasm(String),
+
+    #[error("invalid result: {0}")]
+    Result(String),
+}
+
+impl PluginError {
+    fn wasm(error: impl std::fmt::Display) -> Self {
+        PluginError::Wasm(error.to_string())
+    }
+}
+
+/// Run every plugin on a tool call and return the strictest decision,
+/// or `None` when no plugin has an opinion (or none are configured).
+pub fn analyze_plugins(input: &HookInput, config: &CompiledConfig) -> Option<Decision> {
+    let settings = &config.raw.plugins;
+    let dir = plugin_dir(settings.dir.as_deref()?, input.cwd.as_deref());
+    let plugins = list_plugins(&dir);
+    if plugins.is_empty() {
+        return None;
+    }
+
+    let payload = payload(input).to_string();
+
+    let mut decision: Option<Decision> = None;
+    for path in plugins {
+        let result = run_plugin(&path, &payload, settings);
+        tracing::debug!(
+            plugin = %path.display(),
+            decision = match &result {
+                Ok(Some(found)) => found.label(),
+                Ok(None) => "none",
+                Err(_) => "error",
+            },
+            "plugin ran"
+        );
+        match result {
+            Ok(Some(found)) => {
+                decision = Some(match decision {
+                    Some(current) => current.stricter(found),
+                    None => found,
+                });
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(plugin = %path.display(), error = %e, "plugin failed"),
+        }
+    }
+    decision
+}
+
+/// Resolve the configured directory against the call's working directory.
+fn plugin_dir(dir: &str, cwd: Option<&str>) -> PathBuf {
+    let dir = Path::new(dir);
+    match cwd {
+        Some(cwd) if dir.is_relative() => Path::new(cwd).join(dir),
+        _ => dir.to_path_buf(),
+    }
+}
+
+/// `.wasm` files in `dir`, in name order so results are reproducible.
+fn list_plugins(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut plugins: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    plugins.sort();
+    plugins
+}
+
+fn engine() -> Result<Engine, PluginError> {
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    config.epoch_interruption(true);
+    Engine::new(&config).map_err(PluginError::wasm)
+}
+
+/// The JSON document handed to every plugin.
+fn payload(input: &HookInput) -> serde_json::Value {
+    let mut payload = json!({
+        "tool_name": input.tool_name,
+        "tool_input": input.tool_input,
+        "cwd": input.cwd,
+    });
+    if let Ok(bash) = input.try_as_bash() {
+        let segments: Vec<_> = split_commands(&bash.command)
+            .iter()
+            .map(|segment| {
+                let command = strip_wrappers(&segment.command);
+                let words: Vec<String> = tokenize(&command)
+                    .into_iter()
+                    .filter_map(|token| match token {
+                        Token::Word(word) => Some(word),
+                        _ => None,
+                    })
+                    .collect();
+                json!({ "command": command, "words": words })
+            })
+            .collect();
+        payload["segments"] = json!(segments);
+    }
+    payload
+}
+
+/// Store state: only the memory limits.
+struct Limits(StoreLimits);
+
+fn run_plugin(
+    path: &Path,
+    payload: &str,
+    settings: &PluginConfig,
+) -> Result<Option<Decision>, PluginError> {
+    // An engine per plugin, so one plugin's timer cannot interrupt the next
+    let engine = engine()?;
+    let module = Module::from_binary(&engine, &fs::read(path)?).map_err(PluginError::wasm)?;
+
+    let limits = StoreLimitsBuilder::new()
+        .memory_size(settings.max_memory_mb.saturating_mul(1024 * 1024))
+        .instances(1)
+        .build();
+    let mut store = Store::new(&engine, Limits(limits));
+    store.limiter(|state| &mut state.0);
+    store.set_fuel(settings.fuel).map_err(PluginError::wasm)?;
+    store.set_epoch_deadline(1);
+
+    // One tick of the epoch interrupts the plugin wherever it is
+    let timer = engine.clone();
+    let timeout = Duration::from_millis(settings.timeout_ms);
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+        timer.increment_epoch();
+    });
+
+    // No imports: the module can only compute on what it is given
+    let instance = Instance::new(&mut store, &module, &[]).map_err(PluginError::wasm)?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| PluginError::Wasm("missing export 'memory'".to_string()))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "alloc")
+        .map_err(PluginError::wasm)?;
+    let analyze = instance
+        .get_typed_func::<(i32, i32), i64>(&mut store, "analyze")
+        .map_err(PluginError::wasm)?;
+
+    let len = i32::try_from(payload.len()).map_err(PluginError::wasm)?;
+    let ptr = alloc.call(&mut store, len).map_err(PluginError::wasm)?;
+    memory
+        .write(&mut store, ptr as u32 as usize, payload.as_bytes())
+        .map_err(PluginError::wasm)?;
+
+    let packed = analyze
+        .call(&mut store, (ptr, len))
+        .map_err(PluginError::wasm)? as u64;
+    if packed == 0 {
+        return Ok(None);
+    }
+    let (result_ptr, result_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    // The length comes from the plugin, so the result is read in place
+    // rather than copied into a buffer of that size
+    let result = result_ptr
+        .checked_add(result_len)
+        .and_then(|end| memory.data(&store).get(result_ptr..end))
+        .ok_or_else(|| {
+            PluginError::Result(format!(
+                "{} bytes at {} are outside the plugin's memory",
+                result_len, result_ptr
+            ))
+        })?;
+
+    let verdict: ExternalVerdict =
+        serde_json::from_slice(result).map_err(|e| PluginError::Result(e.to_string()))?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    verdict
+        .into_decision(
+            &format!("plugin.{}", stem),
+            &format!("Rejected by plugin {}", stem),
+        )
+        .map_err(PluginError::Result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use tempfile::TempDir;
+
+    /// A plugin that blocks when the input contains `needle`, by scanning
+    /// the bytes; the result JSON lives in a data segment at offset 0.
+    fn scanning_plugin(needle: &str, result: &str) -> String {
+        let needle_at = 1024;
+        format!(
+            r#"(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{result}")
+  (data (i32.const {needle_at}) "{needle}")
+  (func (export "alloc") (param i32) (result i32) (i32.const 4096))
+  (func (export "analyze") (param $ptr i32) (param $len i32) (result i64)
+    (local $i i32) (local $j i32)
+    (block $done
+      (loop $outer
+        (br_if $done (i32.gt_s (i32.add (local.get $i) (i32.const {n}))
+                               (local.get $len)))
+        (local.set $j (i32.const 0))
+        (block $mismatch
+          (loop $inner
+            (if (i32.eq (local.get $j) (i32.const {n}))
+              (then (return (i64.const {len}))))
+            (br_if $mismatch (i32.ne
+              (i32.load8_u (i32.add (local.get $ptr) (i32.add (local.get $i) (local.get $j))))
+              (i32.load8_u (i32.add (i32.const {needle_at}) (local.get $j)))))
+            (local.set $j (i32.add (local.get $j) (i32.const 1)))
+            (br $inner)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $outer)))
+    (i64.const 0)))"#,
+            result = result.replace('"', "\\\""),
+            n = needle.len(),
+            len = result.len(),
+        )
+    }
+
+    fn write_plugin(dir: &Path, name: &str, wat: &str) {
+        fs::write(dir.join(name), wat::parse_str(wat).unwrap()).unwrap();
+    }
+
+    fn config_for(dir: &Path, extra: &str) -> CompiledConfig {
+        let toml = format!("[plugins]\ndir = '{}'\n{}", dir.display(), extra);
+        let config: Config = toml::from_str(&toml).unwrap();
+        config.compile().unwrap()
+    }
+
+    fn bash(command: &str) -> HookInput {
+        HookInput::for_tool("Bash", command, None).unwrap()
+    }
+
+    #[test]
+    fn test_plugin_blocks_matching_input() {
+        let dir = TempDir::new().unwrap();
+        write_plugin(
+            dir.path(),
+            "no-terraform-destroy.wasm",
+            &scanning_plugin(
+                "terraform destroy",
+                r#"{"decision":"block","reason":"Destroys infrastructure"}"#,
+            ),
+        );
+        let config = config_for(dir.path(), "");
+
+        let decision = analyze_plugins(&bash("cd infra && terraform destroy"), &config).unwrap();
+        assert!(decision.is_blocked());
+        assert_eq!(decision.rule(), Some("plugin.no-terraform-destroy"));
+        assert_eq!(decision.reason(), Some("Destroys infrastructure"));
+        assert!(analyze_plugins(&bash("terraform plan"), &config).is_none());
+    }
+
+    #[test]
+    fn test_strictest_plugin_wins() {
+        let dir = TempDir::new().unwrap();
+        let warn = r#"{"decision":"warn","rule":"a.warn","reason":"risky"}"#;
+        let ask = r#"{"decision":"ask","rule":"b.ask","reason":"confirm"}"#;
+        write_plugin(dir.path(), "a.wasm", &scanning_plugin("deploy", warn));
+        write_plugin(dir.path(), "b.wasm", &scanning_plugin("deploy", ask));
+        write_plugin(dir.path(), "ignored.txt", &scanning_plugin("deploy", ask));
+        let config = config_for(dir.path(), "");
+
+        let decision = analyze_plugins(&bash("make deploy"), &config).unwrap();
+        assert_eq!(decision.rule(), Some("b.ask"));
+    }
+
+    #[test]
+    fn test_runaway_plugin_is_stopped() {
+        let dir = TempDir::new().unwrap();
+        let spin = r#"(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 0))
+  (func (export "analyze") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))"#;
+        write_plugin(dir.path(), "spin.wasm", spin);
+
+        let config = config_for(dir.path(), "fuel = 100000");
+        assert!(analyze_plugins(&bash("ls"), &config).is_none());
+        // Effectively unlimited fuel: the timeout stops it instead
+        let config = config_for(dir.path(), "fuel = 18446744073709551615\ntimeout_ms = 50");
+        assert!(analyze_plugins(&bash("ls"), &config).is_none());
+    }
+
+    #[test]
+    fn test_plugin_with_imports_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let importing = r#"(module
+  (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 0))
+  (func (export "analyze") (param i32 i32) (result i64) (i64.const 0)))"#;
+        write_plugin(dir.path(), "importing.wasm", importing);
+        let config = config_for(dir.path(), "");
+
+        let path = dir.path().join("importing.wasm");
+        let err = run_plugin(&path, "{}", &config.raw.plugins).unwrap_err();
+        assert!(matches!(err, PluginError::Wasm(_)));
+    }
+
+    #[test]
+    fn test_result_outside_memory_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let oversized = r#"(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 0))
+  (func (export "analyze") (param i32 i32) (result i64) (i64.const 0xffffffff)))"#;
+        write_plugin(dir.path(), "oversized.wasm", oversized);
+        let config = config_for(dir.path(), "");
+
+        let path = dir.path().join("oversized.wasm");
+        let err = run_plugin(&path, "{}", &config.raw.plugins).unwrap_err();
+        assert!(matches!(err, PluginError::Result(_)));
+        assert!(analyze_plugins(&bash("ls"), &config).is_none());
+    }
+
+    #[test]
+    fn test_bash_payload_has_segments() {
+        let payload = payload(&bash("sudo rm -rf build && ls"));
+        assert_eq!(payload["tool_name"], "Bash");
+        assert_eq!(
+            payload["segments"][0]["words"],
+            json!(["rm", "-rf", "build"])
+        );
+        assert_eq!(payload["segments"][1]["command"], "ls");
+    }
+}
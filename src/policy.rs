@@ -0,0 +1,267 @@
+//! Organization policy: a signed config file fetched from `[policy] url`.
+//!
+//! Security teams publish one policy for every developer machine instead of
+//! editing each user config. The file is only applied when `<url>.sig`
+//! holds a valid Ed25519 signature of it under `[policy] public_key`, so a
+//! compromised server or network path cannot loosen the rules. A verified
+//! copy is cached for `ttl_minutes`, and used past that when the server
+//! cannot be reached.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+use crate::config::PolicyConfig;
+
+/// Errors that can occur when fetching or verifying the policy.
+#[derive(Debug, Error)]
+pub enum PolicyError {
+    #[error("policy request failed: {0}")]
+    Http(#[from] ureq::Error),
+
+    #[error("failed to cache policy: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("policy.public_key is not a base64 Ed25519 public key")]
+    PublicKey,
+
+    #[error("policy signature is missing or does not verify")]
+    Signature,
+}
+
+/// A verified policy file.
+pub(crate) struct Policy {
+    pub url: String,
+    pub content: String,
+}
+
+/// The policy `config` points at, or `None` if there is none or no verified
+/// copy could be obtained.
+pub(crate) fn load(config: &PolicyConfig) -> Option<Policy> {
+    let url = config.url.as_ref()?;
+    load_from(url, config, cache_path(url).as_deref())
+}
+
+/// Load the policy at `url`, caching it at `cache`.
+fn load_from(url: &str, config: &PolicyConfig, cache: Option<&Path>) -> Option<Policy> {
+    let key = match parse_public_key(config.public_key.as_deref().unwrap_or_default()) {
+        Ok(key) => key,
+        Err(e) => {
+            tracing::warn!(url, error = %e, "ignoring organization policy");
+            return None;
+        }
+    };
+    let ttl = Duration::from_secs(config.ttl_minutes * 60);
+    let cached = |max_age| read_cached(cache?, max_age, &key);
+    if let Some(content) = cached(Some(ttl)) {
+        tracing::debug!(url, "using cached organization policy");
+        return Some(Policy {
+            url: url.to_string(),
+            content,
+        });
+    }
+
+    let fetched = fetch(url, config.timeout_ms).and_then(|(content, signature)| {
+        verify(&key, &content, &signature)?;
+        if let Some(path) = cache {
+            write_cached(path, &content, &signature)?;
+        }
+        Ok(content)
+    });
+    let content = match fetched {
+        Ok(content) => content,
+        Err(e) => {
+            // An outdated policy still protects more than none
+            tracing::warn!(url, error = %e, "could not update organization policy");
+            cached(None)?
+        }
+    };
+    Some(Policy {
+        url: url.to_string(),
+        content,
+    })
+}
+
+/// Decode a base64 Ed25519 public key.
+fn parse_public_key(key: &str) -> Result<VerifyingKey, PolicyError> {
+    let bytes = STANDARD
+        .decode(key.trim())
+        .map_err(|_| PolicyError::PublicKey)?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| PolicyError::PublicKey)?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| PolicyError::PublicKey)
+}
+
+/// Check a base64 Ed25519 signature of `content`.
+fn verify(key: &VerifyingKey, content: &str, signature: &str) -> Result<(), PolicyError> {
+    let bytes = STANDARD
+        .decode(signature.trim())
+        .map_err(|_| PolicyError::Signature)?;
+    let signature = Signature::from_slice(&bytes).map_err(|_| PolicyError::Signature)?;
+    key.verify_strict(content.as_bytes(), &signature)
+        .map_err(|_| PolicyError::Signature)
+}
+
+/// Download the policy and its signature.
+fn fetch(url: &str, timeout_ms: u64) -> Result<(String, String), PolicyError> {
+    let agent = ureq::Agent::new_with_config(
+        ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_millis(timeout_ms)))
+            .build(),
+    );
+    let get = |url: &str| agent.get(url).call()?.body_mut().read_to_string();
+    let content = get(url)?;
+    let signature = get(&format!("{}.sig", url))?;
+    Ok((content, signature))
+}
+
+/// Where the policy from `url` is cached.
+///
+/// Respects `ACO_SAFETY_NET_CACHE_DIR`, like the config cache.
+fn cache_path(url: &str) -> Option<PathBuf> {
+    let dir = match std::env::var_os("ACO_SAFETY_NET_CACHE_DIR") {
+        Some(dir) => PathBuf::from(dir).join("policy"),
+        None => dirs::cache_dir()?.join("aca-safety-net/policy"),
+    };
+    let digest = Sha256::digest(url.as_bytes());
+    let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    Some(dir.join(name))
+}
+
+/// The cached policy, if it is younger than `max_age` and still verifies.
+fn read_cached(path: &Path, max_age: Option<Duration>, key: &VerifyingKey) -> Option<String> {
+    if let Some(max_age) = max_age {
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        if SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default()
+            > max_age
+        {
+            return None;
+        }
+    }
+    let content = fs::read_to_string(path).ok()?;
+    let signature = fs::read_to_string(path.with_extension("sig")).ok()?;
+    verify(key, &content, &signature).ok()?;
+    Some(content)
+}
+
+/// Store a verified policy and its signature.
+fn write_cached(path: &Path, content: &str, signature: &str) -> Result<(), PolicyError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Signature first: a reader never pairs a new policy with an old one
+    fs::write(path.with_extension("sig"), signature)?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use tempfile::TempDir;
+
+    const POLICY: &str =
+        "[[deny]]\ntool = \"Bash\"\npattern = \"^terraform destroy\"\nreason = \"Org policy\"\n";
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn public_key() -> String {
+        STANDARD.encode(signing_key().verifying_key().as_bytes())
+    }
+
+    fn sign(content: &str) -> String {
+        STANDARD.encode(signing_key().sign(content.as_bytes()).to_bytes())
+    }
+
+    /// Serve `policy` and `signature` to the next two requests.
+    fn serve(policy: &str, signature: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/policy.toml", listener.local_addr().unwrap());
+        let bodies = [policy.to_string(), signature.to_string()];
+        std::thread::spawn(move || {
+            for body in bodies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    fn config(url: &str) -> PolicyConfig {
+        PolicyConfig {
+            url: Some(url.to_string()),
+            public_key: Some(public_key()),
+            ..PolicyConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let key = parse_public_key(&public_key()).unwrap();
+        assert!(verify(&key, POLICY, &sign(POLICY)).is_ok());
+        let tampered = POLICY.replace("destroy", "destroyed");
+        assert!(verify(&key, &tampered, &sign(POLICY)).is_err());
+        assert!(verify(&key, POLICY, "not base64!").is_err());
+        assert!(parse_public_key("AAAA").is_err());
+    }
+
+    #[test]
+    fn test_fetched_policy_is_cached() {
+        let dir = TempDir::new().unwrap();
+        let cache = dir.path().join("policy");
+        let url = serve(POLICY, &sign(POLICY));
+        let policy = load_from(&url, &config(&url), Some(&cache)).unwrap();
+        assert_eq!(policy.url, url);
+        assert_eq!(policy.content, POLICY);
+
+        // Within the TTL the cached copy is used without a request
+        let offline = "http://127.0.0.1:9/policy.toml";
+        let policy = load_from(offline, &config(offline), Some(&cache)).unwrap();
+        assert_eq!(policy.content, POLICY);
+
+        // Past it, the cached copy stands in while the server is unreachable
+        let mut expired = config(offline);
+        expired.ttl_minutes = 0;
+        assert!(load_from(offline, &expired, Some(&cache)).is_some());
+
+        // unless it was tampered with
+        fs::write(&cache, POLICY.replace("destroy", "apply")).unwrap();
+        assert!(load_from(offline, &expired, Some(&cache)).is_none());
+    }
+
+    #[test]
+    fn test_unsigned_policy_ignored() {
+        let dir = TempDir::new().unwrap();
+        let cache = dir.path().join("policy");
+        let url = serve(POLICY, &sign("something else"));
+        assert!(load_from(&url, &config(&url), Some(&cache)).is_none());
+        assert!(!cache.exists());
+
+        let mut config = config(&url);
+        config.public_key = Some("AAAA".to_string());
+        assert!(load_from(&url, &config, None).is_none());
+        assert!(load(&PolicyConfig::default()).is_none());
+    }
+}
@@ -0,0 +1,91 @@
+//! `.protectedfiles`: extra sensitive paths in gitignore syntax.
+//!
+//! Teams can list the files a repository must keep away from Claude the way
+//! they already list files git must ignore, without writing regexes. Each
+//! entry becomes a `sensitive_files` pattern, so it applies wherever the
+//! built-in patterns do: Read, read commands in Bash and `git add`.
+
+use std::path::Path;
+
+use crate::pattern_set::glob_to_regex;
+
+/// Translate the entries of a `.protectedfiles` file in `root` into
+/// `sensitive_files` regexes.
+///
+/// Entries containing a `/` are relative to `root`; the others match at any
+/// depth. `!` negations are skipped: re-including a path would need
+/// gitignore's last-match-wins order, which a pattern list cannot express,
+/// and dropping one only protects more.
+pub(crate) fn sensitive_patterns(content: &str, root: &Path) -> Vec<String> {
+    let root = regex::escape(root.to_string_lossy().trim_end_matches('/'));
+    content
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            if line.starts_with('!') {
+                tracing::warn!(entry = line, "ignoring negated .protectedfiles entry");
+                return None;
+            }
+            let pattern = line.trim_end_matches('/');
+            if pattern.is_empty() {
+                return None;
+            }
+            let anchored = pattern.contains('/');
+            let body = glob_to_regex(pattern.strip_prefix('/').unwrap_or(pattern));
+            Some(if anchored {
+                format!(r"^(?:\./|{}/)?{}(?:/|$)", root, body)
+            } else {
+                format!(r"(?:^|/){}(?:/|$)", body)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn matches(entry: &str, path: &str) -> bool {
+        let patterns = sensitive_patterns(entry, Path::new("/work/repo"));
+        patterns
+            .iter()
+            .any(|p| Regex::new(p).unwrap().is_match(path))
+    }
+
+    #[test]
+    fn test_unanchored_entries_match_at_any_depth() {
+        assert!(matches("*.tfstate", "terraform.tfstate"));
+        assert!(matches("*.tfstate", "/work/repo/infra/prod.tfstate"));
+        assert!(matches("secrets", "config/secrets/db.yml"));
+        assert!(matches("fixtures/", "tests/fixtures/users.json"));
+        assert!(!matches("*.tfstate", "terraform.tfstate.md5/x.rs"));
+        assert!(!matches("secrets", "src/secrets_manager.rs"));
+    }
+
+    #[test]
+    fn test_anchored_entries_match_from_root() {
+        for path in [
+            "config/prod.yml",
+            "./config/prod.yml",
+            "/work/repo/config/prod.yml",
+        ] {
+            assert!(matches("/config/prod.yml", path), "{}", path);
+        }
+        assert!(!matches("/config/prod.yml", "vendor/config/prod.yml"));
+        assert!(matches("deploy/**/keys", "deploy/eu/west/keys/a.pem"));
+        assert!(matches("deploy/**/keys", "deploy/keys"));
+        assert!(matches("data/[!a]?.csv", "data/b1.csv"));
+        assert!(!matches("data/[!a]?.csv", "data/a1.csv"));
+    }
+
+    #[test]
+    fn test_comments_blanks_and_negations_skipped() {
+        let content = "# team secrets\n\n*.p12\n!public.p12\n\\#literal\n";
+        let patterns = sensitive_patterns(content, Path::new("/work/repo"));
+        assert_eq!(patterns.len(), 2);
+        assert!(matches(content, "#literal"));
+        assert!(matches(content, "public.p12"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Allowlist mode: Bash runs only the commands in `allowed_commands`.
+//!
+//! The other rules block what is known to be dangerous and let the rest
+//! through. With `mode = "allowlist"` that is turned around for high-security
+//! setups: a command line is blocked as soon as one of its commands is not
+//! listed, and listed commands still go through every other check.
+
+use crate::config::{CompiledConfig, EnforcementMode};
+use crate::decision::Decision;
+use crate::shell::{Token, expand_commands, strip_wrappers, tokenize};
+
+/// Block a command line that runs a command missing from
+/// `allowed_commands`, in allowlist mode.
+///
+/// Every segment counts, including those nested in `bash -c` and
+/// substitutions. Wrappers are skipped, so `sudo make` runs `make`. Names
+/// match as written: allowing `git` allows neither `/usr/bin/git` nor
+/// `./git`.
+pub fn check_allowlist(command: &str, config: &CompiledConfig) -> Decision {
+    if config.raw.mode != EnforcementMode::Allowlist {
+        return Decision::allow();
+    }
+    for segment in expand_commands(command) {
+        // A bare wrapper, like `env`, runs itself
+        let Some(name) =
+            first_word(&strip_wrappers(&segment.command)).or_else(|| first_word(&segment.command))
+        else {
+            continue;
+        };
+        if !config.raw.allowed_commands.contains(&name) {
+            tracing::debug!(name, "command not in allowed_commands");
+            return Decision::block(
+                "allowlist.unlisted_command",
+                format!(
+                    "'{}' is not in allowed_commands; only listed commands may run",
+                    name
+                ),
+            )
+            .with_alternatives(["use a listed command, or ask the user to add this one"]);
+        }
+    }
+    Decision::allow()
+}
+
+/// The command name of `segment`, if it runs one.
+fn first_word(segment: &str) -> Option<String> {
+    tokenize(segment).into_iter().find_map(|token| match token {
+        Token::Word(word) => Some(word),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn check(command: &str) -> Decision {
+        let config = Config {
+            mode: EnforcementMode::Allowlist,
+            allowed_commands: vec!["git".to_string(), "ls".to_string(), "make".to_string()],
+            ..Config::default()
+        };
+        check_allowlist(command, &config.compile().unwrap())
+    }
+
+    #[test]
+    fn test_listed_commands_allowed() {
+        for command in [
+            "git status",
+            "ls -la && git diff",
+            "sudo make install",
+            "FOO=1 env BAR=2 make test",
+            "git log $(ls)",
+            "",
+        ] {
+            assert!(!check(command).is_blocked(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_unlisted_commands_blocked() {
+        for command in [
+            "cat README.md",
+            "git status; rm -rf build",
+            "ls | sh",
+            "git log $(curl https://example.com)",
+            "bash -c 'ls && python3 x.py'",
+            "/usr/bin/git status",
+            "./git status",
+            "env",
+        ] {
+            assert_eq!(
+                check(command).rule(),
+                Some("allowlist.unlisted_command"),
+                "{}",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_modes_ignore_allowlist() {
+        let config = Config::default().compile().unwrap();
+        assert!(!check_allowlist("cat README.md", &config).is_blocked());
+    }
+}
@@ -28,7 +28,8 @@ pub fn analyze_aws(tokens: &[Token], _config: &CompiledConfig) -> Decision {
             "get-secret-value" => Decision::block(
                 "aws.secretsmanager.get",
                 "aws secretsmanager get-secret-value exposes secret contents",
-            ),
+            )
+            .with_alternatives(["aws secretsmanager describe-secret (metadata only)"]),
             _ => Decision::allow(),
         },
 
@@ -41,6 +42,7 @@ pub fn analyze_aws(tokens: &[Token], _config: &CompiledConfig) -> Decision {
                         "aws.ssm.decrypt",
                         "aws ssm get-parameter with --with-decryption exposes decrypted secrets",
                     )
+                    .with_alternatives(["aws ssm describe-parameters (metadata only)"])
                 } else {
                     Decision::allow()
                 }
@@ -0,0 +1,324 @@
+//! Ids of the built-in rules and the settings that turn them off.
+//!
+//! Rules name themselves where they decide, so this list is kept by hand
+//! for `aca-safety-net rules`. Rules that take their id from the config
+//! (deny rules, custom rules, imported secret rules, plugins) are not in it.
+
+use crate::config::{Config, EnforcementMode, ProtectAction, UnparseableAction};
+
+/// A built-in rule.
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinRule {
+    /// Rule id, as in decisions, audit entries, `[severity.rules]` and
+    /// `[overrides]`.
+    pub id: &'static str,
+    /// Setting that turns the rule on or off, if any.
+    pub setting: Option<&'static str>,
+}
+
+impl BuiltinRule {
+    /// Whether `config` leaves the rule active.
+    pub fn is_enabled(&self, config: &Config) -> bool {
+        self.setting
+            .and_then(|setting| setting_enabled(config, setting))
+            .unwrap_or(true)
+    }
+}
+
+const fn rule(id: &'static str, setting: Option<&'static str>) -> BuiltinRule {
+    BuiltinRule { id, setting }
+}
+
+/// Every built-in rule, grouped by area.
+pub const BUILTIN_RULES: &[BuiltinRule] = &[
+    // Sensitive files and secrets
+    rule("secrets.sensitive_file", None),
+    rule("secrets.sensitive_upload", None),
+    rule("secrets.inline.assignment", Some("secrets.block_inline")),
+    rule("secrets.inline.authorization", Some("secrets.block_inline")),
+    rule("secrets.inline.known_token", Some("secrets.block_inline")),
+    rule(
+        "secrets.inline.url_credentials",
+        Some("secrets.block_inline"),
+    ),
+    rule("secrets.url", Some("secrets.block_inline")),
+    rule(
+        "secrets.content.gcp_service_account",
+        Some("secrets.scan_content"),
+    ),
+    rule("secrets.content.known_token", Some("secrets.scan_content")),
+    rule("paranoid.sensitive_file", Some("paranoid.enabled")),
+    rule("paranoid.sensitive_mention", Some("paranoid.enabled")),
+    rule("search.sensitive_dir", Some("search.block_sensitive_paths")),
+    rule("search.secret_hunting", Some("search.block_secret_hunting")),
+    rule("env.dump", None),
+    rule("env.sensitive_var", None),
+    // Git
+    rule("git.add.sensitive", Some("git.block_add_sensitive")),
+    rule("git.commit.secret", Some("git.block_commit_secrets")),
+    rule("git.push.force", None),
+    rule("git.reset.hard", None),
+    rule("git.checkout", None),
+    rule("git.checkout.force", None),
+    rule("git.clean", None),
+    rule("git.clean.force", None),
+    rule("git.branch.force_delete", None),
+    rule("git.stash.drop", None),
+    rule("git.stash.clear", None),
+    // Protected write destinations
+    rule("git.internals.hooks", Some("protect.git_internals")),
+    rule("git.internals.config", Some("protect.git_internals")),
+    rule("git.internals.credentials", Some("protect.git_internals")),
+    rule("persistence.shell_startup", Some("protect.shell_startup")),
+    rule("persistence.ssh_keys", Some("protect.ssh")),
+    rule("persistence.ssh_config", Some("protect.ssh")),
+    rule(
+        "self_protection.hook_config",
+        Some("protect.self_protection"),
+    ),
+    rule(
+        "self_protection.claude_settings",
+        Some("protect.self_protection"),
+    ),
+    rule(
+        "self_protection.hook_cache",
+        Some("protect.self_protection"),
+    ),
+    rule(
+        "self_protection.hook_state",
+        Some("protect.self_protection"),
+    ),
+    rule("self_protection.binary", Some("protect.self_protection")),
+    rule(
+        "self_protection.hook_command",
+        Some("protect.self_protection"),
+    ),
+    rule("self_protection.signal", Some("protect.self_protection")),
+    rule("ci.workflow", Some("protect.ci")),
+    rule("persistence.cron", Some("protect.persistence")),
+    rule("persistence.crontab", Some("protect.persistence")),
+    rule("persistence.systemd", Some("protect.persistence")),
+    rule("persistence.launchd", Some("protect.persistence")),
+    rule("persistence.autostart", Some("protect.persistence")),
+    // Deletion and disks
+    rule("rm.dangerous_path", None),
+    rule("rm.parent_escape", None),
+    rule("rm.outside_cwd", Some("rm.block_outside_cwd")),
+    rule("find.delete", None),
+    rule("find.exec_rm", None),
+    rule("find.ok_rm", None),
+    rule("xargs.rm", None),
+    rule("xargs.rm_rf", None),
+    rule("parallel.rm", None),
+    rule("parallel.rm_rf", None),
+    rule("windows.delete.dangerous_path", None),
+    rule("windows.delete.parent_escape", None),
+    rule("windows.reg.credential_hive", None),
+    rule("disk.dd.device", None),
+    rule("disk.dd.sensitive", None),
+    rule("disk.format", None),
+    rule("disk.wipe", None),
+    rule(
+        "permissions.world_writable",
+        Some("permissions.block_world_writable"),
+    ),
+    rule(
+        "permissions.system_path",
+        Some("permissions.block_system_paths"),
+    ),
+    rule("permissions.sensitive", Some("permissions.block_sensitive")),
+    // Network
+    rule("network.metadata", None),
+    rule("network.blocked_host", None),
+    rule("network.local_admin", Some("network.block_local_admin")),
+    rule(
+        "network.upload.external",
+        Some("network.ask_external_uploads"),
+    ),
+    rule("network.pipe_to_shell", None),
+    rule("remote_copy.upload", Some("network.block_remote_copy")),
+    rule("obfuscation.encoded_command", None),
+    rule("obfuscation.decode_pipe", None),
+    rule("obfuscation.inline_decode", None),
+    // Dependencies
+    rule("dependencies.edit", Some("dependencies.enabled")),
+    rule("dependencies.write", Some("dependencies.enabled")),
+    rule(
+        "dependencies.pip_install",
+        Some("dependencies.block_direct_installs"),
+    ),
+    rule(
+        "dependencies.pipx_install",
+        Some("dependencies.block_direct_installs"),
+    ),
+    rule(
+        "dependencies.conda_install",
+        Some("dependencies.block_direct_installs"),
+    ),
+    rule(
+        "dependencies.mamba_install",
+        Some("dependencies.block_direct_installs"),
+    ),
+    rule("uv.pip.install", None),
+    rule("uv.run.with", None),
+    rule("npm.install.no_save", None),
+    rule("node.add.workspace_root", None),
+    rule("node.install.global", None),
+    // Containers and infrastructure
+    rule("docker.exec.env", Some("docker.block_env")),
+    rule("docker.inspect.env", Some("docker.block_inspect")),
+    rule(
+        "docker.run.sensitive_mount",
+        Some("docker.block_sensitive_mounts"),
+    ),
+    rule("docker.cp.sensitive", Some("docker.block_sensitive_copy")),
+    rule("kubectl.get.secret", None),
+    rule("kubectl.delete.all", None),
+    rule("kubectl.delete.namespace", None),
+    rule("kubectl.delete.volume", None),
+    rule("kubectl.drain.force", None),
+    rule("terraform.destroy", None),
+    rule("terraform.apply.auto_approve", None),
+    rule("terraform.state.rm", None),
+    rule("terraform.workspace.delete", None),
+    // Cloud CLIs
+    rule("aws.configure.export", None),
+    rule("aws.iam.keys", None),
+    rule("aws.kms.decrypt", None),
+    rule("aws.secretsmanager.get", None),
+    rule("aws.ssm.decrypt", None),
+    rule("aws.sts.credentials", None),
+    rule("az.account.token", None),
+    rule("az.acr.credentials", None),
+    rule("az.ad.sp.create", None),
+    rule("az.aks.credentials", None),
+    rule("az.appconfig.credentials", None),
+    rule("az.batch.keys", None),
+    rule("az.cognitiveservices.keys", None),
+    rule("az.communication.keys", None),
+    rule("az.containerapp.secret", None),
+    rule("az.containerapp.secrets", None),
+    rule("az.cosmosdb.keys", None),
+    rule("az.eventgrid.keys", None),
+    rule("az.eventhubs.keys", None),
+    rule("az.functionapp.keys", None),
+    rule("az.keyvault.cert.download", None),
+    rule("az.keyvault.key.download", None),
+    rule("az.keyvault.secret", None),
+    rule("az.keyvault.secret.download", None),
+    rule("az.maps.keys", None),
+    rule("az.purview.keys", None),
+    rule("az.redis.keys", None),
+    rule("az.relay.keys", None),
+    rule("az.servicebus.keys", None),
+    rule("az.signalr.keys", None),
+    rule("az.staticwebapp.secrets", None),
+    rule("az.storage.keys", None),
+    rule("az.storage.sas", None),
+    rule("az.webapp.appsettings", None),
+    rule("az.webapp.publishing", None),
+    rule("az.webpubsub.keys", None),
+    rule("gcloud.auth.token", None),
+    rule("gcloud.secret", None),
+    rule("gcloud.secrets.access", None),
+    rule("gcloud.sql.password", None),
+    rule("heroku.auth.token", None),
+    rule("heroku.config", None),
+    rule("heroku.config.get", None),
+    rule("heroku.pg.credentials", None),
+    rule("heroku.redis.credentials", None),
+    rule("vault.kv.get", None),
+    rule("vault.read", None),
+    rule("vault.login.token", None),
+    rule("vault.token.create", None),
+    rule("vault.token.print", None),
+    // Rewrites, tool input, sessions and allowlist mode
+    rule("rm.interactive", Some("rewrite.enabled")),
+    rule("git.clean.dry_run", Some("rewrite.enabled")),
+    rule("task.policy_reminder", Some("task.policy_reminder")),
+    rule("input.unparseable", Some("input.on_unparseable")),
+    rule("escalation.strict", Some("escalation.strict")),
+    rule("lockdown.session", Some("lockdown.enabled")),
+    rule("allowlist.unlisted_command", Some("mode")),
+];
+
+/// Whether `setting` is on in `config`, or `None` for an unknown setting.
+fn setting_enabled(config: &Config, setting: &str) -> Option<bool> {
+    let protected = |action: ProtectAction| action != ProtectAction::Allow;
+    let enabled = match setting {
+        "secrets.block_inline" => config.secrets.block_inline,
+        "secrets.scan_content" => config.secrets.scan_content,
+        "paranoid.enabled" => config.paranoid.enabled,
+        "search.block_sensitive_paths" => config.search.block_sensitive_paths,
+        "search.block_secret_hunting" => config.search.block_secret_hunting,
+        "git.block_add_sensitive" => config.git.block_add_sensitive,
+        "git.block_commit_secrets" => config.git.block_commit_secrets,
+        "protect.git_internals" => protected(config.protect.git_internals()),
+        "protect.shell_startup" => protected(config.protect.shell_startup()),
+        "protect.ssh" => protected(config.protect.ssh()),
+        "protect.self_protection" => protected(config.protect.self_protection()),
+        "protect.ci" => protected(config.protect.ci()),
+        "protect.persistence" => protected(config.protect.persistence()),
+        "rm.block_outside_cwd" => config.rm.block_outside_cwd,
+        "permissions.block_world_writable" => config.permissions.block_world_writable,
+        "permissions.block_system_paths" => config.permissions.block_system_paths,
+        "permissions.block_sensitive" => config.permissions.block_sensitive,
+        "network.block_local_admin" => config.network.block_local_admin,
+        "network.ask_external_uploads" => config.network.ask_external_uploads,
+        "network.block_remote_copy" => config.network.block_remote_copy,
+        "dependencies.enabled" => config.dependencies.enabled,
+        "dependencies.block_direct_installs" => {
+            config.dependencies.enabled && config.dependencies.block_direct_installs
+        }
+        "docker.block_env" => config.docker.block_env,
+        "docker.block_inspect" => config.docker.block_inspect,
+        "docker.block_sensitive_mounts" => config.docker.block_sensitive_mounts,
+        "docker.block_sensitive_copy" => config.docker.block_sensitive_copy,
+        "rewrite.enabled" => config.rewrite.enabled,
+        "task.policy_reminder" => config.task.policy_reminder.is_some(),
+        "input.on_unparseable" => config.input.on_unparseable != UnparseableAction::Allow,
+        "escalation.strict" => config.escalation.threshold > 0 && config.escalation.strict,
+        "lockdown.enabled" => config.lockdown.enabled,
+        "mode" => config.mode == EnforcementMode::Allowlist,
+        _ => return None,
+    };
+    Some(enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_catalog_ids_unique_and_settings_known() {
+        let config = Config::default();
+        let mut ids = HashSet::new();
+        for rule in BUILTIN_RULES {
+            assert!(ids.insert(rule.id), "duplicate rule {}", rule.id);
+            if let Some(setting) = rule.setting {
+                assert!(
+                    setting_enabled(&config, setting).is_some(),
+                    "unknown setting {}",
+                    setting
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_settings_turn_rules_off() {
+        let rule = |id: &str| BUILTIN_RULES.iter().find(|rule| rule.id == id).unwrap();
+        let mut config = Config::default();
+        assert!(rule("git.push.force").is_enabled(&config));
+        assert!(rule("ci.workflow").is_enabled(&config));
+        assert!(!rule("paranoid.sensitive_file").is_enabled(&config));
+
+        config.protect.ci = Some(ProtectAction::Allow);
+        config.paranoid.enabled = true;
+        config.dependencies.enabled = false;
+        assert!(!rule("ci.workflow").is_enabled(&config));
+        assert!(rule("paranoid.sensitive_file").is_enabled(&config));
+        assert!(!rule("dependencies.pip_install").is_enabled(&config));
+    }
+}
@@ -0,0 +1,134 @@
+//! dd/mkfs/shred analysis - blocks commands that overwrite or wipe block
+//! devices, and dd reads of sensitive files.
+
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::Token;
+
+/// Device files that are safe to read or write: they hold no data.
+const SAFE_DEVICES: &[&str] = &[
+    "/dev/null",
+    "/dev/zero",
+    "/dev/full",
+    "/dev/random",
+    "/dev/urandom",
+    "/dev/stdin",
+    "/dev/stdout",
+    "/dev/stderr",
+    "/dev/tty",
+];
+
+/// Analyze disk commands for destructive writes to block devices.
+pub fn analyze_disk(tokens: &[Token], config: &CompiledConfig) -> Decision {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+    let Some((&cmd, args)) = words.split_first() else {
+        return Decision::allow();
+    };
+    let mut operands = args.iter().copied().filter(|w| !w.starts_with('-'));
+
+    match cmd {
+        "dd" => {
+            let operand = |name: &str| {
+                args.iter()
+                    .find_map(|arg| arg.strip_prefix(name)?.strip_prefix('='))
+            };
+            if let Some(device) = operand("of").filter(|of| is_block_device(of)) {
+                Decision::block(
+                    "disk.dd.device",
+                    format!("dd of={} overwrites a block device", device),
+                )
+            } else if let Some(file) =
+                operand("if").filter(|f| config.is_sensitive_path(f).is_some())
+            {
+                Decision::block(
+                    "disk.dd.sensitive",
+                    format!("dd if={} reads a sensitive file", file),
+                )
+            } else {
+                Decision::allow()
+            }
+        }
+        _ if cmd.starts_with("mkfs") || cmd == "mke2fs" || cmd == "mkswap" => {
+            match operands.find(|w| is_block_device(w)) {
+                Some(device) => Decision::block(
+                    "disk.format",
+                    format!(
+                        "{} {} formats a block device, destroying its data",
+                        cmd, device
+                    ),
+                ),
+                None => Decision::allow(),
+            }
+        }
+        "shred" | "wipefs" | "blkdiscard" => match operands.find(|w| is_block_device(w)) {
+            Some(device) => Decision::block(
+                "disk.wipe",
+                format!("{} {} wipes a block device", cmd, device),
+            ),
+            None => Decision::allow(),
+        },
+        _ => Decision::allow(),
+    }
+}
+
+/// Whether `path` names a device that may hold data (a disk, partition,
+/// volume or loop device).
+fn is_block_device(path: &str) -> bool {
+    path.starts_with("/dev/")
+        && !SAFE_DEVICES.contains(&path)
+        && !path.starts_with("/dev/fd/")
+        && !path.starts_with("/dev/pts/")
+        && !path.starts_with("/dev/shm/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+
+    fn analyze(command: &str) -> Decision {
+        analyze_disk(&tokenize(command), &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_device_writes_blocked() {
+        for (command, rule) in [
+            ("dd if=/dev/zero of=/dev/sda bs=1M", "disk.dd.device"),
+            ("dd if=image.iso of=/dev/disk2 bs=4m", "disk.dd.device"),
+            ("dd if=/dev/urandom of=/dev/nvme0n1p1", "disk.dd.device"),
+            ("dd if=.env of=out.txt", "disk.dd.sensitive"),
+            ("dd if=/home/user/.ssh/id_rsa", "disk.dd.sensitive"),
+            ("mkfs.ext4 /dev/sdb1", "disk.format"),
+            ("mkfs -t xfs /dev/vdb", "disk.format"),
+            ("mkswap /dev/sda2", "disk.format"),
+            ("shred -n 3 -z /dev/sdb", "disk.wipe"),
+            ("wipefs -a /dev/sdc", "disk.wipe"),
+            ("blkdiscard /dev/nvme1n1", "disk.wipe"),
+        ] {
+            let decision = analyze(command);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some(rule), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_file_operations_allowed() {
+        for command in [
+            "dd if=/dev/zero of=disk.img bs=1M count=64",
+            "dd if=/dev/urandom of=/dev/null count=1",
+            "dd if=input.bin of=/dev/stdout",
+            "mkfs.ext4 disk.img",
+            "shred -u old-notes.txt",
+            "wipefs disk.img",
+        ] {
+            assert!(!analyze(command).is_blocked(), "{}", command);
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! Docker and Podman analysis - blocks commands that expose secrets held by
+//! containers or hand host credentials to them.
+//!
+//! Each capability has its own rule id and `[docker]` toggle:
+//! - `docker.exec.env`: `docker exec`/`run` of `env`, `printenv` or
+//!   `/proc/*/environ` inside a container
+//! - `docker.inspect.env`: `docker inspect` output that includes the
+//!   container's environment
+//! - `docker.run.sensitive_mount`: mounting `~/.ssh`, `~/.aws`, the Docker
+//!   socket or similar into a container
+//! - `docker.cp.sensitive`: `docker cp` of a sensitive path in or out
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::Token;
+
+/// Flags taking a value, for the docker CLI itself and its `exec`/`run`.
+const VALUE_FLAGS: &[&str] = &[
+    // Global
+    "-H",
+    "--host",
+    "-c",
+    "--context",
+    "--config",
+    "-l",
+    "--log-level",
+    // exec/run
+    "-e",
+    "--env",
+    "--env-file",
+    "-u",
+    "--user",
+    "-w",
+    "--workdir",
+    "--detach-keys",
+    "-v",
+    "--volume",
+    "--mount",
+    "-p",
+    "--publish",
+    "--name",
+    "--network",
+    "--net",
+    "--entrypoint",
+    "--label",
+    "--platform",
+    "-m",
+    "--memory",
+    "--cpus",
+    "--restart",
+    "-h",
+    "--hostname",
+    "--add-host",
+    "--device",
+    "--cap-add",
+    "--cap-drop",
+    "--pull",
+    "--log-driver",
+    "--log-opt",
+    "--gpus",
+    "--ulimit",
+    "--shm-size",
+    "--tmpfs",
+    "--dns",
+    "--security-opt",
+    "--runtime",
+    "--ipc",
+    "--pid",
+    "--userns",
+    "--expose",
+    "--volumes-from",
+    // inspect
+    "-f",
+    "--format",
+    "--type",
+];
+
+/// Host paths that hold credentials or control the host when mounted.
+static SENSITIVE_MOUNT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(^|/)\.(ssh|aws|kube|gnupg|docker|azure)(/|$)|(^|/)\.config/(gcloud|gh)(/|$)|^(/|/etc|/root|/home|~|\$HOME|\$\{HOME\})/?$|docker\.sock$",
+    )
+    .unwrap()
+});
+
+/// `env` or `printenv` run as a command in a shell script.
+static SCRIPT_ENV_DUMP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(^|[;&|(]|\$\()\s*(env|printenv)\s*($|[;&|)>])").unwrap());
+
+/// Shells whose `-c` script is checked.
+const SHELLS: &[&str] = &["sh", "bash", "ash", "dash", "zsh"];
+
+/// Analyze a docker, podman or compose command.
+pub fn analyze_docker(tokens: &[Token], config: &CompiledConfig) -> Decision {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+    let Some((&cli, rest)) = words.split_first() else {
+        return Decision::allow();
+    };
+
+    // `docker container exec`, `docker compose exec` and `docker-compose
+    // exec` behave like `docker exec`
+    let mut command = operands(rest);
+    if !cli.ends_with("-compose")
+        && let ["container" | "image" | "compose", group_args @ ..] = command.as_slice()
+    {
+        command = operands(group_args);
+    }
+    let Some((&subcommand, args)) = command.split_first() else {
+        return Decision::allow();
+    };
+    let docker = &config.raw.docker;
+
+    match subcommand {
+        "exec" | "run" => {
+            if docker.block_sensitive_mounts
+                && let Some(source) = mount_sources(args)
+                    .into_iter()
+                    .find(|s| is_sensitive_mount(s, config))
+            {
+                return Decision::block(
+                    "docker.run.sensitive_mount",
+                    format!(
+                        "{} {} mounts {} into the container, exposing host credentials or control of the host",
+                        cli, subcommand, source
+                    ),
+                )
+                .with_alternatives(["mount only the project directory, e.g. -v \"$PWD\":/src"]);
+            }
+            if docker.block_env {
+                // The container (or image) is the first operand; the rest
+                // is the command run inside it
+                let inner = operands(args);
+                if prints_env(inner.get(1..).unwrap_or_default()) {
+                    return Decision::block(
+                        "docker.exec.env",
+                        format!(
+                            "{} {} prints the container's environment, which often holds secrets",
+                            cli, subcommand
+                        ),
+                    )
+                    .with_alternatives(["check one non-secret variable, e.g. echo \"$PATH\""]);
+                }
+            }
+            Decision::allow()
+        }
+        "inspect" if docker.block_inspect && !formats_without_env(args) => Decision::block(
+            "docker.inspect.env",
+            format!(
+                "{} inspect prints the container's environment variables, which often hold secrets",
+                cli
+            ),
+        )
+        .with_alternatives(["select the fields you need, e.g. --format '{{.State.Status}}'"]),
+        "cp" if docker.block_sensitive_copy => operands(args)
+            .iter()
+            .map(|arg| arg.split_once(':').map_or(*arg, |(_, path)| path))
+            .find(|path| {
+                is_sensitive_mount(path, config) || config.is_sensitive_path(path).is_some()
+            })
+            .map_or(Decision::allow(), |path| {
+                Decision::block(
+                    "docker.cp.sensitive",
+                    format!("{} cp copies sensitive path {}", cli, path),
+                )
+            }),
+        _ => Decision::allow(),
+    }
+}
+
+/// `args` from the first operand on, skipping the flags (and their values)
+/// before it. Everything after the first operand is kept, since it is the
+/// rest of a subcommand or the command run inside a container.
+fn operands<'a>(args: &[&'a str]) -> Vec<&'a str> {
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        if !arg.starts_with('-') {
+            return std::iter::once(arg).chain(iter.copied()).collect();
+        }
+        if VALUE_FLAGS.contains(&arg) {
+            iter.next();
+        }
+    }
+    Vec::new()
+}
+
+/// Whether a command run in a container prints its environment: bare
+/// `env` or `printenv`, a shell script running them, or a read of
+/// `/proc/*/environ`.
+fn prints_env(command: &[&str]) -> bool {
+    let Some((&cmd, args)) = command.split_first() else {
+        return false;
+    };
+    if command
+        .iter()
+        .any(|w| w.contains("/proc/") && w.contains("environ"))
+    {
+        return true;
+    }
+    match cmd {
+        "printenv" => true,
+        // `env` with a command runs it instead of printing
+        "env" => args.iter().all(|a| a.starts_with('-') || a.contains('=')),
+        shell if SHELLS.contains(&shell) => args
+            .iter()
+            .skip_while(|a| !a.starts_with('-') || !a.contains('c'))
+            .nth(1)
+            .is_some_and(|script| SCRIPT_ENV_DUMP.is_match(script)),
+        _ => false,
+    }
+}
+
+/// Host sides of `-v`/`--volume` and `--mount` bind mounts.
+fn mount_sources<'a>(args: &[&'a str]) -> Vec<&'a str> {
+    let mut sources = Vec::new();
+    let mut iter = args.iter().copied();
+    while let Some(arg) = iter.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+            _ => (arg, None),
+        };
+        let value = match flag {
+            "-v" | "--volume" | "--mount" => value.or_else(|| iter.next()),
+            // Only flags before the image apply to the container
+            _ if !arg.starts_with('-') => break,
+            _ => {
+                if VALUE_FLAGS.contains(&arg) {
+                    iter.next();
+                }
+                continue;
+            }
+        };
+        let Some(value) = value else { continue };
+        if flag == "--mount" {
+            sources.extend(value.split(',').find_map(|field| {
+                field
+                    .strip_prefix("source=")
+                    .or_else(|| field.strip_prefix("src="))
+            }));
+        } else {
+            sources.extend(value.split(':').next());
+        }
+    }
+    sources
+}
+
+fn is_sensitive_mount(path: &str, config: &CompiledConfig) -> bool {
+    let path = path.trim_end_matches('/');
+    let path = if path.is_empty() { "/" } else { path };
+    SENSITIVE_MOUNT.is_match(path) || config.is_sensitive_path(path).is_some()
+}
+
+/// Whether `inspect` is limited by `--format` to fields other than the
+/// environment.
+fn formats_without_env(args: &[&str]) -> bool {
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        let format = match arg {
+            "-f" | "--format" => iter.next().copied(),
+            _ => arg.strip_prefix("--format="),
+        };
+        if let Some(format) = format {
+            let whole_config = format.contains("json .}}")
+                || format.contains("json .Config}}")
+                || format.contains("{{.Config}}")
+                || format.contains("{{json .}}");
+            return !format.contains("Env") && !whole_config;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+
+    fn analyze(command: &str) -> Decision {
+        analyze_docker(&tokenize(command), &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_exec_env_blocked() {
+        for command in [
+            "docker exec web env",
+            "docker exec -it web printenv",
+            "docker exec -u root web cat /proc/1/environ",
+            "docker exec web sh -c 'env | grep KEY'",
+            "docker run --rm -e A=b alpine env",
+            "podman exec web printenv DATABASE_URL",
+            "docker compose exec api env",
+            "docker-compose exec -T api printenv",
+            "docker container exec web env",
+            "docker --context prod exec web env",
+        ] {
+            assert_eq!(
+                analyze(command).rule(),
+                Some("docker.exec.env"),
+                "{}",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_inspect() {
+        for command in [
+            "docker inspect web",
+            "docker container inspect web",
+            "docker inspect --format '{{json .Config.Env}}' web",
+            "docker inspect -f '{{json .Config}}' web",
+        ] {
+            assert_eq!(
+                analyze(command).rule(),
+                Some("docker.inspect.env"),
+                "{}",
+                command
+            );
+        }
+        assert!(!analyze("docker inspect --format '{{.State.Status}}' web").is_blocked());
+        assert!(!analyze("docker inspect -f '{{.NetworkSettings.IPAddress}}' web").is_blocked());
+    }
+
+    #[test]
+    fn test_sensitive_mounts_blocked() {
+        for command in [
+            "docker run -v ~/.ssh:/root/.ssh alpine sh",
+            "docker run --volume=$HOME/.aws:/root/.aws:ro amazon/aws-cli s3 ls",
+            "docker run -v /var/run/docker.sock:/var/run/docker.sock img",
+            "docker run -v /:/host alpine",
+            "docker run --mount type=bind,source=/home/me/.kube,target=/k img",
+            "podman run -v ./.env:/app/.env img",
+        ] {
+            assert_eq!(
+                analyze(command).rule(),
+                Some("docker.run.sensitive_mount"),
+                "{}",
+                command
+            );
+        }
+        for command in [
+            "docker run -v \"$PWD\":/src -w /src rust cargo build",
+            "docker run -v data:/var/lib/postgresql/data postgres",
+            "docker run --env-file .env.example img",
+            // Arguments of the command inside the container are not mounts
+            "docker run img ls -v /",
+        ] {
+            assert!(!analyze(command).is_blocked(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_cp_sensitive_blocked() {
+        for command in [
+            "docker cp web:/root/.aws/credentials .",
+            "docker cp web:/app/.env ./env-copy",
+            "docker cp ~/.ssh/id_rsa web:/tmp/",
+        ] {
+            assert_eq!(
+                analyze(command).rule(),
+                Some("docker.cp.sensitive"),
+                "{}",
+                command
+            );
+        }
+        assert!(!analyze("docker cp web:/app/logs ./logs").is_blocked());
+    }
+
+    #[test]
+    fn test_routine_commands_allowed() {
+        for command in [
+            "docker ps",
+            "docker build -t app .",
+            "docker exec web ls /app",
+            "docker run --rm -e ENV=prod app ./migrate",
+            "docker compose up -d",
+            "docker logs web",
+            "docker image ls",
+        ] {
+            assert!(!analyze(command).is_blocked(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_capabilities_configured_independently() {
+        let mut config = Config::default();
+        config.docker.block_inspect = false;
+        let config = config.compile().unwrap();
+        assert!(!analyze_docker(&tokenize("docker inspect web"), &config).is_blocked());
+        assert!(analyze_docker(&tokenize("docker exec web env"), &config).is_blocked());
+    }
+}
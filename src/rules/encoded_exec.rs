@@ -0,0 +1,274 @@
+//! Encoded payload detection - blocks running code that is only decoded at
+//! run time, as in `echo cm0gLXJmIH4= | base64 -d | sh`.
+//!
+//! The decoded command never appears in the command line, so none of the
+//! other rules can look at it. Decoding is fine and so is running a
+//! script; running what was just decoded is not.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::pipe_to_shell::runs_stdin;
+use crate::decision::Decision;
+use crate::shell::{
+    CommandSegment, Operator, Token, command_substitutions, split_commands, strip_wrappers,
+    tokenize,
+};
+
+/// Shells, whose `-c` argument is a command line.
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh", "ash", "fish"];
+
+/// Interpreters and the flags that run inline code.
+const INLINE_INTERPRETERS: &[(&str, &[&str])] = &[
+    ("python", &["-c"]),
+    ("python2", &["-c"]),
+    ("python3", &["-c"]),
+    ("node", &["-e", "-p", "--eval", "--print"]),
+    ("perl", &["-e", "-E"]),
+    ("ruby", &["-e"]),
+    ("php", &["-r"]),
+];
+
+/// A call that runs code given as a string.
+static EXEC_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(exec|eval|system|popen|Function|compile|execSync|spawnSync)\s*\(")
+        .expect("valid regex")
+});
+
+/// A call that decodes base64, base32 or hex.
+static DECODE_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(b64decode|b32decode|decode64|decode_base64|\batob\s*\(|fromhex|unhexlify|\bunpack\b|['"](base64|hex)['"])"#)
+        .expect("valid regex")
+});
+
+/// Block a command line that decodes a payload and runs it.
+pub fn check_encoded_execution(segments: &[CommandSegment]) -> Decision {
+    // Whether the pipeline so far decodes its input
+    let mut decoded = false;
+    let mut piped = false;
+    for segment in segments {
+        if !piped {
+            decoded = false;
+        }
+        let words = words(&segment.command);
+        if let Some((cmd, args)) = words.split_first() {
+            let cmd = cmd.rsplit('/').next().unwrap_or(cmd);
+            if piped && decoded && runs_stdin(cmd, args) {
+                return Decision::block(
+                    "obfuscation.decode_pipe",
+                    format!(
+                        "{} would run a payload decoded earlier in the pipeline",
+                        cmd
+                    ),
+                );
+            }
+            if let Some(decision) = check_segment(&segment.command, cmd, args) {
+                return decision;
+            }
+            decoded = decoded || is_decoder(cmd, args);
+        }
+        piped = segment.operator == Some(Operator::Pipe);
+    }
+    Decision::allow()
+}
+
+/// Block a single command that runs decoded or encoded code.
+fn check_segment(command: &str, cmd: &str, args: &[String]) -> Option<Decision> {
+    let lower = cmd.to_lowercase();
+    let name = lower.strip_suffix(".exe").unwrap_or(&lower);
+    if matches!(name, "powershell" | "pwsh") && args.iter().any(|arg| is_encoded_command_flag(arg))
+    {
+        return Some(Decision::block(
+            "obfuscation.encoded_command",
+            format!("{} -EncodedCommand runs a base64-encoded script", cmd),
+        ));
+    }
+
+    // `eval "$(echo ... | base64 -d)"`, `bash -c "$(... | base64 -d)"` and
+    // a bare `$(... | base64 -d)`, which runs its output as a command
+    let code = if cmd.starts_with("$(") || cmd.starts_with('`') {
+        Some(strip_wrappers(command))
+    } else if cmd == "eval" {
+        Some(args.join(" "))
+    } else if SHELLS.contains(&cmd) {
+        inline_code(args, &["-c"])
+    } else {
+        None
+    };
+    if let Some(code) = code
+        && command_substitutions(&code, true)
+            .iter()
+            .flat_map(|substitution| split_commands(substitution))
+            .any(|segment| {
+                let words = words(&segment.command);
+                words
+                    .split_first()
+                    .is_some_and(|(cmd, args)| is_decoder(cmd, args))
+            })
+    {
+        return Some(Decision::block(
+            "obfuscation.decode_pipe",
+            format!("{} would run a payload decoded from base64 or hex", cmd),
+        ));
+    }
+
+    // `python -c "exec(base64.b64decode(...))"`
+    let flags = INLINE_INTERPRETERS
+        .iter()
+        .find(|(name, _)| *name == cmd)
+        .map(|(_, flags)| *flags)?;
+    let code = inline_code(args, flags)?;
+    (EXEC_CALL.is_match(&code) && DECODE_CALL.is_match(&code)).then(|| {
+        Decision::block(
+            "obfuscation.inline_decode",
+            format!("{} would execute code decoded at run time", cmd),
+        )
+    })
+}
+
+/// The argument of the first of `flags`, or of a short flag group ending in
+/// one of them (`-ec`).
+fn inline_code(args: &[String], flags: &[&str]) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let matches = flags.contains(&arg.as_str())
+            || (!arg.starts_with("--")
+                && arg.len() > 2
+                && flags
+                    .iter()
+                    .any(|flag| flag.len() == 2 && arg.ends_with(&flag[1..]))
+                && arg[1..].chars().all(|c| c.is_ascii_alphabetic()));
+        if matches {
+            return args.next().cloned();
+        }
+    }
+    None
+}
+
+/// Check if `cmd args` decodes its input.
+fn is_decoder(cmd: &str, args: &[String]) -> bool {
+    let cmd = cmd.rsplit('/').next().unwrap_or(cmd);
+    let short = |flag: char| {
+        args.iter().any(|arg| {
+            arg.strip_prefix('-')
+                .is_some_and(|flags| !flags.starts_with('-') && flags.contains(flag))
+        })
+    };
+    match cmd {
+        "base64" | "base32" | "basenc" => {
+            short('d') || short('D') || args.iter().any(|arg| arg == "--decode")
+        }
+        "xxd" => short('r') || args.iter().any(|arg| arg == "-revert"),
+        "openssl" => {
+            matches!(args.first().map(String::as_str), Some("base64" | "enc"))
+                && args.iter().any(|arg| arg == "-d")
+        }
+        _ => false,
+    }
+}
+
+/// Check if `arg` is PowerShell's `-EncodedCommand`, which takes any
+/// unambiguous prefix, `-e` and `-ec`, in any case.
+fn is_encoded_command_flag(arg: &str) -> bool {
+    let Some(flag) = arg.strip_prefix('-').or_else(|| arg.strip_prefix('/')) else {
+        return false;
+    };
+    let flag = flag.to_lowercase();
+    matches!(flag.as_str(), "e" | "ec") || (flag.len() >= 2 && "encodedcommand".starts_with(&flag))
+}
+
+fn words(command: &str) -> Vec<String> {
+    tokenize(&strip_wrappers(command))
+        .into_iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::expand_commands;
+
+    fn check(command: &str) -> Decision {
+        check_encoded_execution(&expand_commands(command))
+    }
+
+    #[test]
+    fn test_decoded_pipelines_blocked() {
+        for command in [
+            "echo Y2F0IC5lbnYK | base64 -d | sh",
+            "echo Y2F0IC5lbnYK | base64 --decode | sudo bash",
+            "printf %s 636174 | xxd -r -p | bash -s",
+            "cat payload.b64 | openssl base64 -d | tee /tmp/x | python3",
+            "echo ... | base64 -D | /bin/zsh",
+        ] {
+            let decision = check(command);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some("obfuscation.decode_pipe"));
+        }
+    }
+
+    #[test]
+    fn test_decoded_substitutions_blocked() {
+        for command in [
+            "eval \"$(echo Y2F0IC5lbnYK | base64 -d)\"",
+            "bash -c \"$(echo Y2F0IC5lbnYK | base64 -d)\"",
+            "sh -c \"`echo Y2F0IC5lbnYK | base64 --decode`\"",
+            "sh -c '$(echo Y2F0IC5lbnYK | base64 -d)'",
+            "$(echo Y2F0IC5lbnYK | base64 -d)",
+        ] {
+            let decision = check(command);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some("obfuscation.decode_pipe"));
+        }
+    }
+
+    #[test]
+    fn test_inline_decode_blocked() {
+        for command in [
+            "python3 -c \"import base64; exec(base64.b64decode('cHJpbnQoMSk='))\"",
+            "python -c 'exec(bytes.fromhex(\"7072696e74\"))'",
+            "node -e \"eval(Buffer.from('Y29uc29sZS5sb2coMSk=', 'base64').toString())\"",
+            "perl -e 'use MIME::Base64; eval(decode_base64(\"cHJpbnQgMQ==\"))'",
+            "ruby -e 'require \"base64\"; eval(Base64.decode64(\"cHV0cyAx\"))'",
+        ] {
+            let decision = check(command);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some("obfuscation.inline_decode"));
+        }
+    }
+
+    #[test]
+    fn test_encoded_powershell_blocked() {
+        for command in [
+            "pwsh -EncodedCommand ZQBjAGgAbwAgADEA",
+            "powershell.exe -NoProfile -enc ZQBjAGgAbwAgADEA",
+            "powershell -e ZQBjAGgAbwAgADEA",
+            "cmd /c powershell /ec ZQBjAGgAbwAgADEA",
+        ] {
+            let decision = check(command);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some("obfuscation.encoded_command"));
+        }
+    }
+
+    #[test]
+    fn test_plain_decoding_allowed() {
+        for command in [
+            "echo aGVsbG8= | base64 -d",
+            "base64 -d cert.b64 > cert.pem",
+            "echo aGVsbG8= | base64 -d | jq .",
+            "echo aGVsbG8= | base64 -d; sh build.sh",
+            "base64 data.bin | sh -c 'cat > data.b64'",
+            "python3 -c 'import base64; print(base64.b64decode(\"aGk=\"))'",
+            "python3 -c 'exec(open(\"setup.py\").read())'",
+            "pwsh -ExecutionPolicy Bypass -File build.ps1",
+            "eval \"$(ssh-agent -s)\"",
+        ] {
+            assert!(matches!(check(command), Decision::Allow), "{}", command);
+        }
+    }
+}
@@ -84,6 +84,10 @@ pub fn analyze_gcloud(tokens: &[Token], _config: &CompiledConfig) -> Decision {
                     "gcloud.secrets.access",
                     "gcloud secrets versions access exposes secret value",
                 )
+                .with_alternatives([
+                    "gcloud secrets describe <secret>",
+                    "gcloud secrets versions list --secret=<secret>",
+                ])
             } else {
                 Decision::allow()
             }
@@ -1,88 +1,216 @@
 //! Built-in and custom rules for command analysis.
 
+mod allowlist;
 mod aws;
 mod azure;
+mod catalog;
+mod curl;
 mod custom;
+mod disk;
+mod docker;
+mod encoded_exec;
 mod find;
 mod gcloud;
 mod git;
 mod heroku;
+mod inline_secrets;
 mod kubectl;
+mod network;
+mod node_pm;
 mod parallel;
+mod perms;
+mod persistence;
+mod pip;
+mod pipe_to_shell;
+mod protected_paths;
+mod remote_copy;
+mod rewrite;
 mod rm;
+mod sensitive_env;
 mod sensitive_files;
 pub(crate) mod substitution;
+mod terraform;
 mod uv;
+mod vault;
+mod windows;
+mod workaround;
 mod xargs;
 
+pub use allowlist::check_allowlist;
 pub use aws::analyze_aws;
 pub use azure::analyze_azure;
-pub use custom::check_custom_rules;
+pub use catalog::{BUILTIN_RULES, BuiltinRule};
+pub use curl::analyze_curl;
+pub use custom::{check_custom_rule, check_custom_rules};
+pub use disk::analyze_disk;
+pub use docker::analyze_docker;
+pub use encoded_exec::check_encoded_execution;
 pub use find::analyze_find;
 pub use gcloud::{analyze_gcloud, analyze_gcloud_raw};
 pub use git::analyze_git;
 pub use heroku::analyze_heroku;
-pub use kubectl::analyze_kubectl;
+pub use inline_secrets::{check_content_secrets, check_inline_secrets, check_prompt_secrets};
+pub use kubectl::{analyze_kubectl, analyze_kubectl_raw};
+pub use network::{check_blocked_hosts, check_local_admin};
+pub use node_pm::analyze_node_pm;
 pub use parallel::analyze_parallel;
+pub use perms::analyze_perms;
+pub use persistence::analyze_persistence;
+pub use pip::analyze_pip;
+pub use pipe_to_shell::check_pipe_to_shell;
+pub use protected_paths::{check_protected_write, check_protected_write_tokens};
+pub use remote_copy::analyze_remote_copy;
+pub use rewrite::{CommandRewrite, suggest_rewrite};
 pub use rm::analyze_rm;
-pub use sensitive_files::{check_git_add_sensitive, check_sensitive_path};
+pub use sensitive_env::check_sensitive_env;
+pub(crate) use sensitive_files::is_sensitive_dir;
+pub use sensitive_files::{check_git_add_sensitive, check_sensitive_glob, check_sensitive_path};
+pub use terraform::analyze_terraform;
 pub use uv::analyze_uv;
+pub use vault::analyze_vault;
+pub use windows::{analyze_windows, is_windows_command};
+pub use workaround::is_workaround_attempt;
 pub use xargs::analyze_xargs;
 
+use std::path::Path;
+
 use crate::config::CompiledConfig;
 use crate::decision::Decision;
-use crate::shell::{Token, split_commands, strip_wrappers, tokenize};
+use crate::parallel::{PARALLEL_SEGMENTS, map_ordered};
+use crate::shell::{Token, expand_commands, strip_wrappers, tokenize};
 
 /// Analyze a command and return a decision.
 pub fn analyze_command(command: &str, config: &CompiledConfig, cwd: Option<&str>) -> Decision {
     // These analyzers need the full raw command to detect $(...) substitution bypasses
-    let decision = analyze_kubectl(command);
+    let mut blocks = Decision::Allow;
+    let decision = analyze_kubectl_raw(command);
     if decision.is_blocked() {
-        return decision;
+        tracing::debug!(rule = ?decision.rule(), "kubectl substitution check blocked");
+        blocks = decision;
     }
 
     let decision = analyze_gcloud_raw(command);
     if decision.is_blocked() {
-        return decision;
+        tracing::debug!(rule = ?decision.rule(), "gcloud substitution check blocked");
+        blocks = blocks.and_also(decision);
     }
 
-    // Split command on operators
-    let segments = split_commands(command);
+    // Split command on operators, including inside `bash -c`, `ssh` and
+    // `xargs sh -c`; long scripts are analyzed in parallel
+    let segments = expand_commands(command);
+    let parallel = segments.len() >= PARALLEL_SEGMENTS;
+    let decisions = map_ordered(&segments, parallel, |segment| {
+        analyze_segment(&segment.command, config, cwd)
+    });
 
-    for segment in &segments {
-        // Strip wrappers to get actual command
-        let stripped = strip_wrappers(&segment.command);
-        let tokens = tokenize(&stripped);
+    // The first blocking segment wins, as if evaluated in order, and the
+    // findings of the other segments are kept with it
+    for decision in decisions {
+        blocks = blocks.and_also(decision);
+    }
 
-        // Get command name
-        let cmd_name = tokens.iter().find_map(|t| match t {
-            Token::Word(w) => Some(w.as_str()),
-            _ => None,
-        });
+    // Pipelines, whose segments are harmless one by one
+    let decision = check_encoded_execution(&segments);
+    if decision.is_blocked() {
+        blocks = blocks.and_also(decision);
+    }
+    blocks.and_also(check_pipe_to_shell(&segments))
+}
+
+/// Analyze one command segment with the built-in rules.
+fn analyze_segment(segment: &str, config: &CompiledConfig, cwd: Option<&str>) -> Decision {
+    // Strip wrappers to get actual command
+    let stripped = strip_wrappers(segment);
+    let tokens = tokenize(&stripped);
+    tracing::trace!(segment, stripped = %stripped, "command segment");
 
-        let Some(cmd_name) = cmd_name else {
-            continue;
-        };
+    let protected = check_protected_write_tokens(&tokens, config);
+    if protected.is_blocked() {
+        return protected;
+    }
 
-        // Check built-in rules based on command
-        let decision = match cmd_name {
-            "git" => analyze_git(&tokens, config),
-            "rm" => analyze_rm(&tokens, config, cwd),
-            "find" => analyze_find(&tokens, config),
-            "xargs" => analyze_xargs(&tokens, config),
-            "parallel" => analyze_parallel(&tokens, config),
-            "heroku" => analyze_heroku(&tokens, config),
-            "aws" => analyze_aws(&tokens, config),
-            "az" => analyze_azure(&tokens, config),
-            "gcloud" => analyze_gcloud(&tokens, config),
-            "uv" => analyze_uv(&tokens, config),
-            _ => Decision::Allow,
-        };
+    // Get command name
+    let cmd_name = tokens.iter().find_map(|t| match t {
+        Token::Word(w) => Some(w.as_str()),
+        _ => None,
+    });
 
-        if decision.is_blocked() {
-            return decision;
+    // Check built-in rules based on command
+    cmd_name
+        .and_then(|cmd_name| analyze_builtin(cmd_name, &tokens, config, cwd))
+        .map_or(protected.clone(), |decision| protected.stricter(decision))
+}
+
+/// Run the built-in analyzer for a command name; `None` if there is none.
+///
+/// The command is matched on its basename, so `/usr/local/bin/terraform`
+/// gets the same analysis as `terraform`.
+pub fn analyze_builtin(
+    cmd_name: &str,
+    tokens: &[Token],
+    config: &CompiledConfig,
+    cwd: Option<&str>,
+) -> Option<Decision> {
+    let base = Path::new(cmd_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(cmd_name);
+    // Analyzers read the command name from the tokens too
+    let renamed;
+    let tokens = if base != cmd_name {
+        renamed = rename_command(tokens, base);
+        &renamed[..]
+    } else {
+        tokens
+    };
+    let cmd_name = base;
+    let decision = match cmd_name {
+        "git" => analyze_git(tokens, config, cwd),
+        "rm" => analyze_rm(tokens, config, cwd),
+        "chmod" | "chown" | "chgrp" => analyze_perms(tokens, config),
+        "crontab" | "systemctl" | "launchctl" => analyze_persistence(tokens, config),
+        "find" => analyze_find(tokens, config),
+        "xargs" => analyze_xargs(tokens, config),
+        "parallel" => analyze_parallel(tokens, config),
+        "heroku" => analyze_heroku(tokens, config),
+        "curl" | "wget" => analyze_curl(tokens, config),
+        "scp" | "rsync" | "sftp" | "lftp" => analyze_remote_copy(tokens, config),
+        "aws" => analyze_aws(tokens, config),
+        "az" => analyze_azure(tokens, config),
+        "gcloud" => analyze_gcloud(tokens, config),
+        "docker" | "podman" | "docker-compose" | "podman-compose" => analyze_docker(tokens, config),
+        "kubectl" | "k" => analyze_kubectl(tokens, config),
+        "npm" | "yarn" | "pnpm" => analyze_node_pm(tokens, config),
+        "pip" | "pip3" | "pipx" | "conda" | "mamba" | "python" | "python3" => {
+            analyze_pip(tokens, config, cwd)
         }
-    }
+        "uv" => analyze_uv(tokens, config),
+        "terraform" | "tofu" => analyze_terraform(tokens, config),
+        "vault" => analyze_vault(tokens, config),
+        "dd" | "shred" | "wipefs" | "blkdiscard" | "mke2fs" | "mkswap" => {
+            analyze_disk(tokens, config)
+        }
+        name if name.starts_with("mkfs") => analyze_disk(tokens, config),
+        name if is_windows_command(name) => analyze_windows(tokens, config),
+        _ => return None,
+    };
+    tracing::debug!(
+        command = cmd_name,
+        decision = decision.label(),
+        rule = ?decision.rule(),
+        "built-in rule evaluated"
+    );
+    Some(decision)
+}
 
-    Decision::Allow
+/// Replace the first word of a command with `name`.
+fn rename_command(tokens: &[Token], name: &str) -> Vec<Token> {
+    let mut tokens = tokens.to_vec();
+    if let Some(word) = tokens.iter_mut().find_map(|t| match t {
+        Token::Word(w) => Some(w),
+        _ => None,
+    }) {
+        *word = name.to_string();
+    }
+    tokens
 }
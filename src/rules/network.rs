@@ -0,0 +1,245 @@
+//! Network destinations no command or tool may reach.
+//!
+//! Cloud metadata endpoints hand out live credentials to anything that can
+//! reach them, so they are blocked by default; `[network] blocked_hosts`
+//! adds more. The host is looked for anywhere in a command, which covers
+//! curl, wget, httpie, `python -c` with urllib or requests, and anything
+//! else that takes a URL or a host.
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::config::{CompiledConfig, METADATA_HOSTS};
+use crate::decision::Decision;
+
+/// Block text (a command or a URL) that names a blocked host.
+pub fn check_blocked_hosts(text: &str, config: &CompiledConfig) -> Decision {
+    let Some(host) = find_blocked_host(text, &config.raw.network.blocked_hosts) else {
+        return Decision::allow();
+    };
+    tracing::debug!(host, "blocked network destination");
+    if METADATA_HOSTS.contains(&host) {
+        Decision::block(
+            "network.metadata",
+            format!(
+                "{} is a cloud metadata endpoint, which hands out live credentials",
+                host
+            ),
+        )
+        .with_alternatives(["ask the user for the instance details you need"])
+    } else {
+        Decision::block(
+            "network.blocked_host",
+            format!("{} is a blocked network destination", host),
+        )
+    }
+}
+
+/// Block a URL for an admin port (`[network] admin_ports`) on a loopback
+/// or private address, such as the Docker API on `localhost:2375`.
+pub fn check_local_admin(url: &str, config: &CompiledConfig) -> Decision {
+    let network = &config.raw.network;
+    if !network.block_local_admin {
+        return Decision::allow();
+    }
+    let Some((host, Some(port))) = host_and_port(url) else {
+        return Decision::allow();
+    };
+    if !network.admin_ports.contains(&port) || !is_local(&host) {
+        return Decision::allow();
+    }
+    Decision::block(
+        "network.local_admin",
+        format!(
+            "{}:{} is a local admin port, which trusts whoever can reach it",
+            host, port
+        ),
+    )
+}
+
+/// The host and explicit port of a URL.
+fn host_and_port(url: &str) -> Option<(String, Option<u16>)> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(v6) => {
+            let (host, rest) = v6.split_once(']')?;
+            (host, rest.strip_prefix(':'))
+        }
+        None => match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(port) => Some(port.parse().ok()?),
+        None => None,
+    };
+    (!host.is_empty()).then(|| (host.to_ascii_lowercase(), port))
+}
+
+/// Loopback, private and unspecified addresses, including IPv4 addresses
+/// written as a single decimal or hex number.
+fn is_local(host: &str) -> bool {
+    let number = match host.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => host.parse::<u32>().ok(),
+    };
+    let host = match number {
+        Some(n) => Ipv4Addr::from(n).to_string(),
+        None => host.to_string(),
+    };
+    is_internal(&host) || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_unspecified())
+}
+
+/// The first of `hosts` that `text` names, also matching IPv4 addresses
+/// written as a single decimal or hex number (`2852039166`, `0xa9fea9fe`).
+fn find_blocked_host<'a>(text: &str, hosts: &'a [String]) -> Option<&'a str> {
+    let text = text.to_ascii_lowercase();
+    hosts.iter().map(String::as_str).find(|host| {
+        let host = host.to_ascii_lowercase();
+        let mut spellings = vec![host.clone()];
+        if let Ok(ip) = host.parse::<Ipv4Addr>() {
+            let n = u32::from(ip);
+            spellings.extend([n.to_string(), format!("0x{:x}", n)]);
+        }
+        spellings.iter().any(|s| names_host(&text, s))
+    })
+}
+
+/// Whether `host` appears in `text` as a whole host name, not as part of a
+/// longer name or address.
+fn names_host(text: &str, host: &str) -> bool {
+    let is_host_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    text.match_indices(host).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let mut after = text[start + host.len()..].chars();
+        let ok_before = !before.is_some_and(|c| is_host_char(c) || c == '.');
+        // A trailing dot ends a fully qualified name or a sentence
+        let ok_after = match after.next() {
+            Some('.') => !after.next().is_some_and(is_host_char),
+            Some(c) => !is_host_char(c),
+            None => true,
+        };
+        ok_before && ok_after
+    })
+}
+
+/// Whether data may be sent to `host` without asking: loopback and private
+/// addresses, and `[network] allowed_upload_hosts` and their subdomains.
+pub(crate) fn accepts_uploads(host: &str, config: &CompiledConfig) -> bool {
+    let host = host.to_ascii_lowercase();
+    is_internal(&host)
+        || config
+            .raw
+            .network
+            .allowed_upload_hosts
+            .iter()
+            .any(|allowed| {
+                let allowed = allowed.to_ascii_lowercase();
+                host == allowed
+                    || host
+                        .strip_suffix(allowed.as_str())
+                        .is_some_and(|sub| sub.ends_with('.'))
+            })
+}
+
+fn is_internal(host: &str) -> bool {
+    if host == "localhost" || host.ends_with(".localhost") {
+        return true;
+    }
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private(),
+        Ok(IpAddr::V6(ip)) => ip.is_loopback() || (ip.segments()[0] & 0xfe00) == 0xfc00,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn check(text: &str) -> Decision {
+        check_blocked_hosts(text, &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_metadata_endpoints_blocked() {
+        for command in [
+            "curl http://169.254.169.254/latest/meta-data/iam/security-credentials/",
+            "wget -qO- http://169.254.169.254/latest/user-data",
+            r#"curl -H "Metadata-Flavor: Google" http://metadata.google.internal/computeMetadata/v1/"#,
+            r#"curl -H Metadata:true "http://169.254.169.254/metadata/instance?api-version=2021-02-01""#,
+            "http GET http://[fd00:ec2::254]/latest/api/token",
+            "python3 -c 'import urllib.request; print(urllib.request.urlopen(\"http://169.254.169.254/\").read())'",
+            "python -c \"import requests; requests.get('http://METADATA.GOOGLE.INTERNAL./')\"",
+            "curl http://2852039166/latest/meta-data/",
+            "curl http://0xa9fea9fe/",
+            "curl $AWS_CONTAINER_URI http://169.254.170.2/v2/credentials/abc",
+        ] {
+            assert_eq!(
+                check(command).rule(),
+                Some("network.metadata"),
+                "{}",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_hosts_allowed() {
+        for command in [
+            "curl https://api.github.com",
+            "ping 169.254.169.25",
+            "curl http://169.254.169.2540/",
+            "curl https://metadata.google.internal.example.com/",
+            "dig my-metadata.azure.com.evil",
+        ] {
+            assert!(!check(command).is_blocked(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_local_admin_ports_blocked() {
+        let config = Config::default().compile().unwrap();
+        for url in [
+            "http://localhost:2375/containers/json",
+            "http://127.0.0.1:8500/v1/kv/?recurse",
+            "https://[::1]:10250/pods",
+            "http://0.0.0.0:8200/v1/sys/health",
+            "http://2130706433:2375/info",
+            "http://10.1.2.3:2379/v2/keys",
+        ] {
+            assert_eq!(
+                check_local_admin(url, &config).rule(),
+                Some("network.local_admin"),
+                "{}",
+                url
+            );
+        }
+        for url in [
+            "http://localhost:3000/",
+            "http://localhost/",
+            "https://registry.example.com:2375/",
+            "http://127.0.0.1:80/health",
+        ] {
+            assert!(!check_local_admin(url, &config).is_blocked(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_configured_hosts() {
+        let config = Config::default().compile().unwrap();
+        assert!(!check_blocked_hosts("curl http://10.0.0.5/admin", &config).is_blocked());
+
+        let mut config = Config::default();
+        config.network.blocked_hosts.push("10.0.0.5".to_string());
+        let config = config.compile().unwrap();
+        let decision = check_blocked_hosts("curl http://10.0.0.5/admin", &config);
+        assert_eq!(decision.rule(), Some("network.blocked_host"));
+        assert!(check_blocked_hosts("curl http://169.254.169.254/", &config).is_blocked());
+    }
+}
@@ -0,0 +1,148 @@
+//! npm/yarn/pnpm analysis - catches installs that bypass package.json.
+
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::Token;
+
+/// npm's spellings of `install`, including the typo aliases it accepts.
+const NPM_INSTALL: &[&str] = &[
+    "install", "i", "in", "ins", "inst", "insta", "instal", "isnt", "isnta", "isntal", "isntall",
+    "add",
+];
+
+/// Analyze Node package manager commands for installs that bypass package.json.
+pub fn analyze_node_pm(tokens: &[Token], _config: &CompiledConfig) -> Decision {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+    let Some((&cli, rest)) = words.split_first() else {
+        return Decision::allow();
+    };
+    let (flags, args): (Vec<&str>, Vec<&str>) = rest.iter().partition(|w| w.starts_with('-'));
+    let has_flag = |names: &[&str]| flags.iter().any(|f| names.contains(f));
+    let global = has_flag(&["-g", "--global", "--location=global"]);
+
+    match (cli, args.as_slice()) {
+        ("npm", [sub, packages @ ..]) if NPM_INSTALL.contains(sub) => {
+            if global {
+                global_install(cli)
+            } else if has_flag(&["--no-save", "--save=false"]) && !packages.is_empty() {
+                Decision::block(
+                    "npm.install.no_save",
+                    "npm install --no-save installs packages without recording them in package.json",
+                )
+                .with_alternatives(["npm install <package> (adds it to package.json)"])
+            } else {
+                Decision::allow()
+            }
+        }
+
+        ("yarn", ["global", "add", ..]) => global_install(cli),
+        ("pnpm", ["add" | "install" | "i", ..]) if global => global_install(cli),
+
+        // Yarn refuses to add to a workspace root unless told to, since
+        // dependencies usually belong in a workspace package
+        ("yarn" | "pnpm", ["add", ..]) if has_flag(&["-W", "--ignore-workspace-root-check"]) => {
+            Decision::ask(
+                "node.add.workspace_root",
+                format!(
+                    "{} add --ignore-workspace-root-check adds dependencies to the workspace root",
+                    cli
+                ),
+            )
+        }
+
+        _ => Decision::allow(),
+    }
+}
+
+fn global_install(cli: &str) -> Decision {
+    Decision::ask(
+        "node.install.global",
+        format!(
+            "Global {} installs change the machine rather than the project's package.json",
+            cli
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+
+    fn analyze(command: &str) -> Decision {
+        analyze_node_pm(&tokenize(command), &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_no_save_blocked() {
+        for command in [
+            "npm install left-pad --no-save",
+            "npm i --no-save left-pad",
+            "npm add left-pad --save=false",
+        ] {
+            assert_eq!(
+                analyze(command).rule(),
+                Some("npm.install.no_save"),
+                "{}",
+                command
+            );
+        }
+        assert!(!analyze("npm install --no-save").is_blocked());
+    }
+
+    #[test]
+    fn test_global_installs_ask() {
+        for command in [
+            "npm i -g typescript",
+            "npm install --global typescript",
+            "npm install --location=global typescript",
+            "yarn global add typescript",
+            "pnpm add -g typescript",
+        ] {
+            let decision = analyze(command);
+            assert!(decision.is_ask(), "{}", command);
+            assert_eq!(decision.rule(), Some("node.install.global"), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_workspace_root_check_asks() {
+        for command in [
+            "yarn add -W lodash",
+            "yarn add lodash --ignore-workspace-root-check",
+            "pnpm add lodash --ignore-workspace-root-check",
+        ] {
+            let decision = analyze(command);
+            assert!(decision.is_ask(), "{}", command);
+            assert_eq!(
+                decision.rule(),
+                Some("node.add.workspace_root"),
+                "{}",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_recorded_installs_allowed() {
+        for command in [
+            "npm install",
+            "npm install lodash",
+            "npm ci",
+            "npm run build",
+            "yarn add lodash",
+            "yarn install",
+            "pnpm add lodash",
+            "pnpm add -w lodash",
+        ] {
+            assert!(matches!(analyze(command), Decision::Allow), "{}", command);
+        }
+    }
+}
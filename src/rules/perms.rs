@@ -0,0 +1,179 @@
+//! chmod/chown/chgrp analysis - blocks world-writable trees, recursive
+//! changes on system paths and changes to credential directories.
+
+use super::sensitive_files::is_sensitive_dir;
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::Token;
+
+/// Analyze chmod, chown and chgrp commands.
+pub fn analyze_perms(tokens: &[Token], config: &CompiledConfig) -> Decision {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+    let Some((&cmd, args)) = words.split_first() else {
+        return Decision::allow();
+    };
+    let permissions = &config.raw.permissions;
+
+    let (flags, mut operands): (Vec<&str>, Vec<&str>) =
+        args.iter().partition(|w| w.starts_with('-') && w.len() > 1);
+    let recursive = flags
+        .iter()
+        .any(|f| *f == "--recursive" || (!f.starts_with("--") && f.contains('R')));
+    // The mode or owner comes first, unless copied from a reference file
+    let spec = if flags.iter().any(|f| f.starts_with("--reference")) || operands.is_empty() {
+        None
+    } else {
+        Some(operands.remove(0))
+    };
+    let system_path = operands
+        .iter()
+        .copied()
+        .find(|path| is_system_path(path, &permissions.system_paths));
+
+    if permissions.block_sensitive
+        && let Some(path) = operands
+            .iter()
+            .find(|path| is_sensitive_dir(path) || config.is_sensitive_path(path).is_some())
+    {
+        return Decision::block(
+            "permissions.sensitive",
+            format!("{} on {} changes who can use credentials", cmd, path),
+        )
+        .with_alternatives(["ask the user to change the permissions themselves"]);
+    }
+
+    if permissions.block_world_writable
+        && cmd == "chmod"
+        && let Some(mode) = spec.filter(|mode| is_world_writable(mode))
+        && (recursive || system_path.is_some())
+    {
+        return Decision::block(
+            "permissions.world_writable",
+            format!(
+                "chmod {} {} makes files writable by every user",
+                mode,
+                if recursive {
+                    "-R"
+                } else {
+                    system_path.unwrap_or_default()
+                }
+            ),
+        )
+        .with_alternatives(["grant write access to the owner or group only (e.g. 755 or g+w)"]);
+    }
+
+    if permissions.block_system_paths
+        && recursive
+        && let Some(path) = system_path
+    {
+        return Decision::block(
+            "permissions.system_path",
+            format!("recursive {} on system path {}", cmd, path),
+        );
+    }
+
+    Decision::allow()
+}
+
+/// Whether a chmod mode grants write access to others, as in `777`,
+/// `o+w` or `a=rwx`.
+fn is_world_writable(mode: &str) -> bool {
+    if mode.chars().all(|c| c.is_ascii_digit()) {
+        return mode
+            .chars()
+            .last()
+            .and_then(|c| c.to_digit(8))
+            .is_some_and(|others| others & 2 != 0);
+    }
+    mode.split(',').any(|clause| {
+        let Some(op) = clause.find(['+', '=']) else {
+            return false;
+        };
+        let (who, perms) = clause.split_at(op);
+        who.contains(['o', 'a']) && perms.contains('w')
+    })
+}
+
+fn is_system_path(path: &str, system_paths: &[String]) -> bool {
+    let path = path.trim_end_matches('/');
+    system_paths.iter().any(|system| {
+        let system = system.trim_end_matches('/');
+        // Directly under it, e.g. /usr/local or /etc/ssh; nothing is
+        // directly under / only
+        let child = |system: &str| {
+            path.strip_prefix(system)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .is_some_and(|child| !child.is_empty() && !child.contains('/'))
+        };
+        path == system || (!system.is_empty() && child(system))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+
+    fn analyze(command: &str) -> Decision {
+        analyze_perms(&tokenize(command), &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_dangerous_changes_blocked() {
+        for (command, rule) in [
+            ("chmod -R 777 .", "permissions.world_writable"),
+            ("chmod -Rf a+rwx build/", "permissions.world_writable"),
+            ("chmod --recursive o+w public", "permissions.world_writable"),
+            ("chmod 777 /", "permissions.world_writable"),
+            ("chmod 666 /etc/passwd", "permissions.world_writable"),
+            ("chown -R user:user /", "permissions.system_path"),
+            ("chown -R me /usr/local", "permissions.system_path"),
+            ("chown -R me /", "permissions.system_path"),
+            ("chgrp -R staff /etc/", "permissions.system_path"),
+            ("chmod -R 755 /usr", "permissions.system_path"),
+            ("chmod 700 ~/.ssh", "permissions.sensitive"),
+            ("chmod 644 /home/user/.ssh/id_rsa", "permissions.sensitive"),
+            ("chown -R nobody ~/.aws/", "permissions.sensitive"),
+        ] {
+            let decision = analyze(command);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some(rule), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_project_changes_allowed() {
+        for command in [
+            "chmod +x scripts/deploy.sh",
+            "chmod 755 bin/tool",
+            "chmod 777 tmp-socket",
+            "chmod -R u+w,g-w src",
+            "chmod -R 750 build",
+            "chown -R me:me ./node_modules",
+            "chown -R app:app /app",
+            "chown me /usr/local/bin/tool",
+            "chmod --reference=a.txt b.txt",
+        ] {
+            assert!(matches!(analyze(command), Decision::Allow), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_rules_can_be_disabled() {
+        let mut config = Config::default();
+        config.permissions.block_sensitive = false;
+        config.permissions.block_world_writable = false;
+        let config = config.compile().unwrap();
+        for command in ["chmod 700 ~/.ssh", "chmod -R 777 ."] {
+            let decision = analyze_perms(&tokenize(command), &config);
+            assert!(matches!(decision, Decision::Allow), "{}", command);
+        }
+    }
+}
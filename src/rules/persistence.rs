@@ -0,0 +1,145 @@
+//! crontab/systemctl/launchctl analysis - asks before installing jobs and
+//! services that keep running after the session ends.
+//!
+//! Writing the job files directly is covered by the protected paths; both
+//! follow `[protect] persistence`.
+
+use crate::config::{CompiledConfig, ProtectAction};
+use crate::decision::{AskInfo, Decision};
+use crate::shell::Token;
+
+/// Analyze commands that install cron jobs, services or launch agents.
+pub fn analyze_persistence(tokens: &[Token], config: &CompiledConfig) -> Decision {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+    let Some((&cmd, args)) = words.split_first() else {
+        return Decision::allow();
+    };
+    let mut flags = Vec::new();
+    let mut operands = Vec::new();
+    let mut args = args.iter().copied();
+    while let Some(arg) = args.next() {
+        if arg.starts_with('-') && arg.len() > 1 {
+            flags.push(arg);
+            // crontab -u USER
+            if cmd == "crontab" && arg == "-u" {
+                args.next();
+            }
+        } else {
+            operands.push(arg);
+        }
+    }
+
+    let (rule, reason) = match (cmd, operands.as_slice()) {
+        // crontab FILE (or -) replaces the crontab; -l only lists it
+        ("crontab", _) if flags.contains(&"-e") || flags.contains(&"-r") => (
+            "persistence.crontab",
+            "crontab -e/-r edits the scheduled jobs".to_string(),
+        ),
+        ("crontab", [file, ..]) if !flags.contains(&"-l") => (
+            "persistence.crontab",
+            format!("crontab {} replaces the scheduled jobs", file),
+        ),
+        (
+            "systemctl",
+            [
+                sub @ ("enable" | "link" | "reenable" | "preset"),
+                units @ ..,
+            ],
+        ) => (
+            "persistence.systemd",
+            format!(
+                "systemctl {} {} starts the service at every boot or login",
+                sub,
+                units.join(" ")
+            ),
+        ),
+        (
+            "launchctl",
+            [
+                sub @ ("load" | "bootstrap" | "enable" | "submit"),
+                rest @ ..,
+            ],
+        ) => (
+            "persistence.launchd",
+            format!(
+                "launchctl {} {} starts the agent at every login or boot",
+                sub,
+                rest.join(" ")
+            ),
+        ),
+        _ => return Decision::allow(),
+    };
+
+    let alternative = "show the user the job or service definition and let them install it";
+    match config.raw.protect.persistence() {
+        ProtectAction::Allow => Decision::allow(),
+        ProtectAction::Ask => {
+            Decision::Ask(AskInfo::new(rule, reason).with_suggestion(alternative))
+        }
+        ProtectAction::Block => Decision::block(rule, reason).with_alternatives([alternative]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+
+    fn analyze(command: &str) -> Decision {
+        analyze_persistence(&tokenize(command), &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_installing_jobs_asks() {
+        for (command, rule) in [
+            ("crontab -e", "persistence.crontab"),
+            ("crontab jobs.txt", "persistence.crontab"),
+            ("crontab -u root -", "persistence.crontab"),
+            (
+                "systemctl enable --now backdoor.service",
+                "persistence.systemd",
+            ),
+            ("systemctl --user enable sync.timer", "persistence.systemd"),
+            (
+                "launchctl load -w ~/Library/LaunchAgents/com.x.plist",
+                "persistence.launchd",
+            ),
+            (
+                "launchctl bootstrap gui/501 agent.plist",
+                "persistence.launchd",
+            ),
+        ] {
+            let decision = analyze(command);
+            assert!(decision.is_ask(), "{}", command);
+            assert_eq!(decision.rule(), Some(rule), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_inspecting_jobs_allowed() {
+        for command in [
+            "crontab -l",
+            "systemctl status nginx",
+            "systemctl restart nginx",
+            "systemctl --user list-timers",
+            "launchctl list",
+        ] {
+            assert!(matches!(analyze(command), Decision::Allow), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_protect_setting_applies() {
+        let mut config = Config::default();
+        config.protect.persistence = Some(ProtectAction::Block);
+        let config = config.compile().unwrap();
+        assert!(analyze_persistence(&tokenize("crontab -e"), &config).is_blocked());
+    }
+}
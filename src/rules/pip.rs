@@ -0,0 +1,188 @@
+//! pip/pipx/conda analysis - blocks installing packages directly into a
+//! project that declares its dependencies.
+
+use std::path::Path;
+
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::Token;
+
+/// Files whose presence in cwd means dependencies belong in them.
+const PROJECT_FILES: &[&str] = &["pyproject.toml", "environment.yml", "environment.yaml"];
+
+/// Install options that take a value, which is not a package to install.
+const VALUE_FLAGS: &[&str] = &[
+    "-r",
+    "--requirement",
+    "-c",
+    "--constraint",
+    "--channel",
+    "-e",
+    "--editable",
+    "-i",
+    "--index-url",
+    "--extra-index-url",
+    "-f",
+    "--find-links",
+    "-t",
+    "--target",
+    "--prefix",
+    "-p",
+    "--root",
+    "-n",
+    "--name",
+    "--file",
+    "--python",
+    "--pip-args",
+];
+
+/// Analyze pip, pipx and conda commands for direct package installs.
+pub fn analyze_pip(tokens: &[Token], config: &CompiledConfig, cwd: Option<&str>) -> Decision {
+    let dependencies = &config.raw.dependencies;
+    if !dependencies.enabled || !dependencies.block_direct_installs {
+        return Decision::allow();
+    }
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+    // python -m pip behaves like pip
+    let words = match words.as_slice() {
+        [python, "-m", rest @ ..] if python.starts_with("python") => rest,
+        [python, ..] if python.starts_with("python") => return Decision::allow(),
+        words => words,
+    };
+
+    let (cli, args) = match words {
+        [
+            cli @ ("pip" | "pip3" | "pipx" | "conda" | "mamba"),
+            "install",
+            args @ ..,
+        ] => (*cli, args),
+        _ => return Decision::allow(),
+    };
+    let Some(packages) = named_packages(args) else {
+        return Decision::allow();
+    };
+    let Some(project_file) = cwd.and_then(|cwd| {
+        PROJECT_FILES
+            .iter()
+            .find(|file| Path::new(cwd).join(file).is_file())
+    }) else {
+        return Decision::allow();
+    };
+
+    let decision = Decision::block(
+        format!("dependencies.{}_install", cli.trim_end_matches('3')),
+        format!(
+            "{} install {} bypasses {}, so the dependency is never recorded",
+            cli, packages, project_file
+        ),
+    );
+    if matches!(cli, "conda" | "mamba") {
+        decision.with_alternatives([
+            format!("add it to {} and run `{} env update`", project_file, cli),
+            "uv add <package>".to_string(),
+        ])
+    } else {
+        decision.with_alternatives(["uv add <package>", "poetry add <package>"])
+    }
+}
+
+/// The packages named on an install command line, or `None` when it only
+/// installs from requirement files or local paths.
+fn named_packages(args: &[&str]) -> Option<String> {
+    let mut packages = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if VALUE_FLAGS.contains(arg) {
+            args.next();
+        } else if !arg.starts_with('-') && !arg.starts_with(['.', '/', '~']) {
+            packages.push(*arg);
+        }
+    }
+    (!packages.is_empty()).then(|| packages.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+    use tempfile::TempDir;
+
+    fn project(file: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(file), "").unwrap();
+        dir
+    }
+
+    fn analyze(command: &str, cwd: &TempDir) -> Decision {
+        analyze_pip(
+            &tokenize(command),
+            &Config::default().compile().unwrap(),
+            cwd.path().to_str(),
+        )
+    }
+
+    #[test]
+    fn test_direct_installs_blocked_in_project() {
+        let dir = project("pyproject.toml");
+        for (command, rule) in [
+            ("pip install requests", "dependencies.pip_install"),
+            ("pip3 install -U requests", "dependencies.pip_install"),
+            ("python -m pip install requests", "dependencies.pip_install"),
+            (
+                "pip install -r requirements.txt flask",
+                "dependencies.pip_install",
+            ),
+            ("pipx install black", "dependencies.pipx_install"),
+            (
+                "conda install -c conda-forge numpy",
+                "dependencies.conda_install",
+            ),
+        ] {
+            let decision = analyze(command, &dir);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some(rule), "{}", command);
+        }
+        assert!(analyze("conda install numpy", &project("environment.yml")).is_blocked());
+    }
+
+    #[test]
+    fn test_project_installs_allowed() {
+        let dir = project("pyproject.toml");
+        for command in [
+            "pip install -e .",
+            "pip install .",
+            "pip install -r requirements.txt",
+            "pip list",
+            "python -c 'import pip'",
+            "conda install --file requirements.txt",
+        ] {
+            assert!(!analyze(command, &dir).is_blocked(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_installs_allowed_outside_project() {
+        let dir = TempDir::new().unwrap();
+        assert!(!analyze("pip install requests", &dir).is_blocked());
+    }
+
+    #[test]
+    fn test_direct_installs_can_be_allowed() {
+        let dir = project("pyproject.toml");
+        let mut config = Config::default();
+        config.dependencies.block_direct_installs = false;
+        let decision = analyze_pip(
+            &tokenize("pip install requests"),
+            &config.compile().unwrap(),
+            dir.path().to_str(),
+        );
+        assert!(!decision.is_blocked());
+    }
+}
@@ -0,0 +1,109 @@
+//! Safe rewrites of risky commands.
+
+use crate::decision::Decision;
+use crate::rules::git::rewrite_git_clean;
+use crate::rules::rm::rewrite_rm;
+use crate::shell::{Token, join_words, split_commands, tokenize};
+
+/// Characters the shell expands in unquoted words. Words containing these
+/// are not rewritten, since quoting them would change what the command does.
+const EXPANSION_CHARS: &[char] = &['$', '`', '*', '?', '[', '{', '~'];
+
+/// A safer replacement for a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRewrite {
+    /// The rule that produced the rewrite.
+    pub rule: &'static str,
+    /// Why the command was rewritten.
+    pub reason: &'static str,
+    /// The replacement command.
+    pub command: String,
+}
+
+/// Suggest a safer replacement for a simple command.
+///
+/// Only single commands made of plain words are rewritten; pipelines,
+/// redirects, assignments and shell expansions are left alone. Commands
+/// that were blocked by an unrelated rule, or that already need approval,
+/// are never rewritten.
+pub fn suggest_rewrite(command: &str, decision: &Decision) -> Option<CommandRewrite> {
+    if decision.is_ask() || split_commands(command).len() != 1 {
+        return None;
+    }
+
+    let words = tokenize(command)
+        .into_iter()
+        .map(|t| match t {
+            Token::Word(w) if !w.contains(EXPANSION_CHARS) => Some(w),
+            _ => None,
+        })
+        .collect::<Option<Vec<String>>>()?;
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let blocked_rule = decision.block_info().map(|info| info.rule.as_str());
+
+    let (rule, reason, rewritten) = match (words.first()?, blocked_rule) {
+        (&"rm", None) => (
+            "rm.interactive",
+            "rm -rf replaced with rm -rI, which prompts once before deleting",
+            rewrite_rm(&words)?,
+        ),
+        (&"git", None) | (&"git", Some("git.clean" | "git.clean.force")) => (
+            "git.clean.dry_run",
+            "git clean replaced with a dry run that keeps ignored files",
+            rewrite_git_clean(&words)?,
+        ),
+        _ => return None,
+    };
+
+    Some(CommandRewrite {
+        rule,
+        reason,
+        command: join_words(&rewritten),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_rm_rf() {
+        let rewrite = suggest_rewrite("rm -rf build", &Decision::allow()).unwrap();
+        assert_eq!(rewrite.command, "rm -rI build");
+        assert_eq!(rewrite.rule, "rm.interactive");
+    }
+
+    #[test]
+    fn test_rewrite_keeps_quoted_paths() {
+        let rewrite = suggest_rewrite("rm -rf 'my build'", &Decision::allow()).unwrap();
+        assert_eq!(rewrite.command, "rm -rI 'my build'");
+    }
+
+    #[test]
+    fn test_rewrite_git_clean_block() {
+        let decision = Decision::block("git.clean.force", "deletes files");
+        let rewrite = suggest_rewrite("git clean -f -d -x", &decision).unwrap();
+        assert_eq!(rewrite.command, "git clean -f -d -n");
+    }
+
+    #[test]
+    fn test_no_rewrite_for_other_blocks() {
+        let decision = Decision::block("rm.outside_cwd", "outside cwd");
+        assert!(suggest_rewrite("rm -rf /etc", &decision).is_none());
+    }
+
+    #[test]
+    fn test_no_rewrite_for_compound_or_expanded() {
+        let allow = Decision::allow();
+        assert!(suggest_rewrite("rm -rf build && ls", &allow).is_none());
+        assert!(suggest_rewrite("rm -rf build/*", &allow).is_none());
+        assert!(suggest_rewrite("rm -rf $DIR", &allow).is_none());
+        assert!(suggest_rewrite("rm -rf build > log", &allow).is_none());
+    }
+
+    #[test]
+    fn test_no_rewrite_when_asking() {
+        let decision = Decision::ask("custom", "check first");
+        assert!(suggest_rewrite("rm -rf build", &decision).is_none());
+    }
+}
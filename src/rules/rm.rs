@@ -2,7 +2,7 @@
 
 use crate::config::CompiledConfig;
 use crate::decision::Decision;
-use crate::shell::Token;
+use crate::shell::{Token, expand_home};
 use std::path::Path;
 
 /// Analyze rm command for dangerous operations.
@@ -38,7 +38,7 @@ pub fn analyze_rm(tokens: &[Token], config: &CompiledConfig, cwd: Option<&str>)
             // Everything after -- is a path
             continue;
         } else if !word.starts_with('-') {
-            paths.push(*word);
+            paths.push(expand_home(word));
         }
     }
 
@@ -57,6 +57,46 @@ pub fn analyze_rm(tokens: &[Token], config: &CompiledConfig, cwd: Option<&str>)
     Decision::allow()
 }
 
+/// Rewrite a recursive force-delete so it prompts once before deleting.
+///
+/// `rm -rf build` becomes `rm -rI build`. Returns `None` when the command
+/// is not a recursive force-delete.
+pub fn rewrite_rm(words: &[&str]) -> Option<Vec<String>> {
+    let mut has_recursive = false;
+    let mut has_force = false;
+    let mut past_options = false;
+    let mut rewritten = vec![words.first()?.to_string()];
+
+    for word in &words[1..] {
+        if past_options || !word.starts_with('-') || *word == "-" {
+            rewritten.push(word.to_string());
+            continue;
+        }
+        match *word {
+            "--" => {
+                past_options = true;
+                rewritten.push(word.to_string());
+            }
+            "--force" => {
+                has_force = true;
+                rewritten.push("-I".to_string());
+            }
+            "--recursive" => {
+                has_recursive = true;
+                rewritten.push(word.to_string());
+            }
+            _ if word.starts_with("--") => rewritten.push(word.to_string()),
+            _ => {
+                has_recursive |= word.contains(['r', 'R']);
+                has_force |= word.contains('f');
+                rewritten.push(word.replace('f', "I"));
+            }
+        }
+    }
+
+    (has_recursive && has_force).then_some(rewritten)
+}
+
 fn check_rm_path(path: &str, config: &CompiledConfig, cwd: Option<&str>) -> Option<Decision> {
     // Normalize path for analysis
     let path_obj = Path::new(path);
@@ -75,6 +115,16 @@ fn check_rm_path(path: &str, config: &CompiledConfig, cwd: Option<&str>) -> Opti
         path.to_string()
     };
 
+    // Block rm -rf on the home directory itself
+    if let Some(home) = dirs::home_dir()
+        && Path::new(&normalized) == home
+    {
+        return Some(Decision::block(
+            "rm.dangerous_path",
+            format!("rm -rf on home directory '{}' is blocked", path),
+        ));
+    }
+
     // Block rm -rf on root or system directories
     for dangerous in &dangerous_paths {
         if normalized == *dangerous
@@ -90,10 +140,13 @@ fn check_rm_path(path: &str, config: &CompiledConfig, cwd: Option<&str>) -> Opti
 
     // Block any path starting with .. (parent escape) - always dangerous
     if path.starts_with("..") {
-        return Some(Decision::block(
-            "rm.parent_escape",
-            format!("rm -rf with parent traversal '{}' is blocked", path),
-        ));
+        return Some(
+            Decision::block(
+                "rm.parent_escape",
+                format!("rm -rf with parent traversal '{}' is blocked", path),
+            )
+            .with_alternatives(["ask the user to delete it themselves"]),
+        );
     }
 
     // Check if path is outside cwd (if cwd is known)
@@ -101,10 +154,13 @@ fn check_rm_path(path: &str, config: &CompiledConfig, cwd: Option<&str>) -> Opti
         && let Some(cwd) = cwd
         && !is_path_within(path, cwd, &config.raw.rm.allowed_paths)
     {
-        return Some(Decision::block(
-            "rm.outside_cwd",
-            format!("rm -rf outside working directory: '{}'", path),
-        ));
+        return Some(
+            Decision::block(
+                "rm.outside_cwd",
+                format!("rm -rf outside working directory: '{}'", path),
+            )
+            .with_alternatives(["ask the user to delete it themselves"]),
+        );
     }
 
     None
@@ -120,9 +176,9 @@ fn is_path_within(path: &str, cwd: &str, allowed_paths: &[String]) -> bool {
             return true;
         }
 
-        // Check allowed paths (like /tmp)
+        // Check allowed paths (like /tmp or ~/scratch)
         for allowed in allowed_paths {
-            if path.starts_with(allowed.as_str()) {
+            if path.starts_with(&expand_home(allowed)) {
                 return true;
             }
         }
@@ -216,6 +272,21 @@ mod tests {
         assert!(decision.is_blocked());
     }
 
+    #[test]
+    fn test_rewrite_rm_rf() {
+        let rewritten = rewrite_rm(&["rm", "-rf", "build"]).unwrap();
+        assert_eq!(rewritten, vec!["rm", "-rI", "build"]);
+        let rewritten = rewrite_rm(&["rm", "-r", "--force", "build"]).unwrap();
+        assert_eq!(rewritten, vec!["rm", "-r", "-I", "build"]);
+    }
+
+    #[test]
+    fn test_rewrite_rm_requires_recursive_force() {
+        assert!(rewrite_rm(&["rm", "-f", "file"]).is_none());
+        assert!(rewrite_rm(&["rm", "-r", "build"]).is_none());
+        assert!(rewrite_rm(&["rm", "-r", "--", "-f"]).is_none());
+    }
+
     #[test]
     fn test_rm_no_recursive() {
         let config = test_config();
@@ -223,4 +294,32 @@ mod tests {
         let decision = analyze_rm(&tokens, &config, Some("/home/user/project"));
         assert!(!decision.is_blocked()); // Not recursive
     }
+
+    #[test]
+    fn test_rm_rf_home_directory() {
+        let config = Config::default().compile().unwrap();
+        for command in ["rm -rf ~", "rm -rf $HOME", "rm -rf ${HOME}/", "rm -rf ~/"] {
+            let decision = analyze_rm(&tokenize(command), &config, None);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some("rm.dangerous_path"));
+        }
+        let decision = analyze_rm(&tokenize("rm -rf ~root"), &config, None);
+        assert!(decision.is_blocked());
+    }
+
+    #[test]
+    fn test_rm_rf_allowed_path_under_home() {
+        let config = Config {
+            rm: crate::config::RmConfig {
+                block_outside_cwd: true,
+                allowed_paths: vec!["~/scratch".to_string()],
+            },
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        let cwd = Some("/srv/project");
+        assert!(!analyze_rm(&tokenize("rm -rf ~/scratch/cache"), &config, cwd).is_blocked());
+        assert!(analyze_rm(&tokenize("rm -rf ~/work"), &config, cwd).is_blocked());
+    }
 }
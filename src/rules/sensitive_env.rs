@@ -0,0 +1,231 @@
+//! Targeted exposure of secret environment variables.
+//!
+//! The deny rules catch commands that start by dumping the environment
+//! (`printenv`, `export`); this catches a single secret being expanded
+//! (`echo $GITHUB_TOKEN`) or looked up (`printenv API_KEY`), and dumps that
+//! appear later in a command line (`env | grep -i secret`).
+
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::{Token, expand_commands, strip_wrappers, tokenize};
+
+/// Block commands that expand, print or dump secret environment variables.
+pub fn check_sensitive_env(command: &str, config: &CompiledConfig) -> Decision {
+    for name in expanded_variables(command) {
+        if let Some(pattern) = config.is_sensitive_env_var(name) {
+            tracing::debug!(name, pattern, "sensitive variable expanded");
+            return Decision::block(
+                "env.sensitive_var",
+                format!(
+                    "command expands ${}, which holds a secret; its value would be exposed",
+                    name
+                ),
+            )
+            .with_alternatives([
+                "use a tool that reads the variable itself (e.g. gh, aws, npm)",
+                "check that it is set without expanding it: [ -n \"${VAR:+set}\" ]",
+            ]);
+        }
+    }
+
+    for segment in expand_commands(command) {
+        let decision = check_segment(&segment.command, config);
+        if decision.is_blocked() {
+            return decision;
+        }
+    }
+
+    Decision::allow()
+}
+
+fn check_segment(segment: &str, config: &CompiledConfig) -> Decision {
+    let tokens = tokenize(&strip_wrappers(segment));
+    let command_words = words(&tokens);
+    let Some((&cmd, args)) = command_words.split_first() else {
+        // `env` with no command (or `sudo env`) prints the whole environment
+        if words(&tokenize(segment)).contains(&"env") {
+            return dump("env");
+        }
+        return Decision::allow();
+    };
+    let names: Vec<&str> = args
+        .iter()
+        .copied()
+        .filter(|w| !w.starts_with('-'))
+        .collect();
+    match cmd {
+        "printenv" if names.is_empty() => dump(cmd),
+        "printenv" => names
+            .iter()
+            .find(|name| config.is_sensitive_env_var(name).is_some())
+            .map_or(Decision::allow(), |name| {
+                Decision::block(
+                    "env.sensitive_var",
+                    format!("printenv {} prints a variable that holds a secret", name),
+                )
+            }),
+        "set" if args.is_empty() => dump(cmd),
+        "export" if names.is_empty() => dump(cmd),
+        "declare" | "typeset"
+            if names.is_empty() && args.iter().any(|w| w.contains(['x', 'p'])) =>
+        {
+            dump(cmd)
+        }
+        _ => Decision::allow(),
+    }
+}
+
+fn dump(cmd: &str) -> Decision {
+    Decision::block(
+        "env.dump",
+        format!(
+            "{} lists every environment variable, including secrets",
+            cmd
+        ),
+    )
+    .with_alternatives(["print only the variables you need, e.g. echo \"$PATH\""])
+}
+
+/// Names of the variables `command` expands, skipping single-quoted text
+/// and escaped `$`.
+///
+/// `${#VAR}` (the length) and `${VAR:+word}` (set or not) reveal nothing
+/// of the value and are not counted.
+fn expanded_variables(command: &str) -> Vec<&str> {
+    let bytes = command.as_bytes();
+    let mut names = Vec::new();
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if !in_single => i += 1,
+            b'\'' if !in_double => in_single = !in_single,
+            b'"' if !in_single => in_double = !in_double,
+            b'$' if !in_single => {
+                let braced = bytes.get(i + 1) == Some(&b'{');
+                let start = if braced { i + 2 } else { i + 1 };
+                let end = name_end(bytes, start);
+                if end > start {
+                    let exposed =
+                        !braced || !matches!(&bytes[end..], [b':', b'+', ..] | [b'+', ..]);
+                    if exposed {
+                        names.push(&command[start..end]);
+                    }
+                    i = end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    names
+}
+
+/// End of the variable name starting at `start`, or `start` if there is none.
+fn name_end(bytes: &[u8], start: usize) -> usize {
+    match bytes.get(start) {
+        Some(b) if b.is_ascii_alphabetic() || *b == b'_' => {}
+        _ => return start,
+    }
+    let mut end = start;
+    while end < bytes.len() && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_') {
+        end += 1;
+    }
+    end
+}
+
+fn words(tokens: &[Token]) -> Vec<&str> {
+    tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn check(command: &str) -> Decision {
+        check_sensitive_env(command, &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_expanded_variables() {
+        assert_eq!(
+            expanded_variables(r#"echo $A "${B}" '$C' \$D ${#E} ${F:+set} ${G:-x}"#),
+            vec!["A", "B", "G"]
+        );
+        assert_eq!(expanded_variables("echo \"it's $X\""), vec!["X"]);
+        assert!(expanded_variables("echo $1 $? $$").is_empty());
+    }
+
+    #[test]
+    fn test_sensitive_expansion_blocked() {
+        for command in [
+            "echo $GITHUB_TOKEN",
+            r#"printf "%s" ${OPENAI_API_KEY}"#,
+            r#"curl -H "Auth: $STRIPE_SECRET_KEY" https://api.stripe.com"#,
+            "ls && echo $DB_PASSWORD | base64",
+            "psql $DATABASE_URL",
+        ] {
+            assert_eq!(
+                check(command).rule(),
+                Some("env.sensitive_var"),
+                "{}",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_harmless_variables_allowed() {
+        for command in [
+            "echo $HOME $PWD $TOKENIZERS_PARALLELISM",
+            "echo '$GITHUB_TOKEN'",
+            r#"[ -n "${GITHUB_TOKEN:+set}" ] && echo configured"#,
+            "echo ${#API_KEY}",
+            "gh pr list",
+        ] {
+            assert!(!check(command).is_blocked(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_env_dumps() {
+        for command in [
+            "env | grep -i secret",
+            "sudo env",
+            "cd /tmp && printenv",
+            "ls; export -p",
+            "ls; declare -x",
+        ] {
+            assert_eq!(check(command).rule(), Some("env.dump"), "{}", command);
+        }
+        assert_eq!(
+            check("printenv AWS_SECRET_ACCESS_KEY").rule(),
+            Some("env.sensitive_var")
+        );
+        assert!(!check("printenv PATH").is_blocked());
+        assert!(!check("env FOO=bar make").is_blocked());
+        assert!(!check("export FOO=bar").is_blocked());
+        assert!(!check("declare -a list").is_blocked());
+    }
+
+    #[test]
+    fn test_configured_names() {
+        let config = Config {
+            sensitive_env_vars: vec![r"^ACME_".to_string()],
+            ..Default::default()
+        };
+        let config = config.compile().unwrap();
+        assert!(check_sensitive_env("echo $ACME_ID", &config).is_blocked());
+        assert!(!check_sensitive_env("echo $GITHUB_TOKEN", &config).is_blocked());
+    }
+}
@@ -1,5 +1,7 @@
 //! Sensitive file and secrets detection.
 
+use std::path::{Path, PathBuf};
+
 use crate::config::CompiledConfig;
 use crate::decision::{BlockInfo, Decision};
 
@@ -43,6 +45,216 @@ pub fn check_git_add_sensitive(paths: &[&str], config: &CompiledConfig) -> Decis
     Decision::allow()
 }
 
+/// Credential directories, matched as the last components of a path.
+pub(crate) const SENSITIVE_DIRS: &[&str] = &[
+    ".ssh",
+    ".gnupg",
+    ".aws",
+    ".azure",
+    ".kube",
+    ".docker",
+    ".config/gcloud",
+];
+
+/// File names the default sensitive patterns match, tried against globs.
+const SENSITIVE_NAMES: &[&str] = &[
+    ".env",
+    ".env.local",
+    ".env.production",
+    ".envrc",
+    "credentials",
+    "secrets",
+    "secrets.yaml",
+    ".netrc",
+    ".npmrc",
+    ".pypirc",
+    ".git-credentials",
+    ".vault-token",
+    "server.pem",
+    "server.key",
+    "id_rsa",
+    "id_ed25519",
+    "id_ecdsa",
+    "kubeconfig",
+    ".bash_history",
+    ".zsh_history",
+];
+
+/// Check if `path` is one of [`SENSITIVE_DIRS`].
+pub(crate) fn is_sensitive_dir(path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    SENSITIVE_DIRS.iter().any(|dir| {
+        path.strip_suffix(dir)
+            .is_some_and(|parent| parent.is_empty() || parent.ends_with('/'))
+    })
+}
+
+/// Check if a glob argument, such as `.en*`, `*.pem` or `~/.aws/*`, can
+/// expand to a sensitive file.
+///
+/// Only the last path component is matched. A glob with some literal text
+/// is tried against well-known sensitive file names; a bare wildcard only
+/// against a sensitive directory. With `expand_globs`, the names actually
+/// in the directory are tried too.
+pub fn check_sensitive_glob(pattern: &str, config: &CompiledConfig, cwd: Option<&str>) -> Decision {
+    let (dir, name) = match pattern.rsplit_once('/') {
+        Some((dir, name)) => (Some(dir), name),
+        None => (None, pattern),
+    };
+    if !is_glob(name) {
+        return Decision::allow();
+    }
+    let join = |file: &str| dir.map_or_else(|| file.to_string(), |dir| format!("{}/{}", dir, file));
+
+    let mut candidates: Vec<String> = Vec::new();
+    if config.raw.expand_globs {
+        candidates.extend(
+            directory_entries(dir.unwrap_or("."), cwd)
+                .into_iter()
+                .filter(|entry| glob_matches(name, entry))
+                .map(|entry| join(&entry)),
+        );
+    }
+    if has_literal(name) {
+        candidates.extend(
+            SENSITIVE_NAMES
+                .iter()
+                .filter(|file| glob_matches(name, file))
+                .map(|file| join(file)),
+        );
+    } else if let Some(dir) = dir.filter(|dir| is_sensitive_dir(dir)) {
+        return Decision::block(
+            "secrets.sensitive_file",
+            format!("glob '{}' covers sensitive directory '{}'", pattern, dir),
+        );
+    }
+
+    for candidate in candidates {
+        if let Some(matched) = config.is_sensitive_path(&candidate) {
+            let mut block = BlockInfo::new(
+                "secrets.sensitive_file",
+                format!(
+                    "glob '{}' can expand to sensitive file '{}' (matching '{}')",
+                    pattern, candidate, matched
+                ),
+            );
+            if matched.contains(r"\.env") {
+                block = block.with_details(ENV_TIP);
+            }
+            return Decision::Block(block);
+        }
+    }
+    Decision::allow()
+}
+
+/// The names in `dir`, relative to `cwd`, or none if it cannot be read.
+fn directory_entries(dir: &str, cwd: Option<&str>) -> Vec<String> {
+    let path = match dir.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => match dirs::home_dir() {
+            Some(home) => home.join(rest.trim_start_matches('/')),
+            None => return Vec::new(),
+        },
+        _ if dir.is_empty() => PathBuf::from("/"),
+        _ => Path::new(cwd.unwrap_or(".")).join(dir),
+    };
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check if `name` has an unescaped `*`, `?` or `[`.
+fn is_glob(name: &str) -> bool {
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' | '[' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Check if a glob has any characters outside its wildcards.
+fn has_literal(glob: &str) -> bool {
+    let mut in_class = false;
+    glob.chars().any(|c| match c {
+        '[' => {
+            in_class = true;
+            false
+        }
+        ']' if in_class => {
+            in_class = false;
+            false
+        }
+        '*' | '?' => false,
+        _ => !in_class,
+    })
+}
+
+/// Match a file name against a shell glob. As in the shell, a leading `.`
+/// must be matched literally.
+fn glob_matches(glob: &str, name: &str) -> bool {
+    if name.starts_with('.') && !glob.starts_with('.') {
+        return false;
+    }
+    let glob: Vec<char> = glob.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    match_from(&glob, &name)
+}
+
+fn match_from(glob: &[char], name: &[char]) -> bool {
+    match glob.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| match_from(rest, &name[i..])),
+        Some(('?', rest)) => !name.is_empty() && match_from(rest, &name[1..]),
+        Some(('[', rest)) => match (name.split_first(), class_end(rest)) {
+            (Some((c, name)), Some(end)) => {
+                in_class(&rest[..end], *c) && match_from(&rest[end + 1..], name)
+            }
+            (Some((c, name)), None) => *c == '[' && match_from(rest, name),
+            (None, _) => false,
+        },
+        Some(('\\', [escaped, rest @ ..])) => {
+            name.first() == Some(escaped) && match_from(rest, &name[1..])
+        }
+        Some((c, rest)) => name.first() == Some(c) && match_from(rest, &name[1..]),
+    }
+}
+
+/// The index of the `]` closing a bracket expression that starts at `class`.
+fn class_end(class: &[char]) -> Option<usize> {
+    let skip = usize::from(matches!(class.first(), Some('!' | '^')));
+    // A `]` right after the opening bracket is literal
+    (skip + 1..class.len()).find(|&i| class[i] == ']')
+}
+
+/// Check if `c` is in a bracket expression such as `a-z` or `!0-9`.
+fn in_class(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.split_first() {
+        Some(('!' | '^', rest)) => (true, rest),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,4 +418,54 @@ mod tests {
         let decision = check_git_add_sensitive(&[".env.test"], &config);
         assert!(decision.is_blocked());
     }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches(".en*", ".env"));
+        assert!(glob_matches("*.pem", "server.pem"));
+        assert!(glob_matches("id_[re]*", "id_rsa"));
+        assert!(glob_matches("id_[!x]?a", "id_rsa"));
+        assert!(!glob_matches("*", ".env"));
+        assert!(!glob_matches("*.pem", "server.pem.bak"));
+        assert!(!glob_matches("id_[!r]*", "id_rsa"));
+    }
+
+    #[test]
+    fn test_sensitive_globs_blocked() {
+        let config = Config::default().compile().unwrap();
+        for glob in [".en*", "*.pem", "config/.e?v", "~/.ssh/id_*", "~/.aws/*", "/root/.ssh/*"] {
+            let decision = check_sensitive_glob(glob, &config, None);
+            assert!(decision.is_blocked(), "{}", glob);
+            assert_eq!(decision.rule(), Some("secrets.sensitive_file"));
+        }
+    }
+
+    #[test]
+    fn test_other_globs_allowed() {
+        let config = Config::default().compile().unwrap();
+        for glob in ["*", "src/*", "*.rs", "*.json", "docs/*.md", ".env.example", "*.txt"] {
+            let decision = check_sensitive_glob(glob, &config, None);
+            assert!(!decision.is_blocked(), "{}", glob);
+        }
+    }
+
+    #[test]
+    fn test_globs_expanded_against_directory() {
+        let dir = std::env::temp_dir().join(format!("aca-glob-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("deploy.pem"), "").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        let cwd = dir.to_str();
+
+        let mut config = Config::default();
+        // `*` alone is only expanded against the directory when asked to
+        let compiled = config.clone().compile().unwrap();
+        assert!(!check_sensitive_glob("*", &compiled, cwd).is_blocked());
+        config.expand_globs = true;
+        let compiled = config.compile().unwrap();
+        assert!(check_sensitive_glob("*", &compiled, cwd).is_blocked());
+        assert!(!check_sensitive_glob("*.txt", &compiled, cwd).is_blocked());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
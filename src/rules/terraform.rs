@@ -0,0 +1,117 @@
+//! Terraform and OpenTofu analysis - asks before tearing down
+//! infrastructure or forgetting what state tracks.
+
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::Token;
+
+/// Analyze terraform (or tofu) commands for destructive operations.
+pub fn analyze_terraform(tokens: &[Token], _config: &CompiledConfig) -> Decision {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+    let Some((&cli, rest)) = words.split_first() else {
+        return Decision::allow();
+    };
+
+    // Global options such as -chdir=dir come before the subcommand, and
+    // options may be spelled with one dash or two
+    let (flags, args): (Vec<&str>, Vec<&str>) = rest.iter().partition(|w| w.starts_with('-'));
+    let has_flag = |name: &str| {
+        flags.iter().any(|f| {
+            let f = f.trim_start_matches('-');
+            f == name || f.strip_prefix(name).is_some_and(|v| v.starts_with('='))
+        })
+    };
+
+    match args.as_slice() {
+        ["destroy", ..] => Decision::ask(
+            "terraform.destroy",
+            format!("{} destroy tears down every resource in the state", cli),
+        ),
+        ["apply", ..] if has_flag("destroy") => Decision::ask(
+            "terraform.destroy",
+            format!(
+                "{} apply -destroy tears down every resource in the state",
+                cli
+            ),
+        ),
+        ["apply", ..] if has_flag("auto-approve") => Decision::ask(
+            "terraform.apply.auto_approve",
+            format!(
+                "{} apply -auto-approve changes infrastructure without showing the plan",
+                cli
+            ),
+        ),
+        ["state", "rm", ..] => Decision::ask(
+            "terraform.state.rm",
+            format!(
+                "{} state rm stops tracking resources, so they are orphaned or recreated",
+                cli
+            ),
+        ),
+        ["workspace", "delete", ..] => Decision::ask(
+            "terraform.workspace.delete",
+            format!("{} workspace delete discards the workspace's state", cli),
+        ),
+        _ => Decision::allow(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+
+    fn analyze(command: &str) -> Decision {
+        analyze_terraform(&tokenize(command), &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_destructive_commands_ask() {
+        for (command, rule) in [
+            ("terraform destroy", "terraform.destroy"),
+            ("terraform destroy -auto-approve", "terraform.destroy"),
+            ("terraform -chdir=infra destroy", "terraform.destroy"),
+            ("terraform apply -destroy", "terraform.destroy"),
+            ("tofu destroy --auto-approve", "terraform.destroy"),
+            (
+                "terraform apply -auto-approve",
+                "terraform.apply.auto_approve",
+            ),
+            (
+                "terraform apply -auto-approve=true tfplan",
+                "terraform.apply.auto_approve",
+            ),
+            ("terraform state rm aws_instance.web", "terraform.state.rm"),
+            (
+                "terraform workspace delete staging",
+                "terraform.workspace.delete",
+            ),
+        ] {
+            let decision = analyze(command);
+            assert!(decision.is_ask(), "{}", command);
+            assert_eq!(decision.rule(), Some(rule), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_routine_commands_allowed() {
+        for command in [
+            "terraform init",
+            "terraform plan",
+            "terraform plan -destroy",
+            "terraform apply tfplan",
+            "terraform state list",
+            "terraform workspace select staging",
+            "terraform fmt -recursive",
+        ] {
+            assert!(matches!(analyze(command), Decision::Allow), "{}", command);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! HashiCorp Vault CLI analysis - blocks commands that expose secrets or
+//! tokens.
+
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::Token;
+
+/// Analyze Vault CLI commands for secret exposure.
+pub fn analyze_vault(tokens: &[Token], _config: &CompiledConfig) -> Decision {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+    let args: Vec<&str> = words
+        .iter()
+        .skip(1)
+        .copied()
+        .filter(|w| !w.starts_with('-'))
+        .collect();
+    let flag_value = |name: &str| {
+        words.iter().find_map(|w| {
+            w.trim_start_matches('-')
+                .strip_prefix(name)
+                .and_then(|v| v.strip_prefix('='))
+        })
+    };
+
+    match args.as_slice() {
+        // KV secrets engine
+        ["kv", "get", ..] => Decision::block("vault.kv.get", "vault kv get exposes secret values")
+            .with_alternatives(["vault kv metadata get (metadata only)", "vault kv list"]),
+
+        // Generic reads return secrets and dynamic credentials; sys/ is
+        // Vault's own configuration
+        ["read", path, ..] if !path.starts_with("sys/") => Decision::block(
+            "vault.read",
+            format!("vault read {} exposes secret values or credentials", path),
+        )
+        .with_alternatives(["vault list (paths only)"]),
+
+        // Tokens
+        ["token", "create", ..] => Decision::block(
+            "vault.token.create",
+            "vault token create prints a new token",
+        ),
+        ["print", "token", ..] => Decision::block(
+            "vault.token.print",
+            "vault print token exposes the current token",
+        ),
+        ["login", credentials @ ..]
+            if flag_value("method").is_none_or(|m| m == "token") && !credentials.is_empty() =>
+        {
+            Decision::block(
+                "vault.login.token",
+                "vault login with a token on the command line exposes it",
+            )
+            .with_alternatives([
+                "run `vault login` without arguments so it prompts for the token",
+                "use an auth method such as -method=oidc",
+            ])
+        }
+
+        _ => Decision::allow(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+
+    fn analyze(command: &str) -> Decision {
+        analyze_vault(&tokenize(command), &Config::default().compile().unwrap())
+    }
+
+    #[test]
+    fn test_secret_exposure_blocked() {
+        for (command, rule) in [
+            ("vault kv get secret/app", "vault.kv.get"),
+            (
+                "vault kv get -mount=secret -field=password app",
+                "vault.kv.get",
+            ),
+            ("vault read secret/data/app", "vault.read"),
+            (
+                "vault read -format=json database/creds/readonly",
+                "vault.read",
+            ),
+            ("vault token create -policy=admin", "vault.token.create"),
+            ("vault print token", "vault.token.print"),
+            (
+                "vault login -method=token token=<token>",
+                "vault.login.token",
+            ),
+            ("vault login <token>", "vault.login.token"),
+        ] {
+            assert_eq!(analyze(command).rule(), Some(rule), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_metadata_allowed() {
+        for command in [
+            "vault status",
+            "vault kv list secret/",
+            "vault kv metadata get secret/app",
+            "vault read sys/mounts",
+            "vault token lookup",
+            "vault login",
+            "vault login -method=oidc role=dev",
+            "vault secrets list",
+        ] {
+            assert!(!analyze(command).is_blocked(), "{}", command);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Windows command analysis: cmd built-ins, PowerShell cmdlets and reg.exe.
+//!
+//! Command names and switches are case-insensitive on Windows. Paths reach
+//! here with forward slashes when they came through `cmd /c` or
+//! `powershell -Command` (see `strip_wrappers`), but both separators are
+//! accepted.
+
+use crate::config::CompiledConfig;
+use crate::decision::Decision;
+use crate::shell::Token;
+
+/// Commands that delete files or directories, as cmd built-ins or
+/// PowerShell aliases of `Remove-Item`.
+const DELETE_COMMANDS: &[&str] = &["del", "erase", "rd", "rmdir", "remove-item", "ri"];
+
+/// Directories directly under a drive root that must never be deleted.
+const SYSTEM_DIRS: &[&str] = &[
+    "windows",
+    "users",
+    "program files",
+    "program files (x86)",
+    "programdata",
+];
+
+/// Variables that expand to the user profile or a system directory.
+const PROFILE_VARS: &[&str] = &[
+    "%userprofile%",
+    "%homedrive%%homepath%",
+    "%homepath%",
+    "%systemroot%",
+    "%windir%",
+    "%systemdrive%",
+    "%programfiles%",
+    "$env:userprofile",
+    "$env:systemroot",
+    "$env:windir",
+    "$env:systemdrive",
+    "$home",
+    "~",
+];
+
+/// Registry hives holding password hashes and LSA secrets.
+const CREDENTIAL_HIVES: &[&str] = &["sam", "security", "system"];
+
+/// Lowercased command name without `.exe`.
+fn canonical(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    name.strip_suffix(".exe").unwrap_or(&name).to_string()
+}
+
+/// Check if `name` is a Windows command this module analyzes.
+pub fn is_windows_command(name: &str) -> bool {
+    let name = canonical(name);
+    name == "reg" || DELETE_COMMANDS.contains(&name.as_str())
+}
+
+/// Analyze a Windows delete or registry command.
+pub fn analyze_windows(tokens: &[Token], _config: &CompiledConfig) -> Decision {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let Some((name, args)) = words.split_first() else {
+        return Decision::allow();
+    };
+    match canonical(name).as_str() {
+        "reg" => analyze_reg(args),
+        _ => analyze_delete(name, args),
+    }
+}
+
+/// `del /s`, `rd /s` and `Remove-Item -Recurse` on a drive root, system
+/// directory or the user profile.
+fn analyze_delete(name: &str, args: &[&str]) -> Decision {
+    let recursive = args
+        .iter()
+        .any(|arg| has_cmd_switch(arg, 's') || is_ps_param(arg, "recurse"));
+    if !recursive {
+        return Decision::allow();
+    }
+
+    let targets = args
+        .iter()
+        .filter(|arg| !arg.starts_with('-') && !is_cmd_switches(arg));
+    for target in targets {
+        if is_dangerous_path(target) {
+            return Decision::block(
+                "windows.delete.dangerous_path",
+                format!("recursive {} on '{}' is blocked", name, target),
+            )
+            .with_alternatives(["ask the user to delete it themselves"]);
+        }
+        if target.starts_with("..") {
+            return Decision::block(
+                "windows.delete.parent_escape",
+                format!(
+                    "recursive {} with parent traversal '{}' is blocked",
+                    name, target
+                ),
+            )
+            .with_alternatives(["ask the user to delete it themselves"]);
+        }
+    }
+    Decision::allow()
+}
+
+/// `reg export` / `reg save` of the SAM, SECURITY or SYSTEM hive.
+fn analyze_reg(args: &[&str]) -> Decision {
+    let Some(operation) = args.first().map(|op| op.to_ascii_lowercase()) else {
+        return Decision::allow();
+    };
+    if !matches!(operation.as_str(), "export" | "save" | "copy") {
+        return Decision::allow();
+    }
+    match args.iter().find(|arg| is_credential_hive(arg)) {
+        Some(key) => Decision::block(
+            "windows.reg.credential_hive",
+            format!(
+                "reg {} of {} exposes password hashes and LSA secrets",
+                operation, key
+            ),
+        ),
+        None => Decision::allow(),
+    }
+}
+
+/// Check if `arg` is a run of cmd switches (`/s`, `/s/q`, `/a:h`).
+fn is_cmd_switches(arg: &str) -> bool {
+    arg.starts_with('/')
+        && arg[1..].split('/').all(|switch| {
+            let mut chars = switch.chars();
+            chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+                && (chars.as_str().is_empty() || chars.as_str().starts_with(':'))
+        })
+}
+
+/// Check if `arg` contains the cmd switch `/<letter>`.
+fn has_cmd_switch(arg: &str, letter: char) -> bool {
+    is_cmd_switches(arg)
+        && arg[1..].split('/').any(|switch| {
+            switch
+                .chars()
+                .next()
+                .is_some_and(|c| c.eq_ignore_ascii_case(&letter))
+        })
+}
+
+/// Check if `arg` is PowerShell parameter `name`, possibly abbreviated.
+fn is_ps_param(arg: &str, name: &str) -> bool {
+    arg.strip_prefix('-').is_some_and(|param| {
+        let param = param.to_ascii_lowercase();
+        let param = param.strip_suffix(":$true").unwrap_or(&param);
+        !param.is_empty() && name.starts_with(param)
+    })
+}
+
+/// Drive roots, top-level system directories and the user profile, in
+/// Windows (`C:\Users`) or Git Bash (`/c/Users`) form.
+fn is_dangerous_path(path: &str) -> bool {
+    let path = path.to_ascii_lowercase().replace('\\', "/");
+    let path = path.trim_end_matches(['*', '.']).trim_end_matches('/');
+
+    if PROFILE_VARS.contains(&path) {
+        return true;
+    }
+    // C: or /c, then optionally one system directory
+    let rest = match path.as_bytes() {
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => &path[2..],
+        [b'/', drive, ..]
+            if drive.is_ascii_alphabetic() && (path.len() == 2 || path.as_bytes()[2] == b'/') =>
+        {
+            &path[2..]
+        }
+        _ => return false,
+    };
+    let rest = rest.trim_start_matches('/');
+    rest.is_empty() || SYSTEM_DIRS.contains(&rest)
+}
+
+/// Check if a registry key names a credential hive (`HKLM\SAM`).
+fn is_credential_hive(key: &str) -> bool {
+    let key = key.to_ascii_lowercase().replace('\\', "/");
+    let Some(rest) = key
+        .strip_prefix("hklm")
+        .or_else(|| key.strip_prefix("hkey_local_machine"))
+    else {
+        return false;
+    };
+    let rest = rest.trim_start_matches('/');
+    let hive = rest.split('/').next().unwrap_or_default();
+    CREDENTIAL_HIVES.contains(&hive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::shell::tokenize;
+
+    fn test_config() -> CompiledConfig {
+        Config::default().compile().unwrap()
+    }
+
+    fn analyze(command: &str) -> Decision {
+        analyze_windows(&tokenize(command), &test_config())
+    }
+
+    #[test]
+    fn test_recursive_delete_of_system_paths() {
+        for command in [
+            "del /s /q C:/",
+            "DEL /S/Q C:/Windows",
+            "rd /s /q C:/Users",
+            "rmdir /s %USERPROFILE%",
+            "Remove-Item -Recurse -Force C:/",
+            "ri -r -fo $env:USERPROFILE",
+            "remove-item -Path /c/Users -Recurse",
+            "del /s /q C:/*",
+        ] {
+            let decision = analyze(command);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some("windows.delete.dangerous_path"));
+        }
+        assert_eq!(
+            analyze("rd /s /q ../other").rule(),
+            Some("windows.delete.parent_escape")
+        );
+    }
+
+    #[test]
+    fn test_ordinary_deletes_allowed() {
+        for command in [
+            "del /s /q build",
+            "rd /s /q C:/Users/me/project/target",
+            "del C:/Windows",
+            "Remove-Item -Force C:/",
+            "Remove-Item -Recurse node_modules",
+            "rmdir empty-dir",
+        ] {
+            assert!(!analyze(command).is_blocked(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_reg_export_of_credential_hives() {
+        for command in [
+            "reg save HKLM/SAM sam.save",
+            r"reg.exe export 'HKLM\SECURITY' sec.reg",
+            "REG SAVE HKEY_LOCAL_MACHINE/SYSTEM sys.hiv",
+        ] {
+            let decision = analyze(command);
+            assert!(decision.is_blocked(), "{}", command);
+            assert_eq!(decision.rule(), Some("windows.reg.credential_hive"));
+        }
+        assert!(!analyze("reg export HKCU/Software/MyApp app.reg").is_blocked());
+        assert!(!analyze("reg query HKLM/SAM").is_blocked());
+    }
+
+    #[test]
+    fn test_is_windows_command() {
+        assert!(is_windows_command("Remove-Item"));
+        assert!(is_windows_command("REG.EXE"));
+        assert!(!is_windows_command("rm"));
+    }
+}
@@ -0,0 +1,81 @@
+//! Workaround detection - commands typical of getting around a block.
+//!
+//! None of these are dangerous on their own; they only count towards a
+//! session lockdown when they follow a block.
+
+use crate::shell::{Token, expand_commands, strip_wrappers, tokenize};
+
+/// Programs that re-encode data, the usual way to sneak a file past a filter.
+const ENCODERS: &[&str] = &["base64", "base32", "xxd", "od", "uuencode", "basenc"];
+
+/// Interpreters and the flag that runs inline code.
+const INLINE_INTERPRETERS: &[(&str, &[&str])] = &[
+    ("python", &["-c"]),
+    ("python2", &["-c"]),
+    ("python3", &["-c"]),
+    ("node", &["-e", "-p", "--eval", "--print"]),
+    ("perl", &["-e", "-E"]),
+    ("ruby", &["-e"]),
+    ("php", &["-r"]),
+    ("deno", &["eval"]),
+];
+
+/// Check if a command encodes data or runs inline interpreter code.
+pub fn is_workaround_attempt(command: &str) -> bool {
+    expand_commands(command).iter().any(|segment| {
+        let stripped = strip_wrappers(&segment.command);
+        let words: Vec<String> = tokenize(&stripped)
+            .into_iter()
+            .filter_map(|t| match t {
+                Token::Word(w) => Some(w),
+                _ => None,
+            })
+            .collect();
+        let Some(name) = words.first().map(|w| w.rsplit('/').next().unwrap_or(w)) else {
+            return false;
+        };
+
+        if ENCODERS.contains(&name) {
+            return true;
+        }
+        if name == "openssl" {
+            return words
+                .get(1)
+                .is_some_and(|sub| matches!(sub.as_str(), "enc" | "base64"));
+        }
+        INLINE_INTERPRETERS
+            .iter()
+            .find(|(interpreter, _)| *interpreter == name)
+            .is_some_and(|(_, flags)| words[1..].iter().any(|w| flags.contains(&w.as_str())))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoders() {
+        assert!(is_workaround_attempt("base64 .env"));
+        assert!(is_workaround_attempt("cat notes.txt | xxd"));
+        assert!(is_workaround_attempt("openssl base64 -in secrets.json"));
+        assert!(!is_workaround_attempt("openssl version"));
+    }
+
+    #[test]
+    fn test_inline_interpreters() {
+        assert!(is_workaround_attempt(
+            r#"python3 -c "print(open('.env').read())""#
+        ));
+        assert!(is_workaround_attempt("/usr/bin/node -e 'x'"));
+        assert!(is_workaround_attempt("sudo perl -e 'print 1'"));
+        assert!(!is_workaround_attempt("python3 manage.py test"));
+        assert!(!is_workaround_attempt("node server.js"));
+    }
+
+    #[test]
+    fn test_ordinary_commands() {
+        assert!(!is_workaround_attempt("ls -la"));
+        assert!(!is_workaround_attempt("cargo test"));
+    }
+}
@@ -0,0 +1,405 @@
+//! Secret detection rules imported from gitleaks config files.
+//!
+//! A gitleaks file (`[[rules]]` with `id`, `regex`, `secretGroup`,
+//! `entropy`, `keywords` and allowlists) is converted into [`SecretRule`]s.
+//! As in gitleaks, a rule's regex is only compiled and run when the text
+//! contains one of its keywords, so large rule files stay cheap per call.
+//! The converted rules are cached by file content, so they are only parsed
+//! again after the file changes. Rules restricted to file paths (`path`)
+//! are skipped, since commands and hook output have no path to match.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::{ConfigError, hex};
+use crate::config_cache::ConfigCache;
+
+/// Replacement for a secret found by an imported rule.
+const REDACTED: &str = "<REDACTED>";
+
+/// One secret detector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretRule {
+    /// Rule id from the ruleset (e.g. `aws-access-token`).
+    pub id: String,
+    /// What the rule detects.
+    pub description: String,
+    pattern: String,
+    #[serde(skip)]
+    regex: OnceCell<Option<Regex>>,
+    /// Capture group holding the secret; 0 picks the only group, if any.
+    secret_group: usize,
+    /// Lowercase words one of which must appear in the text.
+    keywords: Vec<String>,
+    /// Minimum Shannon entropy of the secret, in bits per character.
+    entropy: Option<f64>,
+    /// Secrets matching one of these are placeholders, not secrets.
+    allowlist: Vec<String>,
+    #[serde(skip)]
+    allowlist_regexes: OnceCell<Vec<Regex>>,
+    /// Lowercase words that mark a secret as a placeholder.
+    stopwords: Vec<String>,
+}
+
+/// A secret found in some text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretMatch<'a> {
+    /// The rule that found it.
+    pub rule: &'a str,
+    /// What the rule detects.
+    pub description: &'a str,
+}
+
+impl SecretRule {
+    /// The rule's regex, or `None` if it is not valid Rust regex syntax.
+    fn regex(&self) -> Option<&Regex> {
+        self.regex
+            .get_or_init(|| {
+                Regex::new(&self.pattern)
+                    .inspect_err(|e| {
+                        tracing::warn!(rule = %self.id, error = %e, "skipping secret rule");
+                    })
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    /// Byte ranges of the secrets this rule finds in `text`.
+    fn find_secrets(&self, text: &str) -> Vec<(usize, usize)> {
+        if !self.keywords.is_empty() {
+            let lower = text.to_lowercase();
+            if !self.keywords.iter().any(|keyword| lower.contains(keyword)) {
+                return Vec::new();
+            }
+        }
+        let Some(regex) = self.regex() else {
+            return Vec::new();
+        };
+        let group = match self.secret_group {
+            0 if regex.captures_len() == 2 => 1,
+            group => group,
+        };
+        regex
+            .captures_iter(text)
+            .filter_map(|captures| captures.get(group).or_else(|| captures.get(0)))
+            .filter(|secret| self.is_secret(secret.as_str()))
+            .map(|secret| (secret.start(), secret.end()))
+            .collect()
+    }
+
+    /// Apply the entropy threshold and allowlists to a candidate secret.
+    fn is_secret(&self, secret: &str) -> bool {
+        if secret.is_empty() {
+            return false;
+        }
+        if let Some(min) = self.entropy
+            && shannon_entropy(secret) < min
+        {
+            return false;
+        }
+        let lower = secret.to_lowercase();
+        !self.stopwords.iter().any(|word| lower.contains(word))
+            && !self
+                .allowlist_regexes()
+                .iter()
+                .any(|re| re.is_match(secret))
+    }
+
+    /// The allowlist, compiled on first use.
+    fn allowlist_regexes(&self) -> &[Regex] {
+        self.allowlist_regexes.get_or_init(|| {
+            self.allowlist
+                .iter()
+                .filter_map(|re| Regex::new(re).ok())
+                .collect()
+        })
+    }
+}
+
+/// Find the first secret any of `rules` detects in `text`.
+pub fn find_secret<'a>(rules: &'a [SecretRule], text: &str) -> Option<SecretMatch<'a>> {
+    rules
+        .iter()
+        .find(|rule| !rule.find_secrets(text).is_empty())
+        .map(|rule| SecretMatch {
+            rule: &rule.id,
+            description: &rule.description,
+        })
+}
+
+/// Replace every secret `rules` detect in `text` with `<REDACTED>`.
+pub fn redact(rules: &[SecretRule], text: &str) -> String {
+    let mut ranges: Vec<(usize, usize)> = rules
+        .iter()
+        .flat_map(|rule| rule.find_secrets(text))
+        .collect();
+    if ranges.is_empty() {
+        return text.to_string();
+    }
+    ranges.sort();
+
+    let mut result = String::with_capacity(text.len());
+    let mut pos = 0;
+    for (start, end) in ranges {
+        // Overlapping finds are redacted once
+        if end <= pos {
+            continue;
+        }
+        result.push_str(&text[pos..start.max(pos)]);
+        result.push_str(REDACTED);
+        pos = end;
+    }
+    result.push_str(&text[pos..]);
+    result
+}
+
+/// Shannon entropy of `s` in bits per character.
+pub(crate) fn shannon_entropy(s: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in s.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len = s.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// A gitleaks config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GitleaksFile {
+    rules: Vec<GitleaksRule>,
+    allowlist: Option<GitleaksAllowlist>,
+    allowlists: Vec<GitleaksAllowlist>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GitleaksRule {
+    id: String,
+    description: String,
+    regex: Option<String>,
+    path: Option<String>,
+    secret_group: usize,
+    entropy: Option<f64>,
+    keywords: Vec<String>,
+    allowlist: Option<GitleaksAllowlist>,
+    allowlists: Vec<GitleaksAllowlist>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GitleaksAllowlist {
+    regexes: Vec<String>,
+    stopwords: Vec<String>,
+}
+
+/// Load the rules of a gitleaks config file, from the cache if it holds
+/// the file's current content.
+///
+/// Allowlist regexes that do not compile are dropped; a rule whose own
+/// regex does not compile is skipped when first used.
+pub fn load_gitleaks(path: &Path) -> Result<Vec<SecretRule>, ConfigError> {
+    let content = fs::read_to_string(path)?;
+    let cache = ConfigCache::open(&gitleaks_cache_key(&content));
+    load_gitleaks_cached(&content, cache.as_ref())
+}
+
+fn load_gitleaks_cached(
+    content: &str,
+    cache: Option<&ConfigCache>,
+) -> Result<Vec<SecretRule>, ConfigError> {
+    if let Some(rules) = cache.and_then(ConfigCache::get::<Vec<SecretRule>>) {
+        tracing::debug!("secret rules cache hit");
+        return Ok(rules);
+    }
+    let rules = parse_gitleaks(content)?;
+    if let Some(cache) = cache {
+        cache.put(&rules);
+    }
+    Ok(rules)
+}
+
+/// Cache key for the rules converted from `content` by this binary.
+fn gitleaks_cache_key(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update([0]);
+    hasher.update(content);
+    format!("gitleaks-{}", hex(&hasher.finalize()))
+}
+
+/// Convert the rules of a gitleaks config file.
+fn parse_gitleaks(content: &str) -> Result<Vec<SecretRule>, ConfigError> {
+    let file: GitleaksFile = toml::from_str(content)?;
+
+    let global: Vec<&GitleaksAllowlist> = file.allowlist.iter().chain(&file.allowlists).collect();
+    let rules = file
+        .rules
+        .iter()
+        .filter_map(|rule| {
+            let Some(pattern) = &rule.regex else {
+                tracing::debug!(rule = %rule.id, "skipping secret rule without regex");
+                return None;
+            };
+            if rule.path.is_some() {
+                tracing::debug!(rule = %rule.id, "skipping path-scoped secret rule");
+                return None;
+            }
+            let allowlists: Vec<&GitleaksAllowlist> = global
+                .iter()
+                .copied()
+                .chain(rule.allowlist.iter())
+                .chain(&rule.allowlists)
+                .collect();
+            Some(SecretRule {
+                id: rule.id.clone(),
+                description: rule.description.clone(),
+                pattern: pattern.clone(),
+                regex: OnceCell::new(),
+                secret_group: rule.secret_group,
+                keywords: rule.keywords.iter().map(|k| k.to_lowercase()).collect(),
+                entropy: rule.entropy,
+                allowlist: allowlists
+                    .iter()
+                    .flat_map(|list| &list.regexes)
+                    .filter(|re| Regex::new(re).is_ok())
+                    .cloned()
+                    .collect(),
+                allowlist_regexes: OnceCell::new(),
+                stopwords: allowlists
+                    .iter()
+                    .flat_map(|list| &list.stopwords)
+                    .map(|word| word.to_lowercase())
+                    .collect(),
+            })
+        })
+        .collect();
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const GITLEAKS: &str = r#"
+title = "org rules"
+
+[allowlist]
+stopwords = ["example"]
+
+[[rules]]
+id = "acme-api-key"
+description = "Acme API key"
+regex = '''(?i)acme[_-]?key\s*[:=]\s*['"]?([a-z0-9]{24})'''
+keywords = ["acme"]
+entropy = 3.0
+
+[[rules]]
+id = "internal-token"
+description = "Internal service token"
+regex = '''\b(itk)_([A-Za-z0-9]{20})\b'''
+secretGroup = 2
+[rules.allowlist]
+regexes = ['''^0+$''']
+
+[[rules]]
+id = "pkcs12-file"
+description = "PKCS12 file"
+path = '''\.p12$'''
+"#;
+
+    fn rules() -> Vec<SecretRule> {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("gitleaks.toml");
+        fs::write(&path, GITLEAKS).unwrap();
+        load_gitleaks(&path).unwrap()
+    }
+
+    #[test]
+    fn test_load_skips_path_rules() {
+        let rules = rules();
+        let ids: Vec<&str> = rules.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["acme-api-key", "internal-token"]);
+    }
+
+    #[test]
+    fn test_find_secret() {
+        let rules = rules();
+        let found = find_secret(&rules, "export ACME_KEY=q8z3k1m9x7w2v5b4n6c0r1t2").unwrap();
+        assert_eq!(found.rule, "acme-api-key");
+        assert_eq!(found.description, "Acme API key");
+        assert_eq!(
+            find_secret(&rules, "curl -H 'X: itk_Ab3dEf6hIj9kLm2nOp5q'").map(|m| m.rule),
+            Some("internal-token")
+        );
+        assert!(find_secret(&rules, "ls -la").is_none());
+    }
+
+    #[test]
+    fn test_entropy_and_allowlists() {
+        let rules = rules();
+        // Too little entropy
+        assert!(find_secret(&rules, "acme_key=aaaaaaaaaaaaaaaaaaaaaaaa").is_none());
+        // Global stopword
+        assert!(find_secret(&rules, "acme_key=example0example0example0").is_none());
+        // Rule allowlist on the secret group
+        assert!(find_secret(&rules, "itk_00000000000000000000").is_none());
+    }
+
+    #[test]
+    fn test_redact_secret_group_only() {
+        let rules = rules();
+        assert_eq!(
+            redact(&rules, "token itk_Ab3dEf6hIj9kLm2nOp5q sent"),
+            "token itk_<REDACTED> sent"
+        );
+        assert_eq!(redact(&rules, "nothing here"), "nothing here");
+    }
+
+    #[test]
+    fn test_invalid_regex_is_skipped() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("gitleaks.toml");
+        fs::write(&path, "[[rules]]\nid = \"bad\"\nregex = '''(?<=x)y'''\n").unwrap();
+        let rules = load_gitleaks(&path).unwrap();
+        assert!(find_secret(&rules, "xy").is_none());
+    }
+
+    #[test]
+    fn test_cached_rules_match_parsed() {
+        let dir = TempDir::new().unwrap();
+        let cache = ConfigCache::in_dir(dir.path().to_path_buf(), &gitleaks_cache_key(GITLEAKS));
+        let parsed = load_gitleaks_cached(GITLEAKS, Some(&cache)).unwrap();
+        let cached = load_gitleaks_cached("not toml", Some(&cache)).unwrap();
+        assert_eq!(cached.len(), parsed.len());
+        assert_eq!(
+            find_secret(&cached, "itk_Ab3dEf6hIj9kLm2nOp5q").map(|m| m.rule),
+            Some("internal-token")
+        );
+        // The allowlist survives the round trip
+        assert!(find_secret(&cached, "itk_00000000000000000000").is_none());
+        assert_ne!(
+            gitleaks_cache_key(GITLEAKS),
+            gitleaks_cache_key("[[rules]]")
+        );
+    }
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert!((shannon_entropy("abcd") - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,644 @@
+//! Per-session state persisted between hook invocations.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::config::{ApprovalScope, CompiledConfig, LockdownConfig};
+use crate::decision::{AskInfo, BlockInfo, Decision};
+use crate::input::HookInput;
+use crate::rules::is_workaround_attempt;
+
+/// Errors that can occur when saving session state.
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("failed to write session state: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to serialize session state: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// State remembered between hook invocations.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// Approved operations by approval key, with the time of approval.
+    pub approvals: HashMap<String, DateTime<Utc>>,
+    /// Decision counts for the session summary.
+    pub stats: SessionStats,
+    /// Times block notifications were sent, for rate limiting.
+    pub notifications: Vec<DateTime<Utc>>,
+    /// Block counts by rule, for escalation.
+    pub blocks_by_rule: HashMap<String, u32>,
+    /// Rules that already escalated (each escalates once).
+    pub escalated_rules: BTreeSet<String>,
+    /// Whether the session was switched to the stricter policy.
+    pub strict: bool,
+    /// Workaround tracking for lockdown.
+    pub lockdown: LockdownState,
+}
+
+/// Workaround attempts since the last block.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockdownState {
+    /// Time of the most recent block.
+    pub last_block_at: Option<DateTime<Utc>>,
+    /// File name the most recent block protected, if it was a file tool.
+    pub last_block_target: Option<String>,
+    /// Workaround attempts since that block.
+    pub attempts: u32,
+    /// Whether every tool call is blocked until `aca-safety-net unlock`.
+    pub locked: bool,
+}
+
+impl LockdownState {
+    /// Track a decision; returns true if the session just became locked.
+    ///
+    /// After a block, each call within the window that is itself blocked,
+    /// encodes data or runs inline code, or mentions the blocked file counts
+    /// as an attempt.
+    pub fn record(
+        &mut self,
+        input: &HookInput,
+        decision: &Decision,
+        now: DateTime<Utc>,
+        config: &LockdownConfig,
+    ) -> bool {
+        let window = i64::try_from(config.window_minutes)
+            .ok()
+            .and_then(Duration::try_minutes)
+            .unwrap_or(Duration::MAX);
+        let in_window = self.last_block_at.is_some_and(|at| now - at <= window);
+        if !in_window {
+            self.attempts = 0;
+        }
+
+        if in_window && self.is_attempt(input, decision) {
+            self.attempts += 1;
+        }
+        if decision.is_blocked() {
+            self.last_block_at = Some(now);
+            self.last_block_target = input
+                .file_path()
+                .and_then(|path| Path::new(path).file_name())
+                .map(|name| name.to_string_lossy().into_owned());
+        }
+
+        if !self.locked && in_window && self.attempts >= config.threshold {
+            self.locked = true;
+            return true;
+        }
+        false
+    }
+
+    fn is_attempt(&self, input: &HookInput, decision: &Decision) -> bool {
+        let mentions_target = self.last_block_target.as_ref().is_some_and(|target| {
+            input.command().is_some_and(|c| c.contains(target.as_str()))
+                || input
+                    .file_path()
+                    .is_some_and(|p| p.contains(target.as_str()))
+        });
+        decision.is_blocked()
+            || mentions_target
+            || input.command().is_some_and(is_workaround_attempt)
+    }
+}
+
+/// Decisions made during one session.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionStats {
+    /// Number of tool calls checked.
+    pub total: u64,
+    /// Number of blocked tool calls.
+    pub blocked: u64,
+    /// Number of tool calls that needed approval.
+    pub asked: u64,
+    /// Number of tool calls allowed with a warning.
+    pub warned: u64,
+    /// Number of tool calls rewritten to a safer form.
+    pub rewritten: u64,
+    /// Time of the first checked tool call.
+    pub first_seen: Option<DateTime<Utc>>,
+    /// Time of the last checked tool call.
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Distinct rules that triggered, sorted.
+    pub rules: BTreeSet<String>,
+    /// How often each rule triggered.
+    pub rule_counts: BTreeMap<String, u64>,
+}
+
+impl SessionStats {
+    /// Count a decision.
+    pub fn record(&mut self, decision: &Decision, now: DateTime<Utc>) {
+        self.total += 1;
+        match decision {
+            Decision::Allow => {}
+            Decision::Block(_) => self.blocked += 1,
+            Decision::Ask(_) => self.asked += 1,
+            Decision::Warn(_) => self.warned += 1,
+            Decision::Rewrite(_) => self.rewritten += 1,
+        }
+        if let Some(rule) = decision.rule() {
+            self.rules.insert(rule.to_string());
+            *self.rule_counts.entry(rule.to_string()).or_default() += 1;
+        }
+        self.first_seen.get_or_insert(now);
+        self.last_seen = Some(now);
+    }
+}
+
+impl SessionState {
+    /// Check if an approval exists and has not expired.
+    pub fn is_approved(&self, key: &str, ttl: Option<Duration>, now: DateTime<Utc>) -> bool {
+        self.approvals
+            .get(key)
+            .is_some_and(|approved_at| ttl.is_none_or(|ttl| now - *approved_at < ttl))
+    }
+
+    /// Record an approval.
+    pub fn approve(&mut self, key: impl Into<String>, now: DateTime<Utc>) {
+        self.approvals.insert(key.into(), now);
+    }
+}
+
+/// File-backed session state, one JSON file per state key.
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    /// Create a store rooted at `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Open the store configured in `config`, falling back to the platform state directory.
+    pub fn from_config(config: &CompiledConfig) -> Option<Self> {
+        let dir = match &config.raw.session.state_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::state_dir()
+                .or_else(dirs::data_local_dir)?
+                .join("aca-safety-net/sessions"),
+        };
+        Some(Self::new(dir))
+    }
+
+    /// Load state for `key`; missing or unreadable state starts empty.
+    pub fn load(&self, key: &str) -> SessionState {
+        fs::read_to_string(self.path(key))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save state for `key`, replacing the file atomically.
+    pub fn save(&self, key: &str, state: &SessionState) -> Result<(), SessionError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(state)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", sanitize_key(key)))
+    }
+}
+
+/// Make a state key safe to use as a file name.
+fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Stable 64-bit FNV-1a hash, used to name per-project state files.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The state key for remembered approvals, or `None` if the scope is unknown.
+fn approval_state_key(input: &HookInput, config: &CompiledConfig) -> Option<String> {
+    match config.raw.approvals.scope {
+        ApprovalScope::Session => input.session_id.clone(),
+        ApprovalScope::Project => {
+            let cwd = input.cwd.as_deref()?;
+            Some(format!("project-{:016x}", fnv1a(cwd)))
+        }
+    }
+}
+
+/// Stable key identifying what an ask decision was about.
+pub fn approval_key(input: &HookInput, info: &AskInfo) -> String {
+    let target = input
+        .file_path()
+        .or_else(|| input.command())
+        .unwrap_or_default();
+    format!("{}:{}", info.rule, target)
+}
+
+/// Apply remembered approvals to a PreToolUse decision.
+///
+/// An ask the user already approved becomes an allow; any other ask gets its
+/// approval key attached.
+pub fn apply_remembered_approval(
+    input: &HookInput,
+    decision: Decision,
+    config: &CompiledConfig,
+) -> Decision {
+    let approvals = &config.raw.approvals;
+    let Decision::Ask(info) = decision else {
+        return decision;
+    };
+    if !approvals.remember {
+        return Decision::Ask(info);
+    }
+
+    let key = approval_key(input, &info);
+    let ttl = (approvals.ttl_minutes > 0).then(|| Duration::minutes(approvals.ttl_minutes as i64));
+    if let (Some(store), Some(state_key)) = (
+        SessionStore::from_config(config),
+        approval_state_key(input, config),
+    ) && store.load(&state_key).is_approved(&key, ttl, Utc::now())
+    {
+        return Decision::allow();
+    }
+
+    Decision::Ask(info.with_approval_key(key))
+}
+
+/// Remember the approval behind a PostToolUse event.
+///
+/// Claude Code only runs a tool after the user approves the ask, so an ask
+/// decision for a tool that ran means the user said yes.
+pub fn record_approval(
+    input: &HookInput,
+    decision: &Decision,
+    config: &CompiledConfig,
+) -> Result<(), SessionError> {
+    let Decision::Ask(info) = decision else {
+        return Ok(());
+    };
+    if !config.raw.approvals.remember {
+        return Ok(());
+    }
+    let (Some(store), Some(state_key)) = (
+        SessionStore::from_config(config),
+        approval_state_key(input, config),
+    ) else {
+        return Ok(());
+    };
+
+    let mut state = store.load(&state_key);
+    state.approve(approval_key(input, info), Utc::now());
+    store.save(&state_key, &state)
+}
+
+/// A rule that crossed the escalation threshold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Escalation {
+    /// The rule that kept blocking.
+    pub rule: String,
+    /// How often it blocked in this session.
+    pub count: u32,
+    /// Whether the session switched to the stricter policy.
+    pub strict: bool,
+}
+
+/// Count a block and report an escalation when its rule crosses the threshold.
+///
+/// Repeated blocks of one rule suggest prompt injection or an agent trying
+/// workarounds, so each rule escalates once per session.
+pub fn track_escalation(
+    input: &HookInput,
+    decision: &Decision,
+    config: &CompiledConfig,
+) -> Result<Option<Escalation>, SessionError> {
+    let escalation = &config.raw.escalation;
+    let Decision::Block(info) = decision else {
+        return Ok(None);
+    };
+    if escalation.threshold == 0 {
+        return Ok(None);
+    }
+    let (Some(store), Some(session_id)) = (SessionStore::from_config(config), &input.session_id)
+    else {
+        return Ok(None);
+    };
+
+    let mut state = store.load(session_id);
+    let count = state.blocks_by_rule.entry(info.rule.clone()).or_default();
+    *count += 1;
+    let count = *count;
+    let escalated = count > escalation.threshold && state.escalated_rules.insert(info.rule.clone());
+    if escalated && escalation.strict {
+        state.strict = true;
+    }
+    store.save(session_id, &state)?;
+
+    Ok(escalated.then(|| Escalation {
+        rule: info.rule.clone(),
+        count,
+        strict: state.strict,
+    }))
+}
+
+/// Apply the stricter policy to sessions that escalated.
+///
+/// Asks, warnings and rewrites become blocks; allows and blocks are unchanged.
+pub fn apply_session_policy(
+    input: &HookInput,
+    decision: Decision,
+    config: &CompiledConfig,
+) -> Decision {
+    if matches!(decision, Decision::Allow | Decision::Block(_)) || !config.raw.escalation.strict {
+        return decision;
+    }
+    let (Some(store), Some(session_id)) = (SessionStore::from_config(config), &input.session_id)
+    else {
+        return decision;
+    };
+    if !store.load(session_id).strict {
+        return decision;
+    }
+    let rule = decision.rule().unwrap_or_default();
+    Decision::block(
+        "escalation.strict",
+        format!(
+            "{} (this session hit the escalation threshold, so it is no longer asked or allowed)",
+            rule
+        ),
+    )
+}
+
+/// Block everything in a locked session, and lock sessions that keep
+/// trying to work around blocks.
+pub fn apply_lockdown(input: &HookInput, decision: Decision, config: &CompiledConfig) -> Decision {
+    let lockdown = &config.raw.lockdown;
+    if !lockdown.enabled {
+        return decision;
+    }
+    let (Some(store), Some(session_id)) = (SessionStore::from_config(config), &input.session_id)
+    else {
+        return decision;
+    };
+
+    let mut state = store.load(session_id);
+    if !state.lockdown.locked {
+        if !state
+            .lockdown
+            .record(input, &decision, Utc::now(), lockdown)
+        {
+            let _ = store.save(session_id, &state);
+            return decision;
+        }
+        let _ = store.save(session_id, &state);
+    }
+
+    Decision::Block(
+        BlockInfo::new(
+            "lockdown.session",
+            "session locked after repeated attempts to work around blocked operations",
+        )
+        .with_model_reason(
+            "This session is locked. Stop and ask the user to review what happened; \
+             do not try other ways to run this.",
+        )
+        .with_user_reason(format!(
+            "Claude kept trying to get around blocked operations, so every tool call in this \
+             session is blocked. Review the session, then run `aca-safety-net unlock {}`.",
+            session_id
+        )),
+    )
+}
+
+/// Lift a lockdown; returns false if the session was not locked.
+pub fn unlock_session(store: &SessionStore, session_id: &str) -> Result<bool, SessionError> {
+    let mut state = store.load(session_id);
+    let was_locked = state.lockdown.locked;
+    state.lockdown = LockdownState::default();
+    store.save(session_id, &state)?;
+    Ok(was_locked)
+}
+
+/// Count a PreToolUse decision towards the session summary.
+pub fn record_decision(
+    input: &HookInput,
+    decision: &Decision,
+    config: &CompiledConfig,
+) -> Result<(), SessionError> {
+    let (Some(store), Some(session_id)) = (SessionStore::from_config(config), &input.session_id)
+    else {
+        return Ok(());
+    };
+    let mut state = store.load(session_id);
+    state.stats.record(decision, Utc::now());
+    store.save(session_id, &state)
+}
+
+/// Decisions recorded so far for the input's session.
+pub fn session_stats(input: &HookInput, config: &CompiledConfig) -> Option<SessionStats> {
+    let store = SessionStore::from_config(config)?;
+    Some(store.load(input.session_id.as_deref()?).stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use tempfile::TempDir;
+
+    fn remembering_config(dir: &TempDir) -> CompiledConfig {
+        let mut config = Config::default();
+        config.approvals.remember = true;
+        config.session.state_dir = Some(dir.path().to_string_lossy().to_string());
+        config.compile().unwrap()
+    }
+
+    fn edit_input(event: &str) -> HookInput {
+        HookInput::parse(&format!(
+            r#"{{"tool_name":"Edit","hook_event_name":"{}","session_id":"abc-123","tool_input":{{"file_path":"Cargo.toml","old_string":"a","new_string":"b"}}}}"#,
+            event
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_approval_expires_after_ttl() {
+        let mut state = SessionState::default();
+        let now = Utc::now();
+        state.approve("k", now - Duration::minutes(10));
+        assert!(state.is_approved("k", None, now));
+        assert!(state.is_approved("k", Some(Duration::minutes(30)), now));
+        assert!(!state.is_approved("k", Some(Duration::minutes(5)), now));
+        assert!(!state.is_approved("other", None, now));
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::new(dir.path());
+        let mut state = store.load("../escape");
+        assert!(state.approvals.is_empty());
+        state.approve("k", Utc::now());
+        store.save("../escape", &state).unwrap();
+        assert!(dir.path().join("___escape.json").exists());
+        assert!(store.load("../escape").approvals.contains_key("k"));
+    }
+
+    #[test]
+    fn test_ask_remembered_after_post_tool_use() {
+        let dir = TempDir::new().unwrap();
+        let config = remembering_config(&dir);
+        let ask = Decision::ask("deps.edit", "editing dependency file");
+
+        let first = apply_remembered_approval(&edit_input("PreToolUse"), ask.clone(), &config);
+        assert_eq!(
+            first.ask_info().unwrap().approval_key.as_deref(),
+            Some("deps.edit:Cargo.toml")
+        );
+
+        record_approval(&edit_input("PostToolUse"), &ask, &config).unwrap();
+        let second = apply_remembered_approval(&edit_input("PreToolUse"), ask, &config);
+        assert!(!second.is_ask() && !second.is_blocked());
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let dir = TempDir::new().unwrap();
+        let config = remembering_config(&dir);
+        let input = edit_input("PreToolUse");
+
+        record_decision(&input, &Decision::allow(), &config).unwrap();
+        record_decision(&input, &Decision::block("git.reset", "r"), &config).unwrap();
+        record_decision(&input, &Decision::ask("deps.edit", "r"), &config).unwrap();
+        record_decision(&input, &Decision::block("git.reset", "r"), &config).unwrap();
+
+        let stats = session_stats(&input, &config).unwrap();
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.blocked, 2);
+        assert_eq!(stats.asked, 1);
+        assert_eq!(
+            stats.rules.into_iter().collect::<Vec<_>>(),
+            vec!["deps.edit", "git.reset"]
+        );
+        assert_eq!(stats.rule_counts["git.reset"], 2);
+        assert!(stats.first_seen <= stats.last_seen);
+    }
+
+    #[test]
+    fn test_escalation_after_threshold() {
+        let dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.session.state_dir = Some(dir.path().to_string_lossy().to_string());
+        config.escalation.threshold = 2;
+        config.escalation.strict = true;
+        let config = config.compile().unwrap();
+        let input = edit_input("PreToolUse");
+        let block = Decision::block("sensitive.read", "r");
+        let ask = Decision::ask("deps.edit", "r");
+
+        assert_eq!(track_escalation(&input, &block, &config).unwrap(), None);
+        assert_eq!(track_escalation(&input, &block, &config).unwrap(), None);
+        assert!(apply_session_policy(&input, ask.clone(), &config).is_ask());
+
+        let escalation = track_escalation(&input, &block, &config).unwrap().unwrap();
+        assert_eq!(escalation.rule, "sensitive.read");
+        assert_eq!(escalation.count, 3);
+        assert!(escalation.strict);
+        // Each rule escalates once
+        assert_eq!(track_escalation(&input, &block, &config).unwrap(), None);
+
+        let strict = apply_session_policy(&input, ask, &config);
+        assert_eq!(strict.block_info().unwrap().rule, "escalation.strict");
+        assert!(!apply_session_policy(&input, Decision::allow(), &config).is_blocked());
+    }
+
+    fn bash_input(command: &str) -> HookInput {
+        let json = serde_json::json!({
+            "tool_name": "Bash",
+            "session_id": "abc-123",
+            "tool_input": {"command": command}
+        });
+        HookInput::parse(&json.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_lockdown_after_workaround_sequence() {
+        let dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.session.state_dir = Some(dir.path().to_string_lossy().to_string());
+        config.lockdown.enabled = true;
+        let config = config.compile().unwrap();
+        let read_env = HookInput::parse(
+            r#"{"tool_name":"Read","session_id":"abc-123","tool_input":{"file_path":"/app/.env"}}"#,
+        )
+        .unwrap();
+
+        let blocked = Decision::block("sensitive.read", "r");
+        assert_eq!(
+            apply_lockdown(&read_env, blocked, &config)
+                .block_info()
+                .unwrap()
+                .rule,
+            "sensitive.read"
+        );
+        let decision = apply_lockdown(&bash_input("ls"), Decision::allow(), &config);
+        assert!(!decision.is_blocked());
+        let decision = apply_lockdown(&bash_input("base64 notes"), Decision::allow(), &config);
+        assert!(!decision.is_blocked());
+
+        let decision = apply_lockdown(
+            &bash_input("python3 -c \"print(open('/app/.env').read())\""),
+            Decision::allow(),
+            &config,
+        );
+        assert_eq!(decision.block_info().unwrap().rule, "lockdown.session");
+        let decision = apply_lockdown(&bash_input("ls"), Decision::allow(), &config);
+        assert!(decision.is_blocked());
+
+        let store = SessionStore::from_config(&config).unwrap();
+        assert!(unlock_session(&store, "abc-123").unwrap());
+        let decision = apply_lockdown(&bash_input("ls"), Decision::allow(), &config);
+        assert!(!decision.is_blocked());
+    }
+
+    #[test]
+    fn test_attempts_outside_window_do_not_count() {
+        let config = LockdownConfig::default();
+        let mut state = LockdownState::default();
+        let now = Utc::now();
+        let blocked = Decision::block("sensitive.read", "r");
+        state.record(
+            &bash_input("cat .env"),
+            &blocked,
+            now - Duration::hours(1),
+            &config,
+        );
+        assert!(!state.record(&bash_input("base64 x"), &Decision::allow(), now, &config));
+        assert_eq!(state.attempts, 0);
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let config = Config::default().compile().unwrap();
+        let ask = Decision::ask("deps.edit", "editing dependency file");
+        let decision = apply_remembered_approval(&edit_input("PreToolUse"), ask, &config);
+        assert!(decision.ask_info().unwrap().approval_key.is_none());
+    }
+}
@@ -1,9 +1,15 @@
 //! Shell command parsing.
 
+mod nested;
+mod quote;
 mod splitter;
 mod tokenizer;
+mod variables;
 mod wrappers;
 
+pub use nested::{command_substitutions, expand_commands};
+pub use quote::{join_words, quote_word};
 pub use splitter::{CommandSegment, Operator, split_commands};
 pub use tokenizer::{Token, tokenize};
+pub use variables::{Variables, expand_home};
 pub use wrappers::{extract_options, strip_wrappers};
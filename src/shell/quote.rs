@@ -0,0 +1,55 @@
+//! Shell quoting for rebuilding commands from words.
+
+/// Quote a single word so the shell reads it back unchanged.
+pub fn quote_word(word: &str) -> String {
+    let is_plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./=:@%+,".contains(c));
+    if is_plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// Join words into a command string, quoting where needed.
+pub fn join_words<S: AsRef<str>>(words: &[S]) -> String {
+    words
+        .iter()
+        .map(|w| quote_word(w.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::{Token, tokenize};
+
+    #[test]
+    fn test_plain_words_unquoted() {
+        assert_eq!(join_words(&["rm", "-rI", "build/"]), "rm -rI build/");
+    }
+
+    #[test]
+    fn test_special_words_quoted() {
+        assert_eq!(quote_word("my dir"), "'my dir'");
+        assert_eq!(quote_word("$HOME"), "'$HOME'");
+        assert_eq!(quote_word(""), "''");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let words = ["rm", "it's here", "a;b"];
+        let tokens = tokenize(&join_words(&words));
+        let parsed: Vec<String> = tokens
+            .into_iter()
+            .map(|t| match t {
+                Token::Word(w) => w,
+                other => panic!("unexpected token {:?}", other),
+            })
+            .collect();
+        assert_eq!(parsed, words);
+    }
+}
@@ -1,4 +1,4 @@
-//! Split shell commands on operators (&&, ||, |, ;, &).
+//! Split shell commands on operators (&&, ||, |, ;, &) and newlines.
 
 /// Shell operators that separate commands.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,11 +22,36 @@ pub struct CommandSegment {
     pub command: String,
     /// The operator that follows this segment (None for last segment).
     pub operator: Option<Operator>,
+    /// The here-documents the command reads, in order.
+    pub heredocs: Vec<Heredoc>,
+}
+
+/// The body of a `<<DELIM` here-document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heredoc {
+    /// The lines up to the delimiter line, each ending in a newline.
+    pub body: String,
+    /// Whether the delimiter was unquoted, so the shell expands `$(...)`
+    /// and variables in the body.
+    pub expands: bool,
+}
+
+/// A here-document whose body starts after the current line.
+struct PendingHeredoc {
+    /// Index of the segment that reads it.
+    segment: usize,
+    delimiter: String,
+    /// `<<-` strips leading tabs, so the delimiter line may be indented.
+    strip_tabs: bool,
+    expands: bool,
 }
 
 /// Split a command line into segments on shell operators.
 ///
-/// Respects quoting (', ", $'...') and escapes.
+/// Respects quoting (', ", $'...'), escapes, and command and process
+/// substitutions.
+/// Here-document bodies are attached to the segment that reads them instead
+/// of being split as commands.
 pub fn split_commands(input: &str) -> Vec<CommandSegment> {
     let mut segments = Vec::new();
     let mut current = String::new();
@@ -34,6 +59,7 @@ pub fn split_commands(input: &str) -> Vec<CommandSegment> {
     let mut in_single_quote = false;
     let mut in_double_quote = false;
     let mut escape_next = false;
+    let mut pending: Vec<PendingHeredoc> = Vec::new();
 
     while let Some(c) = chars.next() {
         if escape_next {
@@ -48,6 +74,21 @@ pub fn split_commands(input: &str) -> Vec<CommandSegment> {
             continue;
         }
 
+        // `$'...'` allows escaped quotes inside
+        if c == '$' && !in_single_quote && !in_double_quote && chars.peek() == Some(&'\'') {
+            current.push(c);
+            current.extend(chars.next());
+            while let Some(c) = chars.next() {
+                current.push(c);
+                match c {
+                    '\\' => current.extend(chars.next()),
+                    '\'' => break,
+                    _ => {}
+                }
+            }
+            continue;
+        }
+
         if c == '\'' && !in_double_quote {
             in_single_quote = !in_single_quote;
             current.push(c);
@@ -70,58 +111,88 @@ pub fn split_commands(input: &str) -> Vec<CommandSegment> {
             '&' => {
                 if chars.peek() == Some(&'&') {
                     chars.next();
-                    let trimmed = current.trim().to_string();
-                    if !trimmed.is_empty() {
-                        segments.push(CommandSegment {
-                            command: trimmed,
-                            operator: Some(Operator::And),
-                        });
-                    }
-                    current.clear();
+                    push_segment(&mut segments, &mut current, Some(Operator::And));
                 } else {
                     // Background operator - but only if at end or followed by space/newline
                     // For simplicity, treat as background
-                    let trimmed = current.trim().to_string();
-                    if !trimmed.is_empty() {
-                        segments.push(CommandSegment {
-                            command: trimmed,
-                            operator: Some(Operator::Background),
-                        });
-                    }
-                    current.clear();
+                    push_segment(&mut segments, &mut current, Some(Operator::Background));
                 }
             }
             '|' => {
                 if chars.peek() == Some(&'|') {
                     chars.next();
-                    let trimmed = current.trim().to_string();
-                    if !trimmed.is_empty() {
-                        segments.push(CommandSegment {
-                            command: trimmed,
-                            operator: Some(Operator::Or),
-                        });
-                    }
-                    current.clear();
+                    push_segment(&mut segments, &mut current, Some(Operator::Or));
                 } else {
-                    let trimmed = current.trim().to_string();
-                    if !trimmed.is_empty() {
-                        segments.push(CommandSegment {
-                            command: trimmed,
-                            operator: Some(Operator::Pipe),
-                        });
+                    push_segment(&mut segments, &mut current, Some(Operator::Pipe));
+                }
+            }
+            // `$(...)`, `<(...)` and `>(...)` run a whole command line
+            '$' | '<' | '>' if chars.peek() == Some(&'(') => {
+                chars.next();
+                current.push(c);
+                current.push('(');
+                if read_group(&mut chars, &mut current) {
+                    current.push(')');
+                }
+            }
+            '`' => {
+                current.push(c);
+                while let Some(c) = chars.next() {
+                    current.push(c);
+                    match c {
+                        '\\' => current.extend(chars.next()),
+                        '`' => break,
+                        _ => {}
                     }
-                    current.clear();
                 }
             }
-            ';' => {
-                let trimmed = current.trim().to_string();
-                if !trimmed.is_empty() {
-                    segments.push(CommandSegment {
-                        command: trimmed,
-                        operator: Some(Operator::Semicolon),
+            // `<<DELIM` or `<<-DELIM`, but not the `<<<` here-string or a
+            // shift in `$((...))`
+            '<' if chars.peek() == Some(&'<') && !in_arithmetic(&current) => {
+                chars.next();
+                current.push_str("<<");
+                if chars.peek() == Some(&'<') {
+                    chars.next();
+                    current.push('<');
+                    continue;
+                }
+                let strip_tabs = chars.next_if_eq(&'-').is_some();
+                if strip_tabs {
+                    current.push('-');
+                }
+                while let Some(space) = chars.next_if(|c| *c == ' ' || *c == '\t') {
+                    current.push(space);
+                }
+                let (delimiter, expands) = read_delimiter(&mut chars, &mut current);
+                if !delimiter.is_empty() {
+                    pending.push(PendingHeredoc {
+                        segment: segments.len(),
+                        delimiter,
+                        strip_tabs,
+                        expands,
                     });
                 }
-                current.clear();
+            }
+            // An unescaped newline ends a command like ;
+            ';' | '\n' => {
+                push_segment(&mut segments, &mut current, Some(Operator::Semicolon));
+                if c == '\n' {
+                    for heredoc in pending.drain(..) {
+                        // Without a delimiter line the rest is split as
+                        // commands, so nothing can hide in a bogus body
+                        let mut rest = chars.clone();
+                        let Some(body) = read_heredoc_body(&mut rest, &heredoc) else {
+                            break;
+                        };
+                        chars = rest;
+                        if let Some(segment) = segments.get_mut(heredoc.segment) {
+                            segment.heredocs.push(Heredoc {
+                                body,
+                                expands: heredoc.expands,
+                            });
+                        }
+                    }
+                }
             }
             _ => {
                 current.push(c);
@@ -130,15 +201,140 @@ pub fn split_commands(input: &str) -> Vec<CommandSegment> {
     }
 
     // Add final segment
-    let trimmed = current.trim().to_string();
+    push_segment(&mut segments, &mut current, None);
+
+    segments
+}
+
+/// End the current segment with `operator`, unless it is empty.
+fn push_segment(
+    segments: &mut Vec<CommandSegment>,
+    current: &mut String,
+    operator: Option<Operator>,
+) {
+    let trimmed = current.trim();
     if !trimmed.is_empty() {
         segments.push(CommandSegment {
-            command: trimmed,
-            operator: None,
+            command: trimmed.to_string(),
+            operator,
+            heredocs: Vec::new(),
         });
     }
+    current.clear();
+}
 
-    segments
+/// Read a here-document delimiter word, copying it to `current`. Quoting
+/// any part of it turns off expansion in the body.
+fn read_delimiter(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    current: &mut String,
+) -> (String, bool) {
+    let mut delimiter = String::new();
+    let mut expands = true;
+    let mut quote = None;
+    while let Some(&c) = chars.peek() {
+        match (quote, c) {
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                expands = false;
+            }
+            (Some(q), _) if c == q => quote = None,
+            (None, '\\') => {
+                expands = false;
+                current.push(c);
+                chars.next();
+                match chars.next() {
+                    Some(escaped) => {
+                        current.push(escaped);
+                        delimiter.push(escaped);
+                    }
+                    None => break,
+                }
+                continue;
+            }
+            (None, c) if c.is_whitespace() || ";&|<>()".contains(c) => break,
+            _ => delimiter.push(c),
+        }
+        current.push(c);
+        chars.next();
+    }
+    (delimiter, expands)
+}
+
+/// Copy the text up to the `)` that closes an opened `(` to `out`,
+/// respecting nested parentheses, quotes and escapes. Consumes the `)` and
+/// returns whether there was one.
+pub(super) fn read_group(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    out: &mut String,
+) -> bool {
+    let mut depth = 1;
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                out.push(c);
+                match chars.next() {
+                    Some(escaped) => out.push(escaped),
+                    None => break,
+                }
+                continue;
+            }
+            (Some(_), '"') => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    false
+}
+
+/// Check if `text` ends inside an unclosed `((...))`.
+fn in_arithmetic(text: &str) -> bool {
+    text.matches("((").count() > text.matches("))").count()
+}
+
+/// Read here-document lines up to and including the delimiter line, or
+/// None if there is none.
+fn read_heredoc_body(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    heredoc: &PendingHeredoc,
+) -> Option<String> {
+    let mut body = String::new();
+    loop {
+        let mut line = String::new();
+        let mut ended = true;
+        for c in chars.by_ref() {
+            if c == '\n' {
+                ended = false;
+                break;
+            }
+            line.push(c);
+        }
+        let line = if heredoc.strip_tabs {
+            line.trim_start_matches('\t')
+        } else {
+            line.as_str()
+        };
+        if line == heredoc.delimiter {
+            return Some(body);
+        }
+        if ended {
+            return None;
+        }
+        body.push_str(line);
+        body.push('\n');
+    }
 }
 
 #[cfg(test)]
@@ -208,4 +404,77 @@ mod tests {
         let segments = split_commands("a && b || c; d | e");
         assert_eq!(segments.len(), 5);
     }
+
+    #[test]
+    fn test_newline_separates_commands() {
+        let segments = split_commands("echo hi\nrm -rf /");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].command, "rm -rf /");
+        // Quoted and escaped newlines do not
+        assert_eq!(split_commands("echo 'a\nb'").len(), 1);
+        assert_eq!(split_commands("rm -rf \\\n/").len(), 1);
+    }
+
+    #[test]
+    fn test_heredoc_body_attached() {
+        let segments = split_commands("cat > notes.md <<'EOF'\nrm -rf /\n$(cat .env)\nEOF\nls");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].command, "cat > notes.md <<'EOF'");
+        assert_eq!(
+            segments[0].heredocs,
+            [Heredoc {
+                body: "rm -rf /\n$(cat .env)\n".to_string(),
+                expands: false,
+            }]
+        );
+        assert_eq!(segments[1].command, "ls");
+    }
+
+    #[test]
+    fn test_heredoc_variants() {
+        // <<- strips tabs, the reading segment may be followed by a pipe
+        let segments = split_commands("cat <<-END | sh\n\tls\n\tEND");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].heredocs[0].body, "ls\n");
+        assert!(segments[0].heredocs[0].expands);
+        assert!(segments[1].heredocs.is_empty());
+        // Two here-documents on one line are read in order
+        let segments = split_commands("paste <<A <<B\n1\nA\n2\nB");
+        let bodies: Vec<_> = segments[0]
+            .heredocs
+            .iter()
+            .map(|h| h.body.as_str())
+            .collect();
+        assert_eq!(bodies, ["1\n", "2\n"]);
+    }
+
+    #[test]
+    fn test_not_heredocs() {
+        // Here-strings, shifts and unterminated bodies leave lines as commands
+        assert_eq!(split_commands("cat <<< hi\nls").len(), 2);
+        assert_eq!(split_commands("echo $((1<<X))\nrm -rf /\nX))").len(), 3);
+        assert_eq!(split_commands("cat <<EOF\nrm -rf /").len(), 2);
+        assert_eq!(split_commands("echo '<<EOF'\nls\nEOF").len(), 3);
+    }
+
+    #[test]
+    fn test_process_substitution_not_split() {
+        let segments = split_commands("diff <(ls a | sort) <(ls b) && echo same");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].command, "diff <(ls a | sort) <(ls b)");
+    }
+
+    #[test]
+    fn test_command_substitution_not_split() {
+        let segments = split_commands("echo $(ls && cat .env) `a | b`; ls");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].command, "echo $(ls && cat .env) `a | b`");
+    }
+
+    #[test]
+    fn test_ansi_c_quote_escapes() {
+        let segments = split_commands("echo $'it\\'s && fine' && cat .env");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].command, "echo $'it\\'s && fine'");
+    }
 }
@@ -1,5 +1,7 @@
 //! Shell-style tokenization (shlex-like).
 
+use super::splitter::read_group;
+
 /// A token from shell parsing.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token {
@@ -19,6 +21,8 @@ pub fn tokenize(input: &str) -> Vec<Token> {
     let mut in_single_quote = false;
     let mut in_double_quote = false;
     let mut escape_next = false;
+    // Set by quotes, so `''` is an (empty) word
+    let mut quoted = false;
 
     while let Some(c) = chars.next() {
         if escape_next {
@@ -28,22 +32,43 @@ pub fn tokenize(input: &str) -> Vec<Token> {
         }
 
         if c == '\\' && !in_single_quote {
-            escape_next = true;
-            // In double quotes, only certain chars are escaped
-            if !in_double_quote {
-                continue; // Don't include the backslash
+            // Backslash-newline is a line continuation and disappears
+            if chars.peek() == Some(&'\n') {
+                chars.next();
+                continue;
             }
-            current.push(c);
+            // In double quotes, only $ ` " \ are escaped; otherwise the
+            // backslash is literal
+            if in_double_quote && !matches!(chars.peek(), Some('$' | '`' | '"' | '\\')) {
+                current.push(c);
+                continue;
+            }
+            escape_next = true;
+            continue; // Don't include the backslash
+        }
+
+        // `$"..."` is translated with the locale, and otherwise like "..."
+        if c == '$' && !in_single_quote && !in_double_quote && chars.peek() == Some(&'"') {
+            continue;
+        }
+
+        // ANSI-C quoting: `$'\x2eenv'` is `.env`
+        if c == '$' && !in_single_quote && !in_double_quote && chars.peek() == Some(&'\'') {
+            chars.next();
+            read_ansi_c_quoted(&mut chars, &mut current);
+            quoted = true;
             continue;
         }
 
         if c == '\'' && !in_double_quote {
             in_single_quote = !in_single_quote;
+            quoted = true;
             continue; // Don't include the quote
         }
 
         if c == '"' && !in_single_quote {
             in_double_quote = !in_double_quote;
+            quoted = true;
             continue; // Don't include the quote
         }
 
@@ -54,18 +79,31 @@ pub fn tokenize(input: &str) -> Vec<Token> {
 
         // Outside quotes
         if c.is_whitespace() {
-            if !current.is_empty() {
-                tokens.push(classify_token(&current));
+            if !current.is_empty() || quoted {
+                tokens.push(classify_token(&current, &tokens));
                 current.clear();
+                quoted = false;
+            }
+            continue;
+        }
+
+        // A process substitution is one word, kept verbatim
+        if (c == '<' || c == '>') && chars.peek() == Some(&'(') {
+            chars.next();
+            current.push(c);
+            current.push('(');
+            if read_group(&mut chars, &mut current) {
+                current.push(')');
             }
             continue;
         }
 
         // Check for redirections
         if c == '>' || c == '<' {
-            if !current.is_empty() {
-                tokens.push(classify_token(&current));
+            if !current.is_empty() || quoted {
+                tokens.push(classify_token(&current, &tokens));
                 current.clear();
+                quoted = false;
             }
             let mut redir = String::from(c);
             if c == '>'
@@ -97,16 +135,81 @@ pub fn tokenize(input: &str) -> Vec<Token> {
         current.push(c);
     }
 
-    if !current.is_empty() {
-        tokens.push(classify_token(&current));
+    // A trailing backslash escapes nothing and is kept
+    if escape_next {
+        current.push('\\');
+    }
+    if !current.is_empty() || quoted {
+        tokens.push(classify_token(&current, &tokens));
     }
 
     tokens
 }
 
-fn classify_token(s: &str) -> Token {
+/// Decode the rest of a `$'...'` string, up to its closing quote, into
+/// `out`.
+fn read_ansi_c_quoted(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, out: &mut String) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => return,
+            '\\' => {}
+            _ => {
+                out.push(c);
+                continue;
+            }
+        }
+        let Some(escape) = chars.next() else {
+            out.push('\\');
+            return;
+        };
+        // Up to `max` digits in `radix`, as a character
+        let mut code = |radix: u32, max: usize, first: Option<char>| {
+            let mut digits: String = first.into_iter().collect();
+            while digits.len() < max
+                && let Some(d) = chars.next_if(|d| d.is_digit(radix))
+            {
+                digits.push(d);
+            }
+            u32::from_str_radix(&digits, radix)
+                .ok()
+                .and_then(char::from_u32)
+        };
+        let decoded = match escape {
+            'a' => Some('\x07'),
+            'b' => Some('\x08'),
+            'e' | 'E' => Some('\x1b'),
+            'f' => Some('\x0c'),
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            'v' => Some('\x0b'),
+            '0'..='7' => code(8, 3, Some(escape)),
+            'x' => code(16, 2, None),
+            'u' => code(16, 4, None),
+            'U' => code(16, 8, None),
+            // Control characters: `\cA` is 0x01
+            'c' => chars
+                .next()
+                .map(|c| char::from(c.to_ascii_uppercase() as u8 & 0x1f)),
+            // `\\`, `\'`, `\"` and `\?` are the character itself
+            '\\' | '\'' | '"' | '?' => Some(escape),
+            other => {
+                out.push('\\');
+                Some(other)
+            }
+        };
+        out.extend(decoded);
+    }
+}
+
+/// Classify a word given the tokens before it.
+fn classify_token(s: &str, before: &[Token]) -> Token {
+    // Only words before the command name are assignments; `rm -rf a=b`
+    // removes a file called `a=b`
+    let is_prefix = !before.iter().any(|t| matches!(t, Token::Word(_)));
     // Check for assignment (VAR=value, not starting with =)
-    if let Some(eq_pos) = s.find('=')
+    if is_prefix
+        && let Some(eq_pos) = s.find('=')
         && eq_pos > 0
     {
         let var = &s[..eq_pos];
@@ -205,6 +308,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_assignment_only_before_command() {
+        let tokens = tokenize("rm -rf a=/");
+        assert_eq!(tokens[2], Token::Word("a=/".to_string()));
+    }
+
+    #[test]
+    fn test_backslash_in_double_quotes() {
+        let tokens = tokenize(r#"echo "a\"b" "c\d" "\$HOME" "e\\""#);
+        let words: Vec<_> = tokens[1..]
+            .iter()
+            .map(|t| match t {
+                Token::Word(w) => w.as_str(),
+                other => panic!("unexpected token {:?}", other),
+            })
+            .collect();
+        assert_eq!(words, vec![r#"a"b"#, r"c\d", "$HOME", r"e\"]);
+    }
+
+    #[test]
+    fn test_empty_quoted_word() {
+        let tokens = tokenize("rm -rf '' \"\"");
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[2], Token::Word(String::new()));
+    }
+
+    #[test]
+    fn test_line_continuation() {
+        let tokens = tokenize("rm -rf \\\n/");
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[2], Token::Word("/".to_string()));
+    }
+
     #[test]
     fn test_redirect() {
         let tokens = tokenize("cat file > output");
@@ -234,4 +370,54 @@ mod tests {
         let args = arguments(&tokens);
         assert_eq!(args, vec!["commit", "-m", "message"]);
     }
+
+    #[test]
+    fn test_process_substitution_is_one_word() {
+        let tokens = tokenize("diff <(cat 'a b') >(wc -l) < in");
+        assert_eq!(
+            tokens,
+            [
+                Token::Word("diff".to_string()),
+                Token::Word("<(cat 'a b')".to_string()),
+                Token::Word(">(wc -l)".to_string()),
+                Token::Redirect("<".to_string()),
+                Token::Word("in".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_obfuscated_paths_normalized() {
+        for input in [
+            "cat \".e\"\"nv\"",
+            "cat '.e'nv",
+            "cat .e\\nv",
+            "cat \\.\\e\\n\\v",
+            "cat .e$''nv",
+            "cat $'\\x2eenv'",
+            "cat $'\\056env'",
+            "cat $'\\u002e\\x65nv'",
+            "cat $\".env\"",
+        ] {
+            assert_eq!(
+                tokenize(input)[1],
+                Token::Word(".env".to_string()),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_ansi_c_escapes() {
+        assert_eq!(
+            tokenize("printf $'a\\tb\\n\\cA\\q\\'s'")[1],
+            Token::Word("a\tb\n\x01\\q's".to_string())
+        );
+        // Only outside quotes
+        assert_eq!(
+            tokenize("echo \"$'\\x41'\"")[1],
+            Token::Word("$'\\x41'".to_string())
+        );
+    }
 }
@@ -0,0 +1,188 @@
+//! Track shell variables assigned earlier in a command line.
+//!
+//! `F=.env; cat $F` reads `.env` without naming it where it is read.
+//! Substituting the values of simple assignments lets the path rules see
+//! what the command actually touches.
+
+use std::collections::HashMap;
+
+use super::tokenizer::Token;
+
+/// Builtins whose `NAME=value` arguments assign variables.
+const DECLARATIONS: &[&str] = &["export", "declare", "typeset", "local", "readonly"];
+
+/// Variables assigned by the segments of a command line seen so far.
+#[derive(Debug, Default, Clone)]
+pub struct Variables {
+    values: HashMap<String, String>,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the assignments of a segment that only assigns (`F=.env`) or
+    /// declares (`export F=.env`). Prefix assignments of a command, as in
+    /// `F=.env make`, only apply to that command and are not recorded.
+    pub fn record(&mut self, tokens: &[Token]) {
+        let mut words = tokens.iter().filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        });
+        match words.next() {
+            None => {
+                for token in tokens {
+                    if let Token::Assignment(name, value) = token {
+                        self.assign(name, value);
+                    }
+                }
+            }
+            Some(cmd) if DECLARATIONS.contains(&cmd) => {
+                for word in words.filter(|w| !w.starts_with('-')) {
+                    if let Some((name, value)) = word.split_once('=') {
+                        self.assign(name, value);
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn assign(&mut self, name: &str, value: &str) {
+        let value = self.substitute_word(value);
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Replace `$NAME` and `${NAME}` in words with the values recorded so
+    /// far. Unknown variables are left as they are.
+    pub fn substitute(&self, tokens: &[Token]) -> Vec<Token> {
+        if self.values.is_empty() {
+            return tokens.to_vec();
+        }
+        tokens
+            .iter()
+            .map(|token| match token {
+                Token::Word(w) => Token::Word(self.substitute_word(w)),
+                other => other.clone(),
+            })
+            .collect()
+    }
+
+    fn substitute_word(&self, word: &str) -> String {
+        let mut result = String::new();
+        let mut rest = word;
+        while let Some(pos) = rest.find('$') {
+            result.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+            let (name, len) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], end + 2),
+                    None => ("", 0),
+                },
+                None => {
+                    let end = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    (&rest[..end], end)
+                }
+            };
+            match self.values.get(name) {
+                Some(value) => {
+                    result.push_str(value);
+                    rest = &rest[len..];
+                }
+                None => result.push('$'),
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Expand a leading `~`, `~user`, `$HOME` or `${HOME}` in a path to the
+/// home directory. Paths without one, or when the home directory is
+/// unknown, are returned as they are.
+pub fn expand_home(path: &str) -> String {
+    let Some(home) = dirs::home_dir() else {
+        return path.to_string();
+    };
+    let home = home.to_string_lossy();
+    let (prefix, rest) = match path.find('/') {
+        Some(slash) => path.split_at(slash),
+        None => (path, ""),
+    };
+    match prefix {
+        "~" | "$HOME" | "${HOME}" => format!("{}{}", home, rest),
+        // Other users' homes are next to ours, except root's
+        "~root" => format!("/root{}", rest),
+        _ => match prefix.strip_prefix('~') {
+            Some(user) if is_user_name(user) => {
+                let parent = home.rsplit_once('/').map_or("", |(parent, _)| parent);
+                format!("{}/{}{}", parent, user, rest)
+            }
+            _ => path.to_string(),
+        },
+    }
+}
+
+fn is_user_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::tokenize;
+
+    fn words(tokens: &[Token]) -> Vec<&str> {
+        tokens
+            .iter()
+            .filter_map(|t| match t {
+                Token::Word(w) => Some(w.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_assignments_substituted() {
+        let mut vars = Variables::new();
+        vars.record(&tokenize("DIR=~/.ssh"));
+        vars.record(&tokenize("export KEY=$DIR/id_rsa"));
+        let tokens = vars.substitute(&tokenize("cat $KEY ${DIR}/config \"$HOME\" $"));
+        assert_eq!(
+            words(&tokens),
+            ["cat", "~/.ssh/id_rsa", "~/.ssh/config", "$HOME", "$"]
+        );
+    }
+
+    #[test]
+    fn test_command_prefix_not_recorded() {
+        let mut vars = Variables::new();
+        vars.record(&tokenize("F=.env make"));
+        vars.record(&tokenize("echo G=.env"));
+        let tokens = vars.substitute(&tokenize("cat $F $G"));
+        assert_eq!(words(&tokens), ["cat", "$F", "$G"]);
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let Some(home) = dirs::home_dir() else {
+            return;
+        };
+        let home = home.to_string_lossy().to_string();
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("~/.ssh"), format!("{}/.ssh", home));
+        assert_eq!(expand_home("$HOME/.aws"), format!("{}/.aws", home));
+        assert_eq!(expand_home("${HOME}"), home);
+        assert_eq!(expand_home("~root/.ssh"), "/root/.ssh");
+        assert!(expand_home("~alice/x").ends_with("/alice/x"));
+        for unchanged in ["src/~x", "$HOMEDIR", "~+", "a~", "/tmp"] {
+            assert_eq!(expand_home(unchanged), unchanged);
+        }
+    }
+}
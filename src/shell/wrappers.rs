@@ -1,5 +1,6 @@
 //! Strip wrapper commands (sudo, env, bash -c, etc.).
 
+use super::quote::join_words;
 use super::tokenizer::{Token, tokenize};
 
 /// Commands that wrap other commands.
@@ -52,6 +53,11 @@ fn strip_wrappers_recursive(command: &str, depth: usize) -> String {
         return handle_shell_c(&tokens[idx..], depth);
     }
 
+    // Windows shells: cmd /c ..., powershell -Command ...
+    if let Some(shell) = WindowsShell::from_name(cmd) {
+        return handle_windows_shell(shell, &tokens[idx..], depth);
+    }
+
     // Check for wrapper commands
     if WRAPPER_COMMANDS.contains(&cmd) {
         return handle_wrapper(&tokens[idx..], depth);
@@ -61,11 +67,11 @@ fn strip_wrappers_recursive(command: &str, depth: usize) -> String {
 }
 
 fn handle_shell_c(tokens: &[Token], depth: usize) -> String {
-    // Look for -c flag
+    // Look for -c flag, alone or grouped with others (-lc, -ec)
     let mut found_c = false;
     for token in tokens.iter() {
         if let Token::Word(w) = token {
-            if w == "-c" {
+            if !found_c && is_c_flag(w) {
                 found_c = true;
             } else if found_c {
                 // This is the command to execute
@@ -75,14 +81,70 @@ fn handle_shell_c(tokens: &[Token], depth: usize) -> String {
     }
 
     // No -c flag found, return original
-    tokens
+    let words: Vec<&str> = tokens
         .iter()
         .filter_map(|t| match t {
             Token::Word(w) => Some(w.as_str()),
             _ => None,
         })
-        .collect::<Vec<_>>()
-        .join(" ")
+        .collect();
+    join_words(&words)
+}
+
+/// Check if `word` is a group of short options that includes `-c`.
+fn is_c_flag(word: &str) -> bool {
+    word.strip_prefix('-')
+        .is_some_and(|flags| flags.contains('c') && flags.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// A Windows command interpreter.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WindowsShell {
+    Cmd,
+    PowerShell,
+}
+
+impl WindowsShell {
+    /// Recognize `cmd`, `powershell` and `pwsh`, in any case, with or
+    /// without `.exe`.
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        match name.strip_suffix(".exe").unwrap_or(&name) {
+            "cmd" => Some(WindowsShell::Cmd),
+            "powershell" | "pwsh" => Some(WindowsShell::PowerShell),
+            _ => None,
+        }
+    }
+
+    /// Check if `word` is the option that introduces the command string.
+    fn is_command_flag(self, word: &str) -> bool {
+        let word = word.to_ascii_lowercase();
+        match self {
+            WindowsShell::Cmd => word == "/c" || word == "/k",
+            // Parameter names may be abbreviated: -c, -com, -command
+            WindowsShell::PowerShell => word
+                .strip_prefix('-')
+                .is_some_and(|name| !name.is_empty() && "command".starts_with(name)),
+        }
+    }
+}
+
+fn handle_windows_shell(shell: WindowsShell, tokens: &[Token], depth: usize) -> String {
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let Some(flag) = words.iter().position(|w| shell.is_command_flag(w)) else {
+        return words.join(" ");
+    };
+    // Backslashes in a Windows command string are path separators, not
+    // escapes; use forward slashes so the POSIX rules and patterns apply
+    let command = words[flag + 1..].join(" ").replace('\\', "/");
+    strip_wrappers_recursive(&command, depth + 1)
 }
 
 fn handle_wrapper(tokens: &[Token], depth: usize) -> String {
@@ -174,7 +236,9 @@ fn handle_wrapper(tokens: &[Token], depth: usize) -> String {
         return String::new();
     }
 
-    let remaining = words[start..].join(" ");
+    // Quote so arguments with spaces survive the re-parse
+    // (`sudo bash -c "rm -rf /"`)
+    let remaining = join_words(&words[start..]);
     strip_wrappers_recursive(&remaining, depth + 1)
 }
 
@@ -262,6 +326,22 @@ mod tests {
         assert_eq!(result, "ls -la");
     }
 
+    #[test]
+    fn test_strip_wrapper_keeps_quoted_arguments() {
+        assert_eq!(strip_wrappers(r#"sudo bash -c "rm -rf /""#), "rm -rf /");
+        assert_eq!(strip_wrappers("nohup cat 'my file'"), "cat 'my file'");
+    }
+
+    #[test]
+    fn test_strip_grouped_c_flag() {
+        assert_eq!(strip_wrappers("bash -lc 'rm -rf /'"), "rm -rf /");
+        assert_eq!(strip_wrappers("sh -ec 'ls'"), "ls");
+        assert_eq!(
+            strip_wrappers("bash --norc script.sh"),
+            "bash --norc script.sh"
+        );
+    }
+
     #[test]
     fn test_strip_timeout() {
         assert_eq!(strip_wrappers("timeout 5 ls"), "ls");
@@ -276,6 +356,32 @@ mod tests {
         assert!(result.contains("sudo") || result == "ls");
     }
 
+    #[test]
+    fn test_strip_cmd_c() {
+        assert_eq!(
+            strip_wrappers(r#"cmd.exe /c "type C:\Users\me\.aws\credentials""#),
+            "type C:/Users/me/.aws/credentials"
+        );
+        assert_eq!(
+            strip_wrappers("CMD /Q /C del /s /q build"),
+            "del /s /q build"
+        );
+    }
+
+    #[test]
+    fn test_strip_powershell_command() {
+        assert_eq!(
+            strip_wrappers(r#"powershell -NoProfile -Command "Remove-Item -Recurse C:\tmp""#),
+            "Remove-Item -Recurse C:/tmp"
+        );
+        assert_eq!(strip_wrappers("pwsh -c 'sudo ls'"), "ls");
+        // Without a command string there is nothing to unwrap
+        assert_eq!(
+            strip_wrappers("pwsh -File script.ps1"),
+            "pwsh -File script.ps1"
+        );
+    }
+
     #[test]
     fn test_no_wrapper() {
         assert_eq!(strip_wrappers("ls -la"), "ls -la");
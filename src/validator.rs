@@ -0,0 +1,279 @@
+//! External validator commands.
+//!
+//! A validator is a program from the user config that receives the hook
+//! JSON on stdin and answers like a Claude Code hook: exit 0 allows, exit 2
+//! blocks with stderr as the reason. On exit 0 it may instead print
+//! `{"decision": "block"|"ask"|"warn"|"allow", "reason": "...", "rule": "..."}`
+//! on stdout. Anything else (another exit code, a timeout, a failure to
+//! start, unreadable JSON) is an error, handled by the validator's
+//! `fail_mode`.
+
+use std::io::{Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{CompiledConfig, FailMode, Validator};
+use crate::decision::{Decision, ExternalVerdict};
+use crate::input::HookInput;
+
+/// How often a running validator is polled for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How long output is still waited for once the timeout has passed.
+const DRAIN_GRACE: Duration = Duration::from_millis(50);
+
+/// Run the validators for this tool in order, stopping at the first block.
+/// Returns the strictest decision, or `None` when all allowed.
+pub fn run_validators(input: &HookInput, config: &CompiledConfig) -> Option<Decision> {
+    let validators = config
+        .raw
+        .validators
+        .iter()
+        .filter(|validator| validator.applies_to(&input.tool_name));
+
+    let mut stdin = None;
+    let mut decision: Option<Decision> = None;
+    for validator in validators {
+        let stdin = stdin.get_or_insert_with(|| serde_json::to_vec(input).unwrap_or_default());
+        let started = Instant::now();
+        let found = match run_validator(validator, stdin) {
+            Ok(found) => found,
+            Err(e) => on_error(validator, &e),
+        };
+        tracing::debug!(
+            validator = %validator.name,
+            decision = found.as_ref().map_or("none", Decision::label),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "validator ran"
+        );
+        if let Some(found) = found {
+            let blocked = found.is_blocked();
+            decision = Some(match decision {
+                Some(current) => current.stricter(found),
+                None => found,
+            });
+            if blocked {
+                break;
+            }
+        }
+    }
+    decision
+}
+
+/// Apply the validator's fail mode to an error.
+fn on_error(validator: &Validator, error: &str) -> Option<Decision> {
+    tracing::warn!(validator = %validator.name, error, "validator failed");
+    match validator.fail_mode {
+        FailMode::Open => None,
+        FailMode::Closed => Some(Decision::block(
+            rule_name(validator),
+            format!("Validator {} failed: {}", validator.name, error),
+        )),
+    }
+}
+
+fn rule_name(validator: &Validator) -> String {
+    format!("validator.{}", validator.name)
+}
+
+/// Run one validator on `stdin`.
+fn run_validator(validator: &Validator, stdin: &[u8]) -> Result<Option<Decision>, String> {
+    let (program, args) = validator.command.split_first().ok_or("empty command")?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not start {}: {}", program, e))?;
+
+    // Feed and drain the pipes on threads so a chatty validator can't
+    // deadlock against us
+    let mut pipe = child.stdin.take().expect("stdin is piped");
+    let input = stdin.to_vec();
+    thread::spawn(move || {
+        let _ = pipe.write_all(&input);
+    });
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let timeout = Duration::from_millis(validator.timeout_ms);
+    let deadline = Instant::now() + timeout;
+    let status = wait_with_timeout(&mut child, timeout)?;
+    // A background process the validator started can hold the pipes open
+    // after it exits, so its output is only waited for until the deadline
+    let output = |pipe: mpsc::Receiver<String>| {
+        let left = deadline.saturating_duration_since(Instant::now());
+        pipe.recv_timeout(left.max(DRAIN_GRACE))
+            .map_err(|_| format!("timed out after {} ms", timeout.as_millis()))
+    };
+    let stdout = output(stdout)?;
+    let stderr = output(stderr)?;
+
+    let rule = rule_name(validator);
+    match status.code() {
+        Some(0) if stdout.trim().is_empty() => Ok(None),
+        Some(0) => {
+            let verdict: ExternalVerdict = serde_json::from_str(stdout.trim())
+                .map_err(|e| format!("invalid JSON on stdout: {}", e))?;
+            verdict.into_decision(&rule, &format!("Rejected by validator {}", validator.name))
+        }
+        Some(2) => {
+            let reason = match stderr.trim() {
+                "" => format!("Rejected by validator {}", validator.name),
+                reason => reason.to_string(),
+            };
+            Ok(Some(Decision::block(rule, reason)))
+        }
+        Some(code) => Err(format!("exited with status {}", code)),
+        None => Err("killed by a signal".to_string()),
+    }
+}
+
+/// Read `pipe` to the end on a thread, sending the output once done.
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> mpsc::Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut output);
+        }
+        let _ = sender.send(output);
+    });
+    receiver
+}
+
+/// Wait for `child`, killing it once `timeout` has passed.
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<ExitStatus, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(status),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {} ms", timeout.as_millis()));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn config(toml: &str) -> CompiledConfig {
+        toml::from_str::<Config>(toml).unwrap().compile().unwrap()
+    }
+
+    fn bash(command: &str) -> HookInput {
+        HookInput::for_tool("Bash", command, None).unwrap()
+    }
+
+    #[test]
+    fn test_exit_code_protocol() {
+        let config = config(
+            r#"
+[[validators]]
+name = "no-deploy"
+command = ["sh", "-c", "if grep -q deploy; then echo 'Deploys need a ticket' >&2; exit 2; fi"]
+"#,
+        );
+        let decision = run_validators(&bash("make deploy"), &config).unwrap();
+        assert!(decision.is_blocked());
+        assert_eq!(decision.rule(), Some("validator.no-deploy"));
+        assert_eq!(decision.reason(), Some("Deploys need a ticket"));
+        assert!(run_validators(&bash("make test"), &config).is_none());
+    }
+
+    #[test]
+    fn test_json_verdict() {
+        let config = config(
+            r#"
+[[validators]]
+name = "policy"
+command = ["sh", "-c", "cat >/dev/null; echo '{\"decision\":\"ask\",\"reason\":\"Confirm\",\"rule\":\"opa.confirm\"}'"]
+tools = ["Bash"]
+"#,
+        );
+        let decision = run_validators(&bash("ls"), &config).unwrap();
+        assert!(decision.is_ask());
+        assert_eq!(decision.rule(), Some("opa.confirm"));
+        // Not configured for Read
+        let read = HookInput::for_tool("Read", "README.md", None).unwrap();
+        assert!(run_validators(&read, &config).is_none());
+    }
+
+    #[test]
+    fn test_fail_mode_on_timeout_and_errors() {
+        let open = config(
+            r#"
+[[validators]]
+name = "slow"
+command = ["sleep", "5"]
+timeout_ms = 50
+"#,
+        );
+        let started = Instant::now();
+        assert!(run_validators(&bash("ls"), &open).is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let closed = config(
+            r#"
+[[validators]]
+name = "broken"
+command = ["sh", "-c", "exit 3"]
+fail_mode = "closed"
+
+[[validators]]
+name = "missing"
+command = ["/nonexistent/validator"]
+fail_mode = "closed"
+"#,
+        );
+        let decision = run_validators(&bash("ls"), &closed).unwrap();
+        assert!(decision.is_blocked());
+        assert_eq!(decision.rule(), Some("validator.broken"));
+        assert!(decision.reason().unwrap().contains("exited with status 3"));
+    }
+
+    #[test]
+    fn test_timeout_bounds_pipes_held_by_background_process() {
+        let config = config(
+            r#"
+[[validators]]
+name = "detached"
+command = ["sh", "-c", "sleep 5 & exit 0"]
+timeout_ms = 200
+fail_mode = "closed"
+"#,
+        );
+        let started = Instant::now();
+        let decision = run_validators(&bash("ls"), &config).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(decision.is_blocked());
+        assert!(
+            decision
+                .reason()
+                .unwrap()
+                .contains("timed out after 200 ms")
+        );
+    }
+
+    #[test]
+    fn test_receives_hook_json() {
+        let config = config(
+            r#"
+[[validators]]
+name = "echo"
+command = ["sh", "-c", "grep -q '\"tool_name\":\"Bash\"' && exit 2; exit 0"]
+"#,
+        );
+        assert!(run_validators(&bash("ls"), &config).unwrap().is_blocked());
+    }
+}
@@ -60,6 +60,7 @@ reason = "Exposes command history"
 fn cmd_with_config(config_file: &NamedTempFile) -> assert_cmd::Command {
     let mut cmd = cargo_bin_cmd!("aca-safety-net");
     cmd.env("ACO_SAFETY_NET_CONFIG", config_file.path());
+    cmd.env("ACO_SAFETY_NET_NO_CACHE", "1");
     cmd
 }
 
@@ -281,6 +282,17 @@ mod should_block {
             .code(2);
     }
 
+    #[test]
+    fn cat_env_with_malformed_timeout() {
+        let cfg = create_config();
+        cmd_with_config(&cfg)
+            .write_stdin(
+                r#"{"tool_name":"Bash","tool_input":{"command":"cat .env","timeout":1.5}}"#,
+            )
+            .assert()
+            .code(2);
+    }
+
     // Sensitive file reads via Read tool
     #[test]
     fn read_env() {
@@ -291,6 +303,15 @@ mod should_block {
             .code(2);
     }
 
+    #[test]
+    fn read_env_with_malformed_offset() {
+        let cfg = create_config();
+        cmd_with_config(&cfg)
+            .write_stdin(r#"{"tool_name":"Read","tool_input":{"file_path":".env","offset":-1}}"#)
+            .assert()
+            .code(2);
+    }
+
     #[test]
     fn read_env_local() {
         let cfg = create_config();